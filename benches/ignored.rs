@@ -0,0 +1,40 @@
+//! Deserializes a document of objects with a large string field, once into a struct that ignores
+//! the field and once into one that reads it, and reports how long each takes.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use serde_derive::Deserialize;
+
+use std::hint::black_box;
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Summary {
+    id: u32,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Article {
+    id: u32,
+    body: String,
+}
+
+fn document(entries: usize) -> String {
+    let body = "Lorem ipsum dolor sit amet,\\n\\tconsectetur \\'adipiscing\\' elit. ".repeat(64);
+    let entry = format!("{{ id: 7, body: '{}' }}", body);
+    format!("[{}]", vec![entry; entries].join(",\n"))
+}
+
+fn ignored(c: &mut Criterion) {
+    let input = document(100);
+
+    c.bench_function("ignored strings", |b| {
+        b.iter(|| json5::from_str::<Vec<Summary>>(black_box(&input)).unwrap())
+    });
+    c.bench_function("deserialized strings", |b| {
+        b.iter(|| json5::from_str::<Vec<Article>>(black_box(&input)).unwrap())
+    });
+}
+
+criterion_group!(benches, ignored);
+criterion_main!(benches);
@@ -0,0 +1,147 @@
+//! Deserializes an array of many objects with the same keys, and measures how many bytes the
+//! result holds on to as well as how long it takes. A `Value` stores every key of every object,
+//! unless the `smol_str` feature is enabled, while structs store none. Running this without the
+//! feature and then with it (`cargo bench --bench keys --features smol_str`) reports the change
+//! in both.
+
+use criterion::measurement::{Measurement, ValueFormatter};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use serde_derive::Deserialize;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct Counting;
+
+static HELD: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        HELD.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        HELD.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+// Measures the bytes still allocated at the end of each iteration, i.e. those held by its result.
+struct BytesHeld;
+
+impl Measurement for BytesHeld {
+    type Intermediate = usize;
+    type Value = usize;
+
+    fn start(&self) -> usize {
+        HELD.load(Ordering::Relaxed)
+    }
+
+    fn end(&self, before: usize) -> usize {
+        HELD.load(Ordering::Relaxed) - before
+    }
+
+    fn add(&self, a: &usize, b: &usize) -> usize {
+        a + b
+    }
+
+    fn zero(&self) -> usize {
+        0
+    }
+
+    fn to_f64(&self, value: &usize) -> f64 {
+        *value as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &BytesFormatter
+    }
+}
+
+struct BytesFormatter;
+
+impl ValueFormatter for BytesFormatter {
+    fn scale_values(&self, typical: f64, values: &mut [f64]) -> &'static str {
+        let (denominator, unit) = if typical < 1024.0 {
+            (1.0, "  B")
+        } else if typical < 1024.0 * 1024.0 {
+            (1024.0, "KiB")
+        } else {
+            (1024.0 * 1024.0, "MiB")
+        };
+        values.iter_mut().for_each(|v| *v /= denominator);
+        unit
+    }
+
+    fn scale_throughputs(&self, _: f64, _: &Throughput, _: &mut [f64]) -> &'static str {
+        "B"
+    }
+
+    fn scale_for_machines(&self, _: &mut [f64]) -> &'static str {
+        "B"
+    }
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Point {
+    identifier: u32,
+    latitude: f64,
+    longitude: f64,
+    description: String,
+    last_modification_time: u64,
+}
+
+fn document(objects: usize) -> String {
+    let object = "{ identifier: 1, latitude: 51.5, longitude: -0.1, description: 'x', \
+                  last_modification_time: 1700000000 }";
+    format!("[{}]", vec![object; objects].join(",\n"))
+}
+
+// Runs `f` `iters` times, dropping each result before the next, and adds up the bytes each held.
+fn held<T, F: Fn() -> T>(iters: u64, f: F) -> usize {
+    (0..iters)
+        .map(|_| {
+            let before = BytesHeld.start();
+            let result = black_box(f());
+            let held = BytesHeld.end(before);
+            drop(result);
+            held
+        })
+        .sum()
+}
+
+fn memory(c: &mut Criterion<BytesHeld>) {
+    let input = document(10_000);
+
+    c.bench_function("memory of uniform objects as Value", |b| {
+        b.iter_custom(|iters| held(iters, || json5::from_str::<json5::Value>(&input).unwrap()))
+    });
+    c.bench_function("memory of uniform objects as structs", |b| {
+        b.iter_custom(|iters| held(iters, || json5::from_str::<Vec<Point>>(&input).unwrap()))
+    });
+}
+
+fn time(c: &mut Criterion) {
+    let input = document(10_000);
+
+    c.bench_function("uniform objects as Value", |b| {
+        b.iter(|| json5::from_str::<json5::Value>(black_box(&input)).unwrap())
+    });
+    c.bench_function("uniform objects as structs", |b| {
+        b.iter(|| json5::from_str::<Vec<Point>>(black_box(&input)).unwrap())
+    });
+}
+
+criterion_group! {
+    name = memory_benches;
+    config = Criterion::default().with_measurement(BytesHeld).sample_size(10).without_plots();
+    targets = memory
+}
+criterion_group!(time_benches, time);
+criterion_main!(memory_benches, time_benches);
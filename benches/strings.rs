@@ -0,0 +1,78 @@
+//! Deserializes a document made up mostly of short strings, some with escapes, and reports how
+//! many allocations that takes as well as how long.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use serde_derive::Deserialize;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Entry<'a> {
+    name: &'a str,
+    #[serde(borrow)]
+    tags: [&'a str; 3],
+    note: String,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct OwnedEntry {
+    name: String,
+    tags: [String; 3],
+    note: String,
+}
+
+fn document(entries: usize) -> String {
+    let entry = "{ name: 'widget', tags: ['a', \"bb\", 'ccc'], note: 'tab\\there' }";
+    format!("[{}]", vec![entry; entries].join(",\n"))
+}
+
+fn allocations<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn strings(c: &mut Criterion) {
+    let input = document(100);
+
+    let borrowed = allocations(|| {
+        black_box(json5::from_str::<Vec<Entry<'_>>>(&input).unwrap());
+    });
+    let owned = allocations(|| {
+        black_box(json5::from_str::<Vec<OwnedEntry>>(&input).unwrap());
+    });
+    println!("allocations, borrowed strings: {}", borrowed);
+    println!("allocations, owned strings: {}", owned);
+
+    c.bench_function("borrowed strings", |b| {
+        b.iter(|| json5::from_str::<Vec<Entry<'_>>>(black_box(&input)).unwrap())
+    });
+    c.bench_function("owned strings", |b| {
+        b.iter(|| json5::from_str::<Vec<OwnedEntry>>(black_box(&input)).unwrap())
+    });
+}
+
+criterion_group!(benches, strings);
+criterion_main!(benches);
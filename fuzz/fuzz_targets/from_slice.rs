@@ -0,0 +1,79 @@
+#![no_main]
+
+// Runs arbitrary bytes through everything that reads JSON5, throwing away the results: any panic
+// is a bug. Start it from the checked-in corpus with
+//
+//     cargo fuzz run from_slice corpus/from_slice
+//
+// and copy anything it finds into that corpus, which `tests/fuzz.rs` replays on every test run.
+
+use std::collections::HashMap;
+
+use json5::{DeserializeOptions, Value};
+use libfuzzer_sys::fuzz_target;
+use serde_derive::Deserialize;
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+enum Enum {
+    Unit,
+    Newtype(i8),
+    Tuple(u16, char),
+    Struct { a: Option<f32> },
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+struct Struct {
+    a: Vec<u8>,
+    b: (i64, String),
+    c: HashMap<u32, Enum>,
+    d: Box<[bool]>,
+}
+
+fuzz_target!(|data: &[u8]| {
+    let lenient = DeserializeOptions::new()
+        .allow_octal_and_binary(true)
+        .allow_underscores_in_numbers(true)
+        .null_as_empty_collection(true)
+        .tuples_from_objects(true)
+        .recursion_limit(16);
+    let strict = DeserializeOptions::new()
+        .deny_duplicate_keys(true)
+        .deny_unknown_fields(true);
+    let _ = json5::from_slice::<Value>(data);
+    let _ = json5::from_slice::<Struct>(data);
+    let _ = json5::from_slice::<Vec<Enum>>(data);
+    let _ = lenient.from_slice::<Struct>(data);
+    let _ = lenient.from_slice::<HashMap<String, Vec<String>>>(data);
+    let _ = strict.from_slice::<Struct>(data);
+    let _ = strict.from_slice::<Value>(data);
+
+    let s = match std::str::from_utf8(data) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    let _ = json5::from_str_with_warnings::<Value>(s);
+    let _ = json5::from_str_at::<Value>(s, "a[0].b");
+    let _ = json5::validate(s);
+    if let Err(err) = json5::from_str::<Struct>(s) {
+        let _ = err.to_string();
+        let _ = err.display_with_source(s).to_string();
+    }
+    let _ = json5::StreamDeserializer::<Value>::new(s).take(8).count();
+    let _ = strict.stream::<Value>(s).take(8).count();
+    if let Ok(events) = json5::events(s) {
+        let _ = events.count();
+    }
+    if let Ok(doc) = json5::parse(s) {
+        let _ = doc.attached_comments();
+        let _ = doc.root().to_spanned_value();
+        let _ = doc.root().leading_comments();
+        let _ = doc.root().trailing_comment();
+    }
+    if let Ok(value) = json5::from_str::<Value>(s) {
+        let _ = json5::to_string(&value);
+        let _ = value.pointer("/a/0");
+        let _ = json5::from_value::<Struct>(value);
+    }
+});
@@ -1,67 +1,1063 @@
+use alloc::borrow::Cow;
+use alloc::collections::{BTreeSet, VecDeque};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::char;
+use core::convert::TryFrom;
+use core::f64;
+use core::marker::PhantomData;
+use core::str::{self, FromStr};
+use pest::error::{ErrorVariant, InputLocation};
 use pest::iterators::Pair;
 use pest::Parser as P;
-use pest_derive::Parser;
+use pest::{Position, Span};
 use serde::de;
 use serde::forward_to_deserialize_any;
-use std::char;
-use std::collections::VecDeque;
-use std::f64;
+#[cfg(feature = "std")]
+use std::io::{self, Read};
 
-use crate::error::{Error, Result};
+use crate::error::{self, Error, Location, Result, ValueKind};
+use crate::parse::PathSegment;
+use crate::warning::{warnings, Warning, WarningKind};
 
-#[derive(Parser)]
-#[grammar = "json5.pest"]
-struct Parser;
+use self::grammar::Parser;
+pub use self::grammar::Rule;
+
+pub(crate) mod grammar {
+    // The generated `Rule` enum has a variant for every rule in the grammar, which aren't worth
+    // documenting individually.
+    #![allow(missing_docs)]
+
+    use pest_derive::Parser;
+
+    /// The parser for the JSON5 grammar, generated by pest. Use it through `pest::Parser`.
+    #[derive(Parser)]
+    #[grammar = "json5.pest"]
+    pub struct Parser;
+}
 
 /// Deserialize an instance of type `T` from a string of JSON5 text. Can fail if the input is
 /// invalid JSON5, or doesn&rsquo;t match the structure of the target type.
+///
+/// The input is parsed where it is and never copied as a whole, so for a very large document it
+/// can be borrowed from a memory-mapped file (e.g. with [`from_slice`][] on the mapped bytes)
+/// rather than read into a `String`. Strings without escapes can also be borrowed from it by
+/// deserializing into `&str`. Memory use is then mostly the parse tree, which grows with the
+/// number of values in the document rather than its size in bytes.
+///
+/// [`from_slice`]: fn.from_slice.html
 pub fn from_str<'a, T>(s: &'a str) -> Result<T>
 where
     T: de::Deserialize<'a>,
 {
-    let mut deserializer = Deserializer::from_str(s)?;
-    T::deserialize(&mut deserializer)
+    DeserializeOptions::new().from_str(s)
+}
+
+/// Deserialize an instance of type `T` from a string of JSON5 text as [`from_str`][] does, but
+/// accepting the extensions to JSON5 that the deserializer can allow (octal and binary literals,
+/// and underscores in numbers) and returning a [`Warning`][] for each use of one, in the order
+/// they appear. This suits tools that read a document leniently but want to flag what a strict
+/// parser would reject. [`DeserializeOptions::from_str_with_warnings`][] does the same with only
+/// the chosen extensions allowed.
+///
+/// ```rust
+/// let (sizes, warnings) = json5::from_str_with_warnings::<Vec<u32>>("[1_024, 0o755, 8]").unwrap();
+/// assert_eq!(sizes, vec![1024, 0o755, 8]);
+/// assert_eq!(warnings.len(), 2);
+/// assert_eq!(warnings[1].to_string(), "octal literals are not JSON5, used at line 1 column 9");
+/// ```
+///
+/// [`from_str`]: fn.from_str.html
+/// [`Warning`]: struct.Warning.html
+/// [`DeserializeOptions::from_str_with_warnings`]: struct.DeserializeOptions.html#method.from_str_with_warnings
+pub fn from_str_with_warnings<'a, T>(s: &'a str) -> Result<(T, Vec<Warning>)>
+where
+    T: de::Deserialize<'a>,
+{
+    DeserializeOptions::new()
+        .allow_octal_and_binary(true)
+        .allow_underscores_in_numbers(true)
+        .from_str_with_warnings(s)
+}
+
+/// Deserialize an instance of type `T` from the value at `path` in a string of JSON5 text, for when
+/// only one part of a large document is wanted. The whole document is still checked for syntax
+/// errors and disabled extensions (as by [`from_str`][]), but nothing outside the value at the
+/// path is deserialized.
+///
+/// A path is a chain of object keys and array indices, as in `servers[0].port`. Keys are separated
+/// by `.`, and a key containing `.`, `[` or `]` can be written in quotes in brackets instead, as in
+/// `servers[0]['host.name']`. An empty path is the whole document. If an object has a key more
+/// than once, the last entry is used. Fails with [`Error::PathNotFound`][] if there is no value at
+/// the path.
+///
+/// ```rust
+/// let input = "{ servers: [{ host: 'a', port: 80 }, { host: 'b', port: 8080 }] }";
+/// assert_eq!(json5::from_str_at::<u16>(input, "servers[1].port"), Ok(8080));
+/// assert_eq!(
+///     json5::from_str_at::<u16>(input, "servers[2].port"),
+///     Err(json5::Error::PathNotFound { path: "servers[2]".to_owned() }),
+/// );
+/// ```
+///
+/// [`from_str`]: fn.from_str.html
+/// [`Error::PathNotFound`]: enum.Error.html#variant.PathNotFound
+pub fn from_str_at<'a, T>(s: &'a str, path: &str) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    DeserializeOptions::new().from_str_at(s, path)
+}
+
+/// Deserialize an instance of type `T` from a string of JSON5 text as [`from_str`][] does, but
+/// failing with [`Error::InputTooLarge`][] without parsing anything if the input is longer than
+/// `max_bytes`. Together with the recursion limit this bounds the work done for untrusted input;
+/// use [`DeserializeOptions::max_input_size`][] to combine it with other options.
+///
+/// ```rust
+/// assert_eq!(json5::from_str_limited::<Vec<u8>>("[1, 2]", 16), Ok(vec![1, 2]));
+/// assert_eq!(
+///     json5::from_str_limited::<Vec<u8>>("[1, 2, 3, 4, 5, 6]", 16),
+///     Err(json5::Error::InputTooLarge { limit: 16 }),
+/// );
+/// ```
+///
+/// [`from_str`]: fn.from_str.html
+/// [`Error::InputTooLarge`]: enum.Error.html#variant.InputTooLarge
+/// [`DeserializeOptions::max_input_size`]: struct.DeserializeOptions.html#method.max_input_size
+pub fn from_str_limited<'a, T>(s: &'a str, max_bytes: usize) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    DeserializeOptions::new()
+        .max_input_size(max_bytes)
+        .from_str(s)
+}
+
+/// Deserialize an instance of type `T` from bytes of JSON5 text. Can fail if the input is not
+/// valid UTF-8, and otherwise as for [`from_str`][].
+///
+/// [`from_str`]: fn.from_str.html
+pub fn from_slice<'a, T>(v: &'a [u8]) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    DeserializeOptions::new().from_slice(v)
+}
+
+/// Deserialize an instance of type `T` from a reader of JSON5 text. The parser needs the whole
+/// document at once, so this reads everything up to EOF into memory before parsing. Can fail with
+/// [`Error::Io`][] if reading fails, and otherwise as for [`from_slice`][].
+///
+/// [`Error::Io`]: enum.Error.html#variant.Io
+/// [`from_slice`]: fn.from_slice.html
+#[cfg(feature = "std")]
+pub fn from_reader<R, T>(reader: R) -> Result<T>
+where
+    R: io::Read,
+    T: de::DeserializeOwned,
+{
+    DeserializeOptions::new().from_reader(reader)
+}
+
+/// Options for reading JSON5 more leniently or more strictly than [`from_str`][] and friends do.
+///
+/// ```rust
+/// use std::collections::HashMap;
+///
+/// let options = json5::DeserializeOptions::new()
+///     .deny_duplicate_keys(true)
+///     .allow_octal_and_binary(true);
+///
+/// let modes: HashMap<String, u32> = options.from_str("{ dir: 0o755, file: 0o644 }").unwrap();
+/// assert_eq!(modes["dir"], 0o755);
+/// assert!(options.from_str::<HashMap<String, u32>>("{ a: 1, a: 2 }").is_err());
+/// ```
+///
+/// [`from_str`]: fn.from_str.html
+#[derive(Clone, Debug)]
+pub struct DeserializeOptions {
+    settings: Settings,
+    recursion_limit: usize,
+    max_input_size: Option<usize>,
+}
+
+impl Default for DeserializeOptions {
+    fn default() -> Self {
+        DeserializeOptions {
+            settings: Settings::default(),
+            recursion_limit: DEFAULT_RECURSION_LIMIT,
+            max_input_size: None,
+        }
+    }
+}
+
+impl DeserializeOptions {
+    /// Creates the default options, which are the same as those used by [`from_str`][].
+    ///
+    /// [`from_str`]: fn.from_str.html
+    pub fn new() -> Self {
+        DeserializeOptions::default()
+    }
+
+    /// Sets whether an object having the same key more than once is an error, as for
+    /// [`Deserializer::deny_duplicate_keys`][]. Defaults to `false`.
+    ///
+    /// [`Deserializer::deny_duplicate_keys`]: struct.Deserializer.html#method.deny_duplicate_keys
+    pub fn deny_duplicate_keys(mut self, deny: bool) -> Self {
+        self.settings.deny_duplicate_keys = deny;
+        self
+    }
+
+    /// Sets whether an object having a key that isn&rsquo;t a field of the struct it&rsquo;s
+    /// deserialized into is an error, as for [`Deserializer::deny_unknown_fields`][]. Defaults to
+    /// `false`.
+    ///
+    /// [`Deserializer::deny_unknown_fields`]: struct.Deserializer.html#method.deny_unknown_fields
+    pub fn deny_unknown_fields(mut self, deny: bool) -> Self {
+        self.settings.deny_unknown_fields = deny;
+        self
+    }
+
+    /// Sets whether to accept integers written in octal or binary, as for
+    /// [`Deserializer::allow_octal_and_binary`][]. Defaults to `false`.
+    ///
+    /// [`Deserializer::allow_octal_and_binary`]: struct.Deserializer.html#method.allow_octal_and_binary
+    pub fn allow_octal_and_binary(mut self, allow: bool) -> Self {
+        self.settings.allow_octal_and_binary = allow;
+        self
+    }
+
+    /// Sets whether to accept underscores between the digits of numbers, as for
+    /// [`Deserializer::allow_underscores_in_numbers`][]. Defaults to `false`.
+    ///
+    /// [`Deserializer::allow_underscores_in_numbers`]: struct.Deserializer.html#method.allow_underscores_in_numbers
+    pub fn allow_underscores_in_numbers(mut self, allow: bool) -> Self {
+        self.settings.allow_underscores_in_numbers = allow;
+        self
+    }
+
+    /// Sets whether `null` is accepted as an empty sequence or map, as for
+    /// [`Deserializer::null_as_empty_collection`][]. Defaults to `false`.
+    ///
+    /// [`Deserializer::null_as_empty_collection`]: struct.Deserializer.html#method.null_as_empty_collection
+    pub fn null_as_empty_collection(mut self, allow: bool) -> Self {
+        self.settings.null_as_empty_collection = allow;
+        self
+    }
+
+    /// Sets how deeply arrays and objects can be nested, failing with
+    /// [`Error::RecursionLimitExceeded`][] for input nested any deeper. Defaults to 128, which is
+    /// also the limit everywhere else input is parsed.
+    ///
+    /// Parsing and deserializing recurse once per level of nesting, so without a limit a small but
+    /// deeply nested input such as `[[[[…]]]]` could overflow the stack. A higher limit needs a
+    /// correspondingly bigger stack.
+    ///
+    /// ```rust
+    /// let nested = format!("{}{}", "[".repeat(200), "]".repeat(200));
+    /// assert!(json5::from_str::<json5::Value>(&nested).is_err());
+    ///
+    /// let options = json5::DeserializeOptions::new().recursion_limit(256);
+    /// assert!(options.from_str::<json5::Value>(&nested).is_ok());
+    /// ```
+    ///
+    /// [`Error::RecursionLimitExceeded`]: enum.Error.html#variant.RecursionLimitExceeded
+    pub fn recursion_limit(mut self, limit: usize) -> Self {
+        self.recursion_limit = limit;
+        self
+    }
+
+    /// Sets the longest input, in bytes, that will be parsed, failing with
+    /// [`Error::InputTooLarge`][] for anything longer. There is no limit by default.
+    ///
+    /// The length is checked before parsing (and [`from_reader`][] stops reading just past it),
+    /// so with this and the [recursion limit][] untrusted input can only cost a bounded amount of
+    /// time and memory.
+    ///
+    /// ```rust
+    /// let options = json5::DeserializeOptions::new()
+    ///     .max_input_size(1024)
+    ///     .recursion_limit(8);
+    /// assert!(options.from_str::<json5::Value>(&"0".repeat(2048)).is_err());
+    /// assert!(options.from_str::<json5::Value>("[[[[[[[[[]]]]]]]]]").is_err());
+    /// assert!(options.from_str::<json5::Value>("{ a: [1, 2, 3] }").is_ok());
+    /// ```
+    ///
+    /// [`Error::InputTooLarge`]: enum.Error.html#variant.InputTooLarge
+    /// [`from_reader`]: #method.from_reader
+    /// [recursion limit]: #method.recursion_limit
+    pub fn max_input_size(mut self, max_bytes: usize) -> Self {
+        self.max_input_size = Some(max_bytes);
+        self
+    }
+
+    /// Sets whether a tuple can also be read from an object with the keys `"0"`, `"1"` and so on,
+    /// as for [`Deserializer::tuples_from_objects`][]. Defaults to `false`.
+    ///
+    /// [`Deserializer::tuples_from_objects`]: struct.Deserializer.html#method.tuples_from_objects
+    pub fn tuples_from_objects(mut self, allow: bool) -> Self {
+        self.settings.tuples_from_objects = allow;
+        self
+    }
+
+    /// Deserialize an instance of type `T` from a string of JSON5 text, as [`from_str`][] does but
+    /// with these options.
+    ///
+    /// [`from_str`]: fn.from_str.html
+    pub fn from_str<'a, T>(&self, s: &'a str) -> Result<T>
+    where
+        T: de::Deserialize<'a>,
+    {
+        let pair = self.parse(s)?;
+        T::deserialize(&mut Deserializer::from_pair(pair, self.settings))
+    }
+
+    /// Deserialize an instance of type `T` from a string of JSON5 text with these options, also
+    /// returning a [`Warning`][] for each use of an extension to JSON5 in the input, in the order
+    /// they appear. Fails as [`from_str`][] does, including for uses of extensions that
+    /// aren&rsquo;t enabled.
+    ///
+    /// ```rust
+    /// use json5::WarningKind;
+    ///
+    /// let options = json5::DeserializeOptions::new()
+    ///     .allow_octal_and_binary(true)
+    ///     .allow_underscores_in_numbers(true);
+    ///
+    /// let (sizes, warnings) = options
+    ///     .from_str_with_warnings::<Vec<u32>>("[1_024, 0o755, 8]")
+    ///     .unwrap();
+    /// assert_eq!(sizes, vec![1024, 0o755, 8]);
+    /// let kinds: Vec<_> = warnings.iter().map(|w| w.kind).collect();
+    /// assert_eq!(kinds, [WarningKind::UnderscoresInNumber, WarningKind::OctalLiteral]);
+    /// assert_eq!(warnings[1].to_string(), "octal literals are not JSON5, used at line 1 column 9");
+    /// ```
+    ///
+    /// [`Warning`]: struct.Warning.html
+    /// [`from_str`]: fn.from_str.html
+    pub fn from_str_with_warnings<'a, T>(&self, s: &'a str) -> Result<(T, Vec<Warning>)>
+    where
+        T: de::Deserialize<'a>,
+    {
+        let pair = self.parse(s)?;
+        let warnings = warnings(&pair);
+        let value = T::deserialize(&mut Deserializer::from_pair(pair, self.settings))?;
+        Ok((value, warnings))
+    }
+
+    /// Deserialize an instance of type `T` from the value at `path` in a string of JSON5 text, as
+    /// [`from_str_at`][] does but with these options.
+    ///
+    /// [`from_str_at`]: fn.from_str_at.html
+    pub fn from_str_at<'a, T>(&self, s: &'a str, path: &str) -> Result<T>
+    where
+        T: de::Deserialize<'a>,
+    {
+        let mut pair = self.parse(s)?;
+        self.settings.check_skipped(&pair)?;
+        for (segment, end) in parse_path(path)? {
+            pair = select(pair, &segment)?.ok_or_else(|| Error::PathNotFound {
+                path: path[..end].into(),
+            })?;
+        }
+        T::deserialize(&mut Deserializer::from_pair(pair, self.settings))
+    }
+
+    /// Deserialize an instance of type `T` from bytes of JSON5 text, as [`from_slice`][] does but
+    /// with these options.
+    ///
+    /// [`from_slice`]: fn.from_slice.html
+    pub fn from_slice<'a, T>(&self, v: &'a [u8]) -> Result<T>
+    where
+        T: de::Deserialize<'a>,
+    {
+        self.check_size(v.len())?;
+        self.from_str(str::from_utf8(v)?)
+    }
+
+    /// Deserialize an instance of type `T` from a reader of JSON5 text, as [`from_reader`][] does
+    /// but with these options.
+    ///
+    /// [`from_reader`]: fn.from_reader.html
+    #[cfg(feature = "std")]
+    pub fn from_reader<R, T>(&self, mut reader: R) -> Result<T>
+    where
+        R: io::Read,
+        T: de::DeserializeOwned,
+    {
+        let mut bytes = Vec::new();
+        match self.max_input_size {
+            // One byte more than the limit is enough to know the input is too long.
+            Some(max) => reader
+                .take((max as u64).saturating_add(1))
+                .read_to_end(&mut bytes)?,
+            None => reader.read_to_end(&mut bytes)?,
+        };
+        self.from_slice(&bytes)
+    }
+
+    /// Creates a stream of JSON5 documents from a `&str`, as [`StreamDeserializer::new`][] does
+    /// but reading each with these options. The size and nesting limits apply to the input as a
+    /// whole, and are checked before the first document is read.
+    ///
+    /// ```rust
+    /// let options = json5::DeserializeOptions::new().allow_octal_and_binary(true);
+    /// let modes = options
+    ///     .stream::<u32>("0o755\n0o644\n")
+    ///     .collect::<json5::Result<Vec<_>>>();
+    /// assert_eq!(modes, Ok(vec![0o755, 0o644]));
+    ///
+    /// let options = json5::DeserializeOptions::new().max_input_size(8);
+    /// let mut stream = options.stream::<u32>("1\n2\n3\n4\n5\n");
+    /// assert_eq!(stream.next(), Some(Err(json5::Error::InputTooLarge { limit: 8 })));
+    /// assert_eq!(stream.next(), None);
+    /// ```
+    ///
+    /// [`StreamDeserializer::new`]: struct.StreamDeserializer.html#method.new
+    pub fn stream<'a, T>(&self, s: &'a str) -> StreamDeserializer<'a, T> {
+        StreamDeserializer {
+            input: s,
+            offset: 0,
+            failed: false,
+            options: self.clone(),
+            output: PhantomData,
+        }
+    }
+
+    fn check_size(&self, len: usize) -> Result<()> {
+        match self.max_input_size {
+            Some(limit) if len > limit => Err(Error::InputTooLarge { limit }),
+            _ => Ok(()),
+        }
+    }
+
+    fn parse<'a>(&self, s: &'a str) -> Result<Pair<'a, Rule>> {
+        self.check_size(s.len())?;
+        parse_text(s, self.recursion_limit)
+    }
 }
 
-struct Deserializer<'de> {
+/// A deserializer for a single JSON5 document. Most of the time [`from_str`][] is more convenient,
+/// but this can be used directly with `Deserialize::deserialize`.
+///
+/// ```rust
+/// use serde::Deserialize;
+///
+/// let mut deserializer = json5::Deserializer::from_str("[1, 2, 3]").unwrap();
+/// assert_eq!(Vec::<i32>::deserialize(&mut deserializer), Ok(vec![1, 2, 3]));
+/// ```
+///
+/// [`from_str`]: fn.from_str.html
+pub struct Deserializer<'de> {
     pair: Option<Pair<'de, Rule>>,
+    settings: Settings,
+}
+
+/// The behaviour that can be toggled on a [`Deserializer`][], passed down to the deserializers of
+/// nested values.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Settings {
+    deny_duplicate_keys: bool,
+    deny_unknown_fields: bool,
+    allow_octal_and_binary: bool,
+    allow_underscores_in_numbers: bool,
+    null_as_empty_collection: bool,
+    tuples_from_objects: bool,
+}
+
+impl Settings {
+    // Numbers in octal or binary are always parsed, so that they can be reported with a clear
+    // error rather than a parse error, but only accepted if enabled.
+    // The same goes for underscores, which are parsed anywhere after the first digit.
+    pub(crate) fn check_number(&self, pair: &Pair<'_, Rule>) -> Result<()> {
+        let s = pair.as_str();
+        let radix = radix_of_literal(split_sign(s).1);
+        let disabled = |kind| {
+            let (location, span) = locate(&pair.as_span());
+            Err(Error::DisabledExtension {
+                kind,
+                location,
+                span,
+            })
+        };
+        if s.contains('_') {
+            if !self.allow_underscores_in_numbers {
+                return disabled(WarningKind::UnderscoresInNumber);
+            }
+            let is_digit = |c: Option<char>| c.is_some_and(|c| c.is_digit(radix));
+            let misplaced = s.match_indices('_').any(|(i, _)| {
+                !is_digit(s[..i].chars().next_back()) || !is_digit(s[i + 1..].chars().next())
+            });
+            if misplaced {
+                let (location, span) = locate(&pair.as_span());
+                return Err(Error::MisplacedUnderscore { location, span });
+            }
+        }
+        match radix {
+            8 if !self.allow_octal_and_binary => disabled(WarningKind::OctalLiteral),
+            2 if !self.allow_octal_and_binary => disabled(WarningKind::BinaryLiteral),
+            _ => Ok(()),
+        }
+    }
+
+    // A value the `Deserialize` implementation skips is still part of the document, so its
+    // numbers and escapes are checked as they would be if it were deserialized, in the same way
+    // `warnings` finds them. Only `\u` escapes can be invalid, so other strings aren't decoded.
+    pub(crate) fn check_skipped(&self, pair: &Pair<'_, Rule>) -> Result<()> {
+        core::iter::once(pair.clone())
+            .chain(pair.clone().into_inner().flatten())
+            .try_for_each(|pair| match pair.as_rule() {
+                Rule::number => self.check_number(&pair),
+                Rule::string | Rule::identifier if pair.as_str().contains("\\u") => {
+                    parse_string(pair).map(drop)
+                }
+                _ => Ok(()),
+            })
+    }
 }
 
 impl<'de> Deserializer<'de> {
     /// Creates a JSON5 deserializer from a `&str`. This parses the input at construction time, so
-    /// can fail if the input is not valid JSON5.
-    fn from_str(input: &'de str) -> Result<Self> {
-        let pair = Parser::parse(Rule::text, input)?.next().unwrap();
-        Ok(Deserializer::from_pair(pair))
+    /// can fail if the input is not valid JSON5 or is nested more deeply than the default
+    /// [`DeserializeOptions::recursion_limit`][].
+    ///
+    /// [`DeserializeOptions::recursion_limit`]: struct.DeserializeOptions.html#method.recursion_limit
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(input: &'de str) -> Result<Self> {
+        Ok(Deserializer::from_pair(
+            parse_text(input, DEFAULT_RECURSION_LIMIT)?,
+            Settings::default(),
+        ))
+    }
+
+    /// Sets whether to fail with [`Error::DuplicateKey`][] if an object has the same key more than
+    /// once (after resolving escapes), rather than passing every entry on to the `Deserialize`
+    /// implementation. Defaults to `false`.
+    ///
+    /// ```rust
+    /// use serde::Deserialize;
+    /// use std::collections::HashMap;
+    ///
+    /// let deserializer = json5::Deserializer::from_str("{ a: 1, 'a': 2 }").unwrap();
+    /// assert_eq!(
+    ///     HashMap::<String, i32>::deserialize(&mut deserializer.deny_duplicate_keys(true)),
+    ///     Err(json5::Error::DuplicateKey("a".to_owned())),
+    /// );
+    /// ```
+    ///
+    /// [`Error::DuplicateKey`]: enum.Error.html#variant.DuplicateKey
+    pub fn deny_duplicate_keys(mut self, deny: bool) -> Self {
+        self.settings.deny_duplicate_keys = deny;
+        self
     }
 
-    fn from_pair(pair: Pair<'de, Rule>) -> Self {
-        Deserializer { pair: Some(pair) }
+    /// Sets whether to fail with [`Error::UnknownField`][] if an object deserialized into a struct
+    /// (or a struct variant) has a key that isn&rsquo;t one of its fields, as
+    /// `#[serde(deny_unknown_fields)]` does but for every struct, including those from other
+    /// crates. Defaults to `false`, in which case unknown keys are skipped.
+    ///
+    /// ```rust
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct Config {
+    ///     port: u16,
+    ///     #[serde(default)]
+    ///     verbose: bool,
+    /// }
+    ///
+    /// let deserializer = json5::Deserializer::from_str("{ port: 80, verbsoe: true }").unwrap();
+    /// assert_eq!(
+    ///     Config::deserialize(&mut deserializer.deny_unknown_fields(true)).unwrap_err(),
+    ///     json5::Error::UnknownField {
+    ///         field: "verbsoe".to_owned(),
+    ///         expected: &["port", "verbose"],
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// [`Error::UnknownField`]: enum.Error.html#variant.UnknownField
+    pub fn deny_unknown_fields(mut self, deny: bool) -> Self {
+        self.settings.deny_unknown_fields = deny;
+        self
+    }
+
+    /// Sets whether to accept integers written in octal (`0o777`) or binary (`0b1010`), which
+    /// aren&rsquo;t part of JSON5. Defaults to `false`, in which case they are an error.
+    ///
+    /// ```rust
+    /// use serde::Deserialize;
+    ///
+    /// let deserializer = json5::Deserializer::from_str("[0o755, 0b1010]").unwrap();
+    /// assert_eq!(
+    ///     Vec::<u32>::deserialize(&mut deserializer.allow_octal_and_binary(true)),
+    ///     Ok(vec![0o755, 0b1010]),
+    /// );
+    /// assert!(json5::from_str::<Vec<u32>>("[0o755, 0b1010]").is_err());
+    /// ```
+    pub fn allow_octal_and_binary(mut self, allow: bool) -> Self {
+        self.settings.allow_octal_and_binary = allow;
+        self
+    }
+
+    /// Sets whether to accept underscores as digit separators in numbers (`1_000_000`), which
+    /// aren&rsquo;t part of JSON5. Each underscore has to be between two digits, so `1_`, `1__0`
+    /// and `0x_FF` are still an [`Error::MisplacedUnderscore`][], and `_1` isn&rsquo;t a number at
+    /// all. Defaults to `false`, in which case any underscore is an error.
+    ///
+    /// ```rust
+    /// use serde::Deserialize;
+    ///
+    /// let deserializer = json5::Deserializer::from_str("[1_000_000, 0xFF_FF, 1.5e1_0]").unwrap();
+    /// assert_eq!(
+    ///     Vec::<f64>::deserialize(&mut deserializer.allow_underscores_in_numbers(true)),
+    ///     Ok(vec![1_000_000., 65535., 1.5e10]),
+    /// );
+    /// assert!(json5::from_str::<u32>("1_000").is_err());
+    /// ```
+    ///
+    /// [`Error::MisplacedUnderscore`]: enum.Error.html#variant.MisplacedUnderscore
+    pub fn allow_underscores_in_numbers(mut self, allow: bool) -> Self {
+        self.settings.allow_underscores_in_numbers = allow;
+        self
+    }
+
+    /// Sets whether `null` is accepted where a sequence or map is wanted (such as for a `Vec` or
+    /// `HashMap`), as if it were `[]` or `{}`, for lenient configs that write `null` for nothing.
+    /// Defaults to `false`, in which case it is an error. Either way `null` is `None` for an
+    /// `Option` of a collection.
+    ///
+    /// ```rust
+    /// use serde::Deserialize;
+    /// use std::collections::HashMap;
+    ///
+    /// #[derive(Deserialize, Debug, PartialEq)]
+    /// struct Config {
+    ///     hosts: Vec<String>,
+    ///     env: HashMap<String, String>,
+    /// }
+    ///
+    /// let deserializer = json5::Deserializer::from_str("{ hosts: null, env: null }").unwrap();
+    /// assert_eq!(
+    ///     Config::deserialize(&mut deserializer.null_as_empty_collection(true)),
+    ///     Ok(Config { hosts: vec![], env: HashMap::new() }),
+    /// );
+    /// assert!(json5::from_str::<Config>("{ hosts: null, env: null }").is_err());
+    /// ```
+    pub fn null_as_empty_collection(mut self, allow: bool) -> Self {
+        self.settings.null_as_empty_collection = allow;
+        self
+    }
+
+    /// Sets whether a tuple (or tuple struct) can also be read from an object whose keys are its
+    /// indices in order, as in `{ "0": 'a', "1": 2 }`, which some JavaScript libraries write for
+    /// tuples. Defaults to `false`, in which case only an array will do. Either way every element
+    /// must be there, and with this enabled a missing or out of order key is an error.
+    ///
+    /// ```rust
+    /// use serde::Deserialize;
+    ///
+    /// let deserializer = json5::Deserializer::from_str("{ '0': 'x', '1': 2 }").unwrap();
+    /// assert_eq!(
+    ///     <(String, i32)>::deserialize(&mut deserializer.tuples_from_objects(true)),
+    ///     Ok(("x".to_owned(), 2)),
+    /// );
+    /// assert!(json5::from_str::<(String, i32)>("{ '0': 'x', '1': 2 }").is_err());
+    /// ```
+    pub fn tuples_from_objects(mut self, allow: bool) -> Self {
+        self.settings.tuples_from_objects = allow;
+        self
+    }
+
+    // For a value of the wrong kind, where there isn't a more specific error.
+    fn type_mismatch(&mut self, expected: ValueKind) -> Error {
+        let pair = match self.take_raw() {
+            Ok(pair) => pair,
+            Err(err) => return err,
+        };
+        let (location, span) = locate(&pair.as_span());
+        Error::TypeMismatch {
+            expected,
+            found: kind_of(&pair),
+            location,
+            span,
+        }
+    }
+
+    // Takes a `null` pair if it stands for an empty collection.
+    fn take_null_as_empty(&mut self) -> bool {
+        let is_null = self.peek_rule() == Ok(Rule::null);
+        if is_null && self.settings.null_as_empty_collection {
+            self.pair.take();
+            return true;
+        }
+        false
+    }
+
+    // The pair is taken by whatever deserializes it, so a `Deserializer` that is used again
+    // afterwards has nothing left to give.
+    fn peek_rule(&self) -> Result<Rule> {
+        self.pair
+            .as_ref()
+            .map(Pair::as_rule)
+            .ok_or_else(already_deserialized)
+    }
+
+    fn take_raw(&mut self) -> Result<Pair<'de, Rule>> {
+        self.pair.take().ok_or_else(already_deserialized)
+    }
+
+    fn take_pair(&mut self) -> Result<Pair<'de, Rule>> {
+        let pair = self.take_raw()?;
+        if pair.as_rule() == Rule::number {
+            self.settings.check_number(&pair)?;
+        }
+        Ok(pair)
+    }
+
+    // Takes the pair for a number, so that anything else is reported as such rather than as a
+    // failure to parse it.
+    fn take_number(&mut self) -> Result<Pair<'de, Rule>> {
+        let pair = self.take_pair()?;
+        match pair.as_rule() {
+            Rule::number => Ok(pair),
+            _ => {
+                let (location, span) = locate(&pair.as_span());
+                Err(Error::ExpectedNumber {
+                    found: kind_of(&pair),
+                    location,
+                    span,
+                })
+            }
+        }
+    }
+
+    pub(crate) fn from_pair(pair: Pair<'de, Rule>, settings: Settings) -> Self {
+        Deserializer {
+            pair: Some(pair),
+            settings,
+        }
+    }
+}
+
+fn already_deserialized() -> Error {
+    de::Error::custom("the value has already been deserialized")
+}
+
+pub(crate) const DEFAULT_RECURSION_LIMIT: usize = 128;
+
+/// Parses a whole document, which must contain exactly one value nested no more than
+/// `recursion_limit` deep.
+pub(crate) fn parse_text(input: &str, recursion_limit: usize) -> Result<Pair<'_, Rule>> {
+    check_nesting(input, recursion_limit)?;
+    if Parser::parse(Rule::stream_end, input).is_ok() {
+        return Err(Error::EmptyDocument);
+    }
+    match Parser::parse(Rule::text, input) {
+        Ok(mut pairs) => pairs.next().ok_or(Error::Unexpected(Rule::text)),
+        Err(err) => Err(bare_identifier(input)
+            .or_else(|| invalid_key(input, &err))
+            .or_else(|| trailing_characters(input))
+            .unwrap_or_else(|| err.into())),
     }
 }
 
-impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
+// JSON5 keys are identifiers or strings, so a number (or any other value) where the parser wanted
+// a key is reported as such rather than as the parser's list of what it expected.
+fn invalid_key(input: &str, err: &pest::error::Error<Rule>) -> Option<Error> {
+    let wanted_key = match err.variant {
+        ErrorVariant::ParsingError { ref positives, .. } => positives.contains(&Rule::identifier),
+        ErrorVariant::CustomError { .. } => false,
+    };
+    let start = match err.location {
+        InputLocation::Pos(pos) if wanted_key => pos,
+        _ => return None,
+    };
+    let pair = Parser::parse(Rule::stream_value, &input[start..])
+        .ok()?
+        .next()?;
+    let end = start + pair.as_str().len();
+    let (location, span) = locate(&Span::new(input, start, end)?);
+    Some(Error::InvalidKey {
+        found: kind_of(&pair),
+        location,
+        span,
+    })
+}
+
+// An identifier is only valid as a key, but as a whole document it's most likely a string that
+// should have been quoted. Keywords such as `null` are identifiers too, so one is only reported if
+// it's longer than any value at the same position, as for `nullable`.
+fn bare_identifier(input: &str) -> Option<Error> {
+    let rest = skip_whitespace_and_comments(input);
+    let identifier = Parser::parse(Rule::identifier, rest).ok()?.next()?.as_str();
+    let value_len = Parser::parse(Rule::stream_value, rest)
+        .ok()
+        .and_then(|mut pairs| pairs.next())
+        .map_or(0, |pair| pair.as_str().len());
+    if identifier.len() <= value_len {
+        return None;
+    }
+    let (line, column) = Position::new(input, input.len() - rest.len())?.line_col();
+    Some(Error::BareIdentifierNotAllowed {
+        identifier: identifier.into(),
+        location: Location { line, column },
+    })
+}
+
+// If the input starts with a whole value, the problem is whatever comes after it.
+fn trailing_characters(input: &str) -> Option<Error> {
+    let pair = Parser::parse(Rule::stream_value, input).ok()?.next()?;
+    let rest = skip_whitespace_and_comments(&input[pair.as_span().end()..]);
+    let (line, column) = Position::new(input, input.len() - rest.len())?.line_col();
+    Some(Error::TrailingCharacters {
+        location: Location { line, column },
+    })
+}
+
+// The pest parser recurses for every level of nesting, so the depth has to be checked before it
+// runs. Brackets are counted outside of strings and comments; anything else that would confuse the
+// count is a syntax error that the parser reports afterwards.
+pub(crate) fn check_nesting(input: &str, limit: usize) -> Result<()> {
+    let bytes = input.as_bytes();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'[' | b'{' => {
+                depth += 1;
+                if depth > limit {
+                    let (line, column) = Position::new(input, i).unwrap().line_col();
+                    return Err(Error::RecursionLimitExceeded {
+                        limit,
+                        location: Location { line, column },
+                    });
+                }
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            quote @ (b'"' | b'\'') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            b'/' if matches!(bytes.get(i + 1), Some(b'/' | b'*')) => {
+                let rest = skip_whitespace_and_comments(&input[i..]);
+                if rest.len() == input.len() - i {
+                    // An unterminated block comment.
+                    return Ok(());
+                }
+                i = input.len() - rest.len();
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    Ok(())
+}
+
+fn skip_whitespace_and_comments(mut rest: &str) -> &str {
+    loop {
+        rest = rest
+            .trim_start_matches(|c: char| (c.is_whitespace() && c != '\u{85}') || c == '\u{FEFF}');
+        if let Some(comment) = rest.strip_prefix("//") {
+            let end = comment.find(['\n', '\r', '\u{2028}', '\u{2029}']);
+            rest = &comment[end.unwrap_or(comment.len())..];
+        } else if let Some(end) = rest.strip_prefix("/*").and_then(|c| c.find("*/")) {
+            rest = &rest[end + 4..];
+        } else {
+            return rest;
+        }
+    }
+}
+
+// Splits a path such as `servers[0].port` into its segments, each with the offset of its end.
+fn parse_path(path: &str) -> Result<Vec<(PathSegment, usize)>> {
+    let invalid = || de::Error::custom(format!("invalid path `{}`", path));
+    let mut segments = Vec::new();
+    let mut rest = path;
+    while !rest.is_empty() {
+        let segment;
+        if let Some(bracketed) = rest.strip_prefix('[') {
+            let quote = bracketed.chars().next().filter(|&c| c == '\'' || c == '"');
+            let (inside, after) = match quote {
+                Some(quote) => {
+                    let end = bracketed[1..].find(quote).ok_or_else(invalid)? + 1;
+                    (&bracketed[1..end], bracketed[end + 1..].strip_prefix(']'))
+                }
+                None => match bracketed.split_once(']') {
+                    Some((inside, after)) => (inside, Some(after)),
+                    None => (bracketed, None),
+                },
+            };
+            segment = match quote {
+                Some(_) => PathSegment::Key(inside.into()),
+                None if inside.bytes().all(|b| b.is_ascii_digit()) => {
+                    PathSegment::Index(inside.parse().map_err(|_| invalid())?)
+                }
+                None => return Err(invalid()),
+            };
+            rest = after.ok_or_else(invalid)?;
+        } else {
+            let key = match rest.strip_prefix('.') {
+                Some(key) if !segments.is_empty() => key,
+                None if segments.is_empty() => rest,
+                _ => return Err(invalid()),
+            };
+            let end = key.find(['.', '[', ']']).unwrap_or(key.len());
+            if end == 0 {
+                return Err(invalid());
+            }
+            segment = PathSegment::Key(key[..end].into());
+            rest = &key[end..];
+        }
+        segments.push((segment, path.len() - rest.len()));
+    }
+    Ok(segments)
+}
+
+// The value of an array or object at one step of a path, if it has one.
+fn select<'a>(pair: Pair<'a, Rule>, segment: &PathSegment) -> Result<Option<Pair<'a, Rule>>> {
+    match (pair.as_rule(), segment) {
+        (Rule::array, PathSegment::Index(index)) => Ok(pair.into_inner().nth(*index)),
+        (Rule::object, PathSegment::Key(key)) => {
+            let mut pairs = pair.into_inner();
+            let mut found = None;
+            while let (Some(k), Some(v)) = (pairs.next(), pairs.next()) {
+                if parse_string(k)? == key.as_str() {
+                    found = Some(v);
+                }
+            }
+            Ok(found)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// An iterator over a sequence of JSON5 documents in one input, separated by whitespace or
+/// comments, deserializing each as a `T`.
+///
+/// Each item is parsed only when the iterator reaches it, so the documents before a syntax error
+/// are still yielded. After a syntax error the iterator ends, but an item that merely fails to
+/// deserialize as a `T` doesn&rsquo;t stop the documents after it from being read.
+///
+/// ```rust
+/// let input = "{ level: 'info' }\n{ level: 'warn' }\n";
+/// let levels = json5::StreamDeserializer::<json5::Value>::new(input)
+///     .map(|record| record.map(|r| r["level"].as_str().unwrap().to_owned()))
+///     .collect::<json5::Result<Vec<_>>>();
+/// assert_eq!(levels, Ok(vec!["info".to_owned(), "warn".to_owned()]));
+/// ```
+pub struct StreamDeserializer<'de, T> {
+    input: &'de str,
+    offset: usize,
+    failed: bool,
+    options: DeserializeOptions,
+    output: PhantomData<T>,
+}
+
+impl<'de, T> StreamDeserializer<'de, T> {
+    /// Creates a stream of JSON5 documents from a `&str`. Nothing is parsed until the first call to
+    /// `next`. Use [`DeserializeOptions::stream`][] to read the documents with other options.
+    ///
+    /// [`DeserializeOptions::stream`]: struct.DeserializeOptions.html#method.stream
+    pub fn new(input: &'de str) -> Self {
+        DeserializeOptions::new().stream(input)
+    }
+
+    /// The number of bytes of the input consumed so far, i.e. the offset just after the last
+    /// document read.
+    pub fn byte_offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'de, T> Iterator for StreamDeserializer<'de, T>
+where
+    T: de::Deserialize<'de>,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        if self.failed {
+            return None;
+        }
+        // The whole input is checked at once, rather than each document as it's reached.
+        if self.offset == 0 {
+            let checked = self
+                .options
+                .check_size(self.input.len())
+                .and_then(|()| check_nesting(self.input, self.options.recursion_limit));
+            if let Err(err) = checked {
+                self.failed = true;
+                return Some(Err(err));
+            }
+        }
+        let rest = &self.input[self.offset..];
+        if Parser::parse(Rule::stream_end, rest).is_ok() {
+            self.offset = self.input.len();
+            return None;
+        }
+        match Parser::parse(Rule::stream_value, rest) {
+            Ok(mut pairs) => {
+                let pair = match pairs.next() {
+                    Some(pair) => pair,
+                    None => {
+                        self.failed = true;
+                        return Some(Err(Error::Unexpected(Rule::stream_value)));
+                    }
+                };
+                self.offset += pair.as_span().end();
+                Some(T::deserialize(&mut Deserializer::from_pair(
+                    pair,
+                    self.options.settings,
+                )))
+            }
+            Err(err) => {
+                self.failed = true;
+                Some(Err(relocate(err, self.input, self.offset).into()))
+            }
+        }
+    }
+}
+
+/// Moves a parse error for `input[offset..]` to the equivalent position in `input`, so that the
+/// reported line and column are relative to the whole input.
+fn relocate(err: pest::error::Error<Rule>, input: &str, offset: usize) -> pest::error::Error<Rule> {
+    let relocated = match err.location {
+        InputLocation::Pos(pos) => Position::new(input, offset + pos)
+            .map(|pos| pest::error::Error::new_from_pos(err.variant.clone(), pos)),
+        InputLocation::Span((start, end)) => Span::new(input, offset + start, offset + end)
+            .map(|span| pest::error::Error::new_from_span(err.variant.clone(), span)),
+    };
+    relocated.unwrap_or(err)
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        let pair = self.pair.take().unwrap();
+        let pair = self.take_pair()?;
         match pair.as_rule() {
             Rule::null => visitor.visit_unit(),
-            Rule::boolean => visitor.visit_bool(parse_bool(&pair)),
-            Rule::string | Rule::identifier => visitor.visit_string(parse_string(pair)?),
+            Rule::boolean => visitor.visit_bool(parse_bool(&pair)?),
+            Rule::string | Rule::identifier => visit_str(parse_string(pair)?, visitor),
             Rule::number => {
                 if is_int(pair.as_str()) {
-                    visitor.visit_i64(parse_integer(&pair)?)
+                    visit_integer(&pair, visitor)
                 } else {
                     visitor.visit_f64(parse_number(&pair)?)
                 }
             }
-            Rule::array => visitor.visit_seq(Seq::new(pair)),
-            Rule::object => visitor.visit_map(Map::new(pair)),
-            _ => unreachable!(),
+            Rule::array => visitor.visit_seq(Seq::new(pair, self.settings)),
+            Rule::object => visitor.visit_map(Map::new(pair, self.settings)?),
+            rule => Err(Error::Unexpected(rule)),
         }
     }
 
@@ -75,177 +1071,507 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         V: de::Visitor<'de>,
     {
         visitor.visit_enum(Enum {
-            pair: self.pair.take().unwrap(),
+            pair: self.take_raw()?,
+            settings: self.settings,
         })
     }
 
-    // The below will get us the right types, but won't necessarily give
-    // meaningful results if the source is out of the range of the target type.
+    // Integer literals are parsed directly as the target type, so are exact and fail if out of
+    // range. Anything else (e.g. `4.2e1`) goes through `f64` and is truncated towards zero.
     fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        let pair = self.pair.take().unwrap();
-        visitor.visit_i8(parse_number(&pair)? as i8)
+        let pair = self.take_number()?;
+        visitor.visit_i8(parse_integer_as(&pair)?)
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        let pair = self.pair.take().unwrap();
-        visitor.visit_i16(parse_number(&pair)? as i16)
+        let pair = self.take_number()?;
+        visitor.visit_i16(parse_integer_as(&pair)?)
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        let pair = self.pair.take().unwrap();
-        visitor.visit_i32(parse_number(&pair)? as i32)
+        let pair = self.take_number()?;
+        visitor.visit_i32(parse_integer_as(&pair)?)
     }
 
     fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        let pair = self.pair.take().unwrap();
-        visitor.visit_i64(parse_number(&pair)? as i64)
+        let pair = self.take_number()?;
+        visitor.visit_i64(parse_integer_as(&pair)?)
     }
 
     fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        let pair = self.pair.take().unwrap();
-        visitor.visit_i128(parse_number(&pair)? as i128)
+        let pair = self.take_number()?;
+        visitor.visit_i128(parse_integer_as(&pair)?)
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        let pair = self.pair.take().unwrap();
-        visitor.visit_u8(parse_number(&pair)? as u8)
+        let pair = self.take_number()?;
+        visitor.visit_u8(parse_integer_as(&pair)?)
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        let pair = self.pair.take().unwrap();
-        visitor.visit_u16(parse_number(&pair)? as u16)
+        let pair = self.take_number()?;
+        visitor.visit_u16(parse_integer_as(&pair)?)
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        let pair = self.pair.take().unwrap();
-        visitor.visit_u32(parse_number(&pair)? as u32)
+        let pair = self.take_number()?;
+        visitor.visit_u32(parse_integer_as(&pair)?)
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        let pair = self.pair.take().unwrap();
-        visitor.visit_u64(parse_number(&pair)? as u64)
+        let pair = self.take_number()?;
+        visitor.visit_u64(parse_integer_as(&pair)?)
     }
 
     fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        let pair = self.pair.take().unwrap();
-        visitor.visit_u128(parse_number(&pair)? as u128)
+        let pair = self.take_number()?;
+        visitor.visit_u128(parse_integer_as(&pair)?)
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        let pair = self.pair.take().unwrap();
-        visitor.visit_f32(parse_number(&pair)? as f32)
+        let pair = self.take_number()?;
+        visitor.visit_f32(parse_f32(&pair)?)
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        let pair = self.pair.take().unwrap();
+        let pair = self.take_number()?;
         visitor.visit_f64(parse_number(&pair)?)
     }
 
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.peek_rule()? {
+            Rule::string | Rule::identifier => {
+                let s = parse_string(self.take_raw()?)?;
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => visitor.visit_char(c),
+                    _ => Err(Error::ExpectedSingleChar(s.into_owned())),
+                }
+            }
+            _ => Err(self.type_mismatch(ValueKind::String)),
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.peek_rule()? {
+            Rule::string | Rule::identifier => visit_str(parse_string(self.take_raw()?)?, visitor),
+            _ => Err(self.type_mismatch(ValueKind::String)),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    // A string gives its UTF-8 encoding and an array gives one byte per element, each of which
+    // must be an integer from 0 to 255. Anything else is handed to the visitor as normal.
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.peek_rule()? {
+            Rule::string => {
+                visitor.visit_byte_buf(parse_string(self.take_raw()?)?.into_owned().into_bytes())
+            }
+            Rule::array => {
+                let bytes = self
+                    .take_raw()?
+                    .into_inner()
+                    .map(|pair| match pair.as_rule() {
+                        Rule::number => {
+                            self.settings.check_number(&pair)?;
+                            parse_integer_as::<u8>(&pair).map_err(|_| {
+                                let (location, span) = locate(&pair.as_span());
+                                Error::IntegerOutOfRange {
+                                    number: pair.as_str().into(),
+                                    expected: "a byte from 0 to 255",
+                                    location,
+                                    span,
+                                }
+                            })
+                        }
+                        _ => {
+                            let (location, span) = locate(&pair.as_span());
+                            Err(Error::ExpectedNumber {
+                                found: kind_of(&pair),
+                                location,
+                                span,
+                            })
+                        }
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                visitor.visit_byte_buf(bytes)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        let pair = self.pair.take().unwrap();
+        match self.peek_rule()? {
+            Rule::null => {
+                self.pair.take();
+                visitor.visit_none()
+            }
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    // Serde's own tuple visitors ignore any extra elements, so check the length up front.
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.peek_rule()? {
+            Rule::array => {
+                let pair = self.take_raw()?;
+                let found = pair.clone().into_inner().count();
+                if found != len {
+                    return Err(Error::WrongTupleLength {
+                        expected: len,
+                        found,
+                    });
+                }
+                visitor.visit_seq(Seq::new(pair, self.settings))
+            }
+            Rule::object if self.settings.tuples_from_objects => {
+                let seq = Seq::from_object(self.take_raw()?, self.settings)?;
+                if seq.pairs.len() != len {
+                    return Err(Error::WrongTupleLength {
+                        expected: len,
+                        found: seq.pairs.len(),
+                    });
+                }
+                visitor.visit_seq(seq)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let pair = self.take_raw()?;
         match pair.as_rule() {
-            Rule::null => visitor.visit_none(),
-            _ => visitor.visit_some(&mut Deserializer::from_pair(pair)),
+            Rule::boolean => visitor.visit_bool(parse_bool(&pair)?),
+            _ => {
+                let (location, span) = locate(&pair.as_span());
+                Err(Error::ExpectedBool {
+                    found: kind_of(&pair),
+                    location,
+                    span,
+                })
+            }
         }
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &str, visitor: V) -> Result<V::Value>
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let pair = self.take_raw()?;
+        match pair.as_rule() {
+            Rule::null => visitor.visit_unit(),
+            _ => {
+                let (location, span) = locate(&pair.as_span());
+                Err(Error::ExpectedNull {
+                    found: kind_of(&pair),
+                    location,
+                    span,
+                })
+            }
+        }
+    }
+
+    // `json5::decimal` asks for a newtype struct with a name of its own, to be given the text of a
+    // decimal number as written rather than an `f64`. Anything else is deserialized as usual.
+    #[cfg_attr(not(feature = "decimal"), allow(unused_variables))]
+    fn deserialize_newtype_struct<V>(self, name: &str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        #[cfg(feature = "decimal")]
+        if name == crate::decimal::NAME && self.peek_rule()? == Rule::number {
+            let pair = self.take_pair()?;
+            let text = number_text(&pair);
+            if radix_of_literal(split_sign(&text).1) == 10 && !is_infinite(&text) && !is_nan(&text)
+            {
+                return visit_str(text, visitor);
+            }
+            self.pair = Some(pair);
+        }
         visitor.visit_newtype_struct(self)
     }
 
+    // The pest parse has already checked the syntax, so there is nothing to do but check the
+    // numbers and escapes and drop the pair, unless it also has to be checked for duplicate keys.
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.settings.deny_duplicate_keys {
+            return self.deserialize_any(visitor);
+        }
+        let pair = self.take_raw()?;
+        self.settings.check_skipped(&pair)?;
+        visitor.visit_unit()
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.take_null_as_empty() {
+            return visitor.visit_seq(Seq::empty(self.settings));
+        }
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.take_null_as_empty() {
+            return visitor.visit_map(Map::empty(self.settings));
+        }
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if let Some(pair) = self.pair.as_ref() {
+            if self.settings.deny_unknown_fields && pair.as_rule() == Rule::object {
+                check_fields(pair, fields)?;
+            }
+        }
+        self.deserialize_any(visitor)
+    }
+
     forward_to_deserialize_any! {
-        bool char str string bytes byte_buf unit unit_struct seq
-        tuple tuple_struct map struct identifier ignored_any
+        identifier
     }
 }
 
-fn parse_bool(pair: &Pair<'_, Rule>) -> bool {
+pub(crate) fn locate(span: &Span<'_>) -> (Location, error::Span) {
+    let (line, column) = span.start_pos().line_col();
+    (
+        Location { line, column },
+        error::Span {
+            start: span.start(),
+            end: span.end(),
+        },
+    )
+}
+
+pub(crate) fn kind_of(pair: &Pair<'_, Rule>) -> ValueKind {
+    match pair.as_rule() {
+        Rule::null => ValueKind::Null,
+        Rule::boolean => ValueKind::Bool,
+        Rule::number => ValueKind::Number,
+        Rule::array => ValueKind::Array,
+        Rule::object => ValueKind::Object,
+        _ => ValueKind::String,
+    }
+}
+
+fn parse_bool(pair: &Pair<'_, Rule>) -> Result<bool> {
     match pair.as_str() {
-        "true" => true,
-        "false" => false,
-        _ => unreachable!(),
-    }
-}
-
-fn parse_string(pair: Pair<'_, Rule>) -> Result<String> {
-    pair.into_inner()
-        .map(|component| match component.as_rule() {
-            Rule::char_literal => Ok(String::from(component.as_str())),
-            Rule::char_escape_sequence => Ok(parse_char_escape_sequence(&component)),
-            Rule::nul_escape_sequence => Ok(String::from("\u{0000}")),
-            Rule::hex_escape_sequence | Rule::unicode_escape_sequence => {
-                let hex_escape = parse_hex(component.as_str())?;
-                match char::from_u32(hex_escape) {
-                    Some(s) => Ok(s.to_string()),
-                    None => Err(de::Error::custom("error parsing hex prefix")),
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(Error::Unexpected(pair.as_rule())),
+    }
+}
+
+// Only allocates if the string has escapes (or line continuations), otherwise the contents are
+// borrowed straight from the input.
+pub(crate) fn parse_string(pair: Pair<'_, Rule>) -> Result<Cow<'_, str>> {
+    if let Some(s) = borrowed_str(&pair) {
+        return Ok(Cow::Borrowed(s));
+    }
+    let mut s = String::with_capacity(pair.as_str().len());
+    let mut components = pair.into_inner().peekable();
+    while let Some(component) = components.next() {
+        match component.as_rule() {
+            Rule::char_literal | Rule::double_quote_chars | Rule::single_quote_chars => {
+                s.push_str(component.as_str())
+            }
+            Rule::char_escape_sequence => s.push(parse_char_escape_sequence(&component)?),
+            Rule::nul_escape_sequence => s.push('\u{0000}'),
+            Rule::hex_escape_sequence => s.push(char::from(parse_hex(component.as_str())? as u8)),
+            Rule::unicode_escape_sequence => {
+                let mut code_point = parse_hex(component.as_str())?;
+                // A UTF-16 surrogate pair written as two escapes is one character.
+                if (0xD800..0xDC00).contains(&code_point) {
+                    let low = components
+                        .peek()
+                        .filter(|next| next.as_rule() == Rule::unicode_escape_sequence)
+                        .map(|next| parse_hex(next.as_str()))
+                        .transpose()?
+                        .filter(|low| (0xDC00..0xE000).contains(low));
+                    if let Some(low) = low {
+                        components.next();
+                        code_point = 0x10000 + ((code_point - 0xD800) << 10) + (low - 0xDC00);
+                    }
+                }
+                match char::from_u32(code_point) {
+                    Some(c) => s.push(c),
+                    None => return Err(Error::InvalidEscape(format!("\\u{}", component.as_str()))),
                 }
             }
-            _ => unreachable!(),
-        })
-        .collect()
-}
-
-fn parse_char_escape_sequence(pair: &Pair<'_, Rule>) -> String {
-    String::from(match pair.as_str() {
-        "b" => "\u{0008}",
-        "f" => "\u{000C}",
-        "n" => "\n",
-        "r" => "\r",
-        "t" => "\t",
-        "v" => "\u{000B}",
-        c => c,
+            rule => return Err(Error::Unexpected(rule)),
+        }
+    }
+    Ok(Cow::Owned(s))
+}
+
+fn visit_str<'de, V>(s: Cow<'de, str>, visitor: V) -> Result<V::Value>
+where
+    V: de::Visitor<'de>,
+{
+    match s {
+        Cow::Borrowed(s) => visitor.visit_borrowed_str(s),
+        Cow::Owned(s) => visitor.visit_string(s),
+    }
+}
+
+// The contents of a string or identifier as they appear in the input, if that is also their
+// value.
+fn borrowed_str<'de>(pair: &Pair<'de, Rule>) -> Option<&'de str> {
+    let s = pair.as_str();
+    let s = match pair.as_rule() {
+        Rule::string => &s[1..s.len() - 1],
+        _ => s,
+    };
+    if s.contains('\\') {
+        None
+    } else {
+        Some(s)
+    }
+}
+
+fn parse_char_escape_sequence(pair: &Pair<'_, Rule>) -> Result<char> {
+    Ok(match pair.as_str() {
+        "b" => '\u{0008}',
+        "f" => '\u{000C}',
+        "n" => '\n',
+        "r" => '\r',
+        "t" => '\t',
+        "v" => '\u{000B}',
+        c => c
+            .chars()
+            .next()
+            .ok_or(Error::Unexpected(Rule::char_escape_sequence))?,
     })
 }
 
+// The text of a number without any underscores, which `check_number` has already made sure are
+// allowed and between digits.
+fn number_text<'a>(pair: &Pair<'a, Rule>) -> Cow<'a, str> {
+    let s = pair.as_str();
+    if s.contains('_') {
+        Cow::Owned(s.replace('_', ""))
+    } else {
+        Cow::Borrowed(s)
+    }
+}
+
 fn parse_number(pair: &Pair<'_, Rule>) -> Result<f64> {
-    match pair.as_str() {
+    let s = number_text(pair);
+    let (negative, unsigned) = split_sign(&s);
+    match unsigned {
+        "Infinity" if negative => Ok(f64::NEG_INFINITY),
         "Infinity" => Ok(f64::INFINITY),
-        "-Infinity" => Ok(f64::NEG_INFINITY),
-        "NaN" | "-NaN" => Ok(f64::NAN),
-        s if is_hex_literal(s) => parse_hex(&s[2..]).map(f64::from),
-        s => {
+        "NaN" => Ok(f64::NAN),
+        u if radix_of_literal(u) != 10 => {
+            let magnitude = parse_radix_literal(u)?;
+            Ok(if negative {
+                -(magnitude as f64)
+            } else {
+                magnitude as f64
+            })
+        }
+        _ => {
             if let Ok(r) = s.parse::<f64>() {
                 if r.is_finite() {
                     Ok(r)
@@ -253,54 +1579,213 @@ fn parse_number(pair: &Pair<'_, Rule>) -> Result<f64> {
                     Err(de::Error::custom("error parsing number: too large"))
                 }
             } else {
-                Err(de::Error::custom("error parsing number"))
+                Err(Error::InvalidNumber(pair.as_str().into()))
             }
         }
     }
 }
 
-fn parse_integer(pair: &Pair<'_, Rule>) -> Result<i64> {
-    match pair.as_str() {
-        s if is_hex_literal(s) => Ok(parse_hex(&s[2..])? as i64),
-        s => s
-            .parse()
-            .or_else(|_| Err(de::Error::custom("error parsing integer"))),
+// Parsed directly rather than narrowed from `parse_number`, which would round twice.
+fn parse_f32(pair: &Pair<'_, Rule>) -> Result<f32> {
+    let s = number_text(pair);
+    let (negative, unsigned) = split_sign(&s);
+    match unsigned {
+        "Infinity" | "NaN" => Ok(parse_number(pair)? as f32),
+        u if radix_of_literal(u) != 10 => {
+            let magnitude = parse_radix_literal(u)?;
+            Ok(if negative {
+                -(magnitude as f32)
+            } else {
+                magnitude as f32
+            })
+        }
+        _ => match s.parse::<f32>() {
+            Ok(r) if r.is_finite() => Ok(r),
+            Ok(_) => Err(de::Error::custom("error parsing number: too large")),
+            Err(_) => Err(Error::InvalidNumber(pair.as_str().into())),
+        },
+    }
+}
+
+// Integers are visited as a `u64`, or an `i64` if negative, as serde_json does, so that
+// self-describing types such as `Value` keep them as integers. Any too big for either are visited
+// as an `f64` instead.
+fn visit_integer<'de, V>(pair: &Pair<'_, Rule>, visitor: V) -> Result<V::Value>
+where
+    V: de::Visitor<'de>,
+{
+    if split_sign(pair.as_str()).0 {
+        if let Ok(v) = parse_integer_as(pair) {
+            return visitor.visit_i64(v);
+        }
+    } else if let Ok(v) = parse_integer_as(pair) {
+        return visitor.visit_u64(v);
+    }
+    visitor.visit_f64(parse_number(pair)?)
+}
+
+fn parse_integer_as<T: Integer>(pair: &Pair<'_, Rule>) -> Result<T> {
+    let out_of_range = || {
+        let (location, span) = locate(&pair.as_span());
+        Error::IntegerOutOfRange {
+            number: pair.as_str().into(),
+            expected: T::EXPECTED,
+            location,
+            span,
+        }
+    };
+    let s = number_text(pair);
+    let s = &*s;
+    if is_infinite(s) || is_nan(s) {
+        return Err(Error::ExpectedInteger(s.into()));
+    }
+    if !is_int(s) {
+        let n = parse_number(pair).map_err(|_| out_of_range())?;
+        let converted = if n < 0. {
+            T::try_from(n as i128).ok()
+        } else {
+            T::try_from(n as u128).ok()
+        };
+        return converted.ok_or_else(out_of_range);
+    }
+
+    let (negative, unsigned) = split_sign(s);
+    let radix = radix_of_literal(unsigned);
+    if radix != 10 {
+        let converted = parse_radix_literal(unsigned).map_err(|_| out_of_range())?;
+        let converted = if negative {
+            // The magnitude of `i128::MIN` doesn't fit in an `i128`, but negates to itself.
+            (converted <= i128::MIN as u128)
+                .then(|| (converted as i128).wrapping_neg())
+                .and_then(|m| T::try_from(m).ok())
+        } else {
+            T::try_from(converted).ok()
+        };
+        converted.ok_or_else(out_of_range)
+    } else {
+        s.parse().map_err(|_| out_of_range())
+    }
+}
+
+// The integer types, with how they're described when a number is out of their range.
+trait Integer: FromStr + TryFrom<i128> + TryFrom<u128> {
+    const EXPECTED: &'static str;
+}
+
+macro_rules! integer {
+    ($($ty:ty => $expected:expr,)*) => {
+        $(
+            impl Integer for $ty {
+                const EXPECTED: &'static str = $expected;
+            }
+        )*
+    };
+}
+
+integer! {
+    i8 => "an i8",
+    i16 => "an i16",
+    i32 => "an i32",
+    i64 => "an i64",
+    i128 => "an i128",
+    u8 => "a u8",
+    u16 => "a u16",
+    u32 => "a u32",
+    u64 => "a u64",
+    u128 => "a u128",
+}
+
+pub(crate) fn split_sign(s: &str) -> (bool, &str) {
+    if let Some(rest) = s.strip_prefix('-') {
+        (true, rest)
+    } else {
+        (false, s.strip_prefix('+').unwrap_or(s))
     }
 }
 
 fn is_int(s: &str) -> bool {
     !s.contains('.')
-        && (is_hex_literal(s) || (!s.contains('e') && !s.contains('E')))
+        && (radix_of_literal(split_sign(s).1) != 10 || (!s.contains('e') && !s.contains('E')))
         && !is_infinite(s)
         && !is_nan(s)
 }
 
 fn parse_hex(s: &str) -> Result<u32> {
-    u32::from_str_radix(s, 16).or_else(|_| Err(de::Error::custom("error parsing hex")))
+    u32::from_str_radix(s, 16).map_err(|_| de::Error::custom("error parsing hex"))
+}
+
+// The radix of an unsigned integer literal, from its prefix.
+pub(crate) fn radix_of_literal(s: &str) -> u32 {
+    match s.get(..2) {
+        Some("0x") | Some("0X") => 16,
+        Some("0o") | Some("0O") => 8,
+        Some("0b") | Some("0B") => 2,
+        _ => 10,
+    }
 }
 
-fn is_hex_literal(s: &str) -> bool {
-    s.len() > 2 && (&s[..2] == "0x" || &s[..2] == "0X")
+// Parses an unsigned hex, octal or binary literal, including its prefix.
+fn parse_radix_literal(s: &str) -> Result<u128> {
+    let radix = radix_of_literal(s);
+    u128::from_str_radix(&s[2..], radix).map_err(|_| radix_error(radix))
+}
+
+fn radix_error(radix: u32) -> Error {
+    de::Error::custom(match radix {
+        16 => "error parsing hex",
+        8 => "error parsing octal",
+        _ => "error parsing binary",
+    })
 }
 
 fn is_infinite(s: &str) -> bool {
-    s == "Infinity" || s == "-Infinity"
+    split_sign(s).1 == "Infinity"
 }
 
 fn is_nan(s: &str) -> bool {
-    s == "NaN" || s == "-NaN"
+    split_sign(s).1 == "NaN"
 }
 
 struct Seq<'de> {
     pairs: VecDeque<Pair<'de, Rule>>,
+    settings: Settings,
 }
 
 impl<'de> Seq<'de> {
-    pub fn new(pair: Pair<'de, Rule>) -> Self {
+    pub fn new(pair: Pair<'de, Rule>, settings: Settings) -> Self {
         Self {
             pairs: pair.into_inner().collect(),
+            settings,
         }
     }
+
+    fn empty(settings: Settings) -> Self {
+        Self {
+            pairs: VecDeque::new(),
+            settings,
+        }
+    }
+
+    // The values of an object whose keys are their indices, in order.
+    fn from_object(pair: Pair<'de, Rule>, settings: Settings) -> Result<Self> {
+        let mut pairs = pair.into_inner();
+        let mut values = VecDeque::new();
+        while let (Some(key), Some(value)) = (pairs.next(), pairs.next()) {
+            let key = parse_string(key)?;
+            if key != values.len().to_string() {
+                return Err(de::Error::custom(format!(
+                    "expected key `{}` for a tuple written as an object, found `{}`",
+                    values.len(),
+                    key
+                )));
+            }
+            values.push_back(value);
+        }
+        Ok(Self {
+            pairs: values,
+            settings,
+        })
+    }
 }
 
 impl<'de> de::SeqAccess<'de> for Seq<'de> {
@@ -315,7 +1800,7 @@ impl<'de> de::SeqAccess<'de> for Seq<'de> {
         T: de::DeserializeSeed<'de>,
     {
         if let Some(pair) = self.pairs.pop_front() {
-            seed.deserialize(&mut Deserializer::from_pair(pair))
+            seed.deserialize(&mut Deserializer::from_pair(pair, self.settings))
                 .map(Some)
         } else {
             Ok(None)
@@ -325,12 +1810,36 @@ impl<'de> de::SeqAccess<'de> for Seq<'de> {
 
 struct Map<'de> {
     pairs: VecDeque<Pair<'de, Rule>>,
+    settings: Settings,
+    // Whether a key has been given out whose value hasn't yet.
+    expecting_value: bool,
 }
 
 impl<'de> Map<'de> {
-    pub fn new(pair: Pair<'de, Rule>) -> Self {
+    pub fn new(pair: Pair<'de, Rule>, settings: Settings) -> Result<Self> {
+        let pairs: VecDeque<_> = pair.into_inner().collect();
+        if settings.deny_duplicate_keys {
+            let mut keys = BTreeSet::new();
+            for key in pairs.iter().step_by(2) {
+                let key = parse_string(key.clone())?;
+                if keys.contains(&key) {
+                    return Err(Error::DuplicateKey(key.into_owned()));
+                }
+                keys.insert(key);
+            }
+        }
+        Ok(Self {
+            pairs,
+            settings,
+            expecting_value: false,
+        })
+    }
+
+    fn empty(settings: Settings) -> Self {
         Self {
-            pairs: pair.into_inner().collect(),
+            pairs: VecDeque::new(),
+            settings,
+            expecting_value: false,
         }
     }
 }
@@ -346,9 +1855,18 @@ impl<'de> de::MapAccess<'de> for Map<'de> {
     where
         K: de::DeserializeSeed<'de>,
     {
+        if self.expecting_value {
+            // Skip the value of the previous key, which the caller didn't ask for.
+            if let Some(value) = self.pairs.pop_front() {
+                self.settings.check_skipped(&value)?;
+            }
+        }
         if let Some(pair) = self.pairs.pop_front() {
-            seed.deserialize(&mut Deserializer::from_pair(pair))
-                .map(Some)
+            self.expecting_value = true;
+            seed.deserialize(MapKey {
+                de: Deserializer::from_pair(pair, self.settings),
+            })
+            .map(Some)
         } else {
             Ok(None)
         }
@@ -358,14 +1876,134 @@ impl<'de> de::MapAccess<'de> for Map<'de> {
     where
         V: de::DeserializeSeed<'de>,
     {
-        seed.deserialize(&mut Deserializer::from_pair(
-            self.pairs.pop_front().unwrap(),
-        ))
+        if !self.expecting_value {
+            return Err(Error::ExpectedMapValue);
+        }
+        self.expecting_value = false;
+        match self.pairs.pop_front() {
+            Some(pair) => seed.deserialize(&mut Deserializer::from_pair(pair, self.settings)),
+            None => Err(Error::ExpectedMapValue),
+        }
+    }
+}
+
+// Checks that every key of an object is one of the fields of the struct it's deserialized into.
+fn check_fields(pair: &Pair<'_, Rule>, fields: &'static [&'static str]) -> Result<()> {
+    for key in pair.clone().into_inner().step_by(2) {
+        let key = parse_string(key)?;
+        if !fields.contains(&&*key) {
+            return Err(Error::UnknownField {
+                field: key.into_owned(),
+                expected: fields,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Deserializes an object key, which is always a string or identifier, but may be wanted as a
+/// number or a boolean (e.g. for a `HashMap<u32, T>`), in which case the key text is parsed.
+struct MapKey<'de> {
+    de: Deserializer<'de>,
+}
+
+impl<'de> MapKey<'de> {
+    fn parse<T, V>(mut self, visitor: &V) -> Result<T>
+    where
+        T: FromStr,
+        V: de::Visitor<'de>,
+    {
+        let s = parse_string(self.de.take_raw()?)?;
+        s.parse()
+            .map_err(|_| de::Error::invalid_value(de::Unexpected::Str(&s), visitor))
+    }
+}
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value>
+            where
+                V: de::Visitor<'de>,
+            {
+                let v = self.parse(&visitor)?;
+                visitor.$visit(v)
+            }
+        )*
+    };
+}
+
+macro_rules! deserialize_key_as_value {
+    ($($method:ident,)*) => {
+        $(
+            fn $method<V>(mut self, visitor: V) -> Result<V::Value>
+            where
+                V: de::Visitor<'de>,
+            {
+                de::Deserializer::$method(&mut self.de, visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for MapKey<'de> {
+    type Error = Error;
+
+    deserialize_parsed_key! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    deserialize_key_as_value! {
+        deserialize_any,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_option,
+        deserialize_identifier,
+        deserialize_ignored_any,
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        mut self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        de::Deserializer::deserialize_enum(&mut self.de, name, variants, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        unit unit_struct seq tuple tuple_struct map struct
     }
 }
 
 struct Enum<'de> {
     pair: Pair<'de, Rule>,
+    settings: Settings,
 }
 
 impl<'de> de::EnumAccess<'de> for Enum<'de> {
@@ -377,30 +2015,58 @@ impl<'de> de::EnumAccess<'de> for Enum<'de> {
         V: de::DeserializeSeed<'de>,
     {
         match self.pair.as_rule() {
-            Rule::string => {
-                let tag = seed.deserialize(&mut Deserializer::from_pair(self.pair))?;
-                Ok((tag, Variant { pair: None }))
+            Rule::string | Rule::identifier => {
+                let span = self.pair.as_span();
+                let tag =
+                    seed.deserialize(&mut Deserializer::from_pair(self.pair, self.settings))?;
+                Ok((
+                    tag,
+                    Variant {
+                        pair: None,
+                        span,
+                        settings: self.settings,
+                    },
+                ))
             }
             Rule::object => {
+                let span = self.pair.as_span();
                 let mut pairs = self.pair.into_inner();
 
                 if let Some(tag_pair) = pairs.next() {
-                    let tag = seed.deserialize(&mut Deserializer::from_pair(tag_pair))?;
-                    Ok((tag, Variant { pair: pairs.next() }))
+                    let tag =
+                        seed.deserialize(&mut Deserializer::from_pair(tag_pair, self.settings))?;
+                    Ok((
+                        tag,
+                        Variant {
+                            pair: pairs.next(),
+                            span,
+                            settings: self.settings,
+                        },
+                    ))
                 } else {
                     Err(de::Error::custom("expected a nonempty object"))
                 }
             }
-            _ => Err(de::Error::custom("expected a string or an object")),
+            _ => {
+                let (location, span) = locate(&self.pair.as_span());
+                Err(Error::NotAnEnum {
+                    found: kind_of(&self.pair),
+                    location,
+                    span,
+                })
+            }
         }
     }
 }
 
 struct Variant<'de> {
     pair: Option<Pair<'de, Rule>>,
+    // The whole enum value, for reporting errors when there is no pair.
+    span: Span<'de>,
+    settings: Settings,
 }
 
-impl<'de, 'a> de::VariantAccess<'de> for Variant<'de> {
+impl<'de> de::VariantAccess<'de> for Variant<'de> {
     type Error = Error;
 
     fn unit_variant(self) -> Result<()> {
@@ -411,7 +2077,13 @@ impl<'de, 'a> de::VariantAccess<'de> for Variant<'de> {
     where
         T: de::DeserializeSeed<'de>,
     {
-        seed.deserialize(&mut Deserializer::from_pair(self.pair.unwrap()))
+        match self.pair {
+            Some(pair) => seed.deserialize(&mut Deserializer::from_pair(pair, self.settings)),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+        }
     }
 
     fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
@@ -420,23 +2092,56 @@ impl<'de, 'a> de::VariantAccess<'de> for Variant<'de> {
     {
         match self.pair {
             Some(pair) => match pair.as_rule() {
-                Rule::array => visitor.visit_seq(Seq::new(pair)),
-                _ => Err(de::Error::custom("expected an array")),
+                Rule::array => visitor.visit_seq(Seq::new(pair, self.settings)),
+                _ => {
+                    let (location, span) = locate(&pair.as_span());
+                    Err(Error::NotATuple {
+                        found: kind_of(&pair),
+                        location,
+                        span,
+                    })
+                }
             },
-            None => Err(de::Error::custom("expected an array")),
+            None => {
+                let (location, span) = locate(&self.span);
+                Err(Error::NotATuple {
+                    found: ValueKind::String,
+                    location,
+                    span,
+                })
+            }
         }
     }
 
-    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
         match self.pair {
             Some(pair) => match pair.as_rule() {
-                Rule::object => visitor.visit_map(Map::new(pair)),
-                _ => Err(de::Error::custom("expected an object")),
+                Rule::object => {
+                    if self.settings.deny_unknown_fields {
+                        check_fields(&pair, fields)?;
+                    }
+                    visitor.visit_map(Map::new(pair, self.settings)?)
+                }
+                _ => {
+                    let (location, span) = locate(&pair.as_span());
+                    Err(Error::NotAStruct {
+                        found: kind_of(&pair),
+                        location,
+                        span,
+                    })
+                }
             },
-            None => Err(de::Error::custom("expected an object")),
+            None => {
+                let (location, span) = locate(&self.span);
+                Err(Error::NotAStruct {
+                    found: ValueKind::String,
+                    location,
+                    span,
+                })
+            }
         }
     }
 }
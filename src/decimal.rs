@@ -0,0 +1,68 @@
+//! Reading numbers into `rust_decimal::Decimal` exactly. Requires the `decimal` feature.
+//!
+//! `Decimal`&rsquo;s own `Deserialize` implementation is given a number the way any other type is,
+//! as the `f64` closest to it, which loses digits past the 17th or so. A field read with
+//! [`deserialize`][] is given the number&rsquo;s digits as written instead:
+//!
+//! ```rust
+//! use rust_decimal::Decimal;
+//! use serde_derive::Deserialize;
+//!
+//! #[derive(Deserialize, Debug, PartialEq)]
+//! struct Payment {
+//!     #[serde(deserialize_with = "json5::decimal::deserialize")]
+//!     amount: Decimal,
+//! }
+//!
+//! assert_eq!(
+//!     json5::from_str("{ amount: 12345678901234567890.12 }"),
+//!     Ok(Payment { amount: "12345678901234567890.12".parse().unwrap() }),
+//! );
+//! ```
+//!
+//! [`deserialize`]: fn.deserialize.html
+
+use core::fmt;
+use core::str::FromStr;
+use rust_decimal::Decimal;
+use serde::de;
+
+// The name of the newtype struct `deserialize` asks for, which the deserializer recognises.
+pub(crate) const NAME: &str = "$json5::private::Decimal";
+
+/// Deserializes a `Decimal` from the text of a number, for use with
+/// `#[serde(deserialize_with = "json5::decimal::deserialize")]`.
+///
+/// Hexadecimal integers, strings and anything else are read by `Decimal`&rsquo;s own
+/// `Deserialize` implementation, as are values from other deserializers, including a
+/// [`Value`](../enum.Value.html), whose numbers are already `f64`s or integers.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Decimal, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    deserializer.deserialize_newtype_struct(NAME, DecimalVisitor)
+}
+
+struct DecimalVisitor;
+
+impl<'de> de::Visitor<'de> for DecimalVisitor {
+    type Value = Decimal;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a decimal number")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Decimal, E>
+    where
+        E: de::Error,
+    {
+        Decimal::from_str(v).map_err(E::custom)
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Decimal, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        de::Deserialize::deserialize(deserializer)
+    }
+}
@@ -1,23 +1,449 @@
+use alloc::string::{String, ToString};
+use core::fmt::{self, Display};
+use core::str::Utf8Error;
+use pest::error::LineColLocation;
 use serde::{de, ser};
-use std::fmt::{self, Display};
+#[cfg(feature = "std")]
+use std::io;
 
 use crate::de::Rule;
+use crate::warning::WarningKind;
 
 /// Alias for a `Result` with error type `json5::Error`
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
-/// A bare bones error type which currently just collapses all the underlying errors in to a single
-/// string... This is fine for displaying to the user, but not very useful otherwise. Work to be
-/// done here.
+/// The errors that can occur while parsing, deserializing or serializing JSON5. Every variant has a
+/// human readable `Display` suitable for showing to the user.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Error {
-    /// Just shove everything in a single variant for now.
+    /// A free-form error message, e.g. from a `Serialize` or `Deserialize` implementation or for a
+    /// problem with the input not covered by the other variants.
     Message(String),
+
+    /// The input is empty, or contains only whitespace and comments.
+    EmptyDocument,
+
+    /// The input is longer than allowed by [`DeserializeOptions::max_input_size`][] (or
+    /// [`from_str_limited`][]). The length is checked before anything is parsed.
+    ///
+    /// [`DeserializeOptions::max_input_size`]: struct.DeserializeOptions.html#method.max_input_size
+    /// [`from_str_limited`]: fn.from_str_limited.html
+    InputTooLarge {
+        /// The maximum length allowed, in bytes.
+        limit: usize,
+    },
+
+    /// The input isn't valid JSON5. The message includes a description of what the parser expected
+    /// and where.
+    Parse {
+        /// The human readable message from the parser.
+        msg: String,
+        /// Where in the input parsing failed.
+        location: Location,
+    },
+
+    /// The input starts with a JSON5 value, but has something other than whitespace or comments
+    /// after it.
+    TrailingCharacters {
+        /// Where the first of the trailing characters is.
+        location: Location,
+    },
+
+    /// The document is a bare word, such as `hello`, which is probably a string missing its
+    /// quotes. Unquoted identifiers are only allowed as object keys, so rather than parsing one as
+    /// a string this is reported as a mistake.
+    BareIdentifierNotAllowed {
+        /// The identifier as written.
+        identifier: String,
+        /// Where the identifier starts.
+        location: Location,
+    },
+
+    /// An object key is a number or some other value, rather than an identifier or a string as
+    /// JSON5 requires. A number can be used as a key by quoting it, as in `{ '1': 'a' }`.
+    InvalidKey {
+        /// The kind of value found instead.
+        found: ValueKind,
+        /// Where the value starts in the input.
+        location: Location,
+        /// The byte range of the value in the input.
+        span: Span,
+    },
+
+    /// Arrays and objects are nested more deeply than the recursion limit, which defaults to 128
+    /// and can be changed with [`DeserializeOptions::recursion_limit`][]. The limit is checked
+    /// before parsing, so that deeply nested input can&rsquo;t overflow the stack.
+    ///
+    /// [`DeserializeOptions::recursion_limit`]: struct.DeserializeOptions.html#method.recursion_limit
+    RecursionLimitExceeded {
+        /// The maximum depth allowed.
+        limit: usize,
+        /// Where the first array or object beyond the limit starts.
+        location: Location,
+    },
+
+    /// A number uses an extension to JSON5, such as an octal literal, that the deserializer
+    /// isn&rsquo;t set to allow. Every number in the document is checked, including those in
+    /// values the `Deserialize` implementation skipped.
+    DisabledExtension {
+        /// The extension used.
+        kind: WarningKind,
+        /// Where the number starts in the input.
+        location: Location,
+        /// The byte range of the number in the input.
+        span: Span,
+    },
+
+    /// A number has an underscore that isn&rsquo;t between two digits, such as in `1_`, `1__0` or
+    /// `0x_FF`, where underscores in numbers are allowed.
+    MisplacedUnderscore {
+        /// Where the number starts in the input.
+        location: Location,
+        /// The byte range of the number in the input.
+        span: Span,
+    },
+
+    /// An enum was expected, which JSON5 represents as a string (for a unit variant) or an object
+    /// with a single entry, but some other kind of value was found.
+    NotAnEnum {
+        /// The kind of value found instead.
+        found: ValueKind,
+        /// Where the value starts in the input.
+        location: Location,
+        /// The byte range of the value in the input.
+        span: Span,
+    },
+
+    /// A tuple variant was expected, which JSON5 represents as an array, but some other kind of
+    /// value was found.
+    NotATuple {
+        /// The kind of value found instead.
+        found: ValueKind,
+        /// Where the value starts in the input.
+        location: Location,
+        /// The byte range of the value in the input.
+        span: Span,
+    },
+
+    /// A struct variant was expected, which JSON5 represents as an object, but some other kind of
+    /// value was found.
+    NotAStruct {
+        /// The kind of value found instead.
+        found: ValueKind,
+        /// Where the value starts in the input.
+        location: Location,
+        /// The byte range of the value in the input.
+        span: Span,
+    },
+
+    /// A unit value was expected, such as `()` or a unit struct, which JSON5 represents as `null`,
+    /// but some other kind of value was found.
+    ExpectedNull {
+        /// The kind of value found instead.
+        found: ValueKind,
+        /// Where the value starts in the input.
+        location: Location,
+        /// The byte range of the value in the input.
+        span: Span,
+    },
+
+    /// A boolean was expected, but some other kind of value was found.
+    ExpectedBool {
+        /// The kind of value found instead.
+        found: ValueKind,
+        /// Where the value starts in the input.
+        location: Location,
+        /// The byte range of the value in the input.
+        span: Span,
+    },
+
+    /// A number was expected, but some other kind of value was found.
+    ExpectedNumber {
+        /// The kind of value found instead.
+        found: ValueKind,
+        /// Where the value starts in the input.
+        location: Location,
+        /// The byte range of the value in the input.
+        span: Span,
+    },
+
+    /// A value of one kind was expected, such as a string for a `String` or `char`, but some other
+    /// kind of value was found. Kinds with a more specific error, such as
+    /// [`ExpectedNumber`](#variant.ExpectedNumber), are reported with that instead.
+    TypeMismatch {
+        /// The kind of value wanted.
+        expected: ValueKind,
+        /// The kind of value found instead.
+        found: ValueKind,
+        /// Where the value starts in the input.
+        location: Location,
+        /// The byte range of the value in the input.
+        span: Span,
+    },
+
+    /// A tuple, tuple struct or fixed size array was expected, but the array found has a different
+    /// number of elements.
+    WrongTupleLength {
+        /// The number of elements wanted.
+        expected: usize,
+        /// The number of elements in the array.
+        found: usize,
+    },
+
+    /// An integer was expected, but the number found is outside the range of the type wanted, such
+    /// as `300` for a `u8`, `-1` for a `u64` or `1e30` for an `i64`.
+    IntegerOutOfRange {
+        /// The number as written.
+        number: String,
+        /// A description of what was wanted, such as `"a u8"`.
+        expected: &'static str,
+        /// Where the number starts in the input.
+        location: Location,
+        /// The byte range of the number in the input.
+        span: Span,
+    },
+
+    /// A number token accepted by the grammar couldn&rsquo;t be parsed as a number. This
+    /// shouldn&rsquo;t happen for any input, but is reported as an error rather than a panic. The
+    /// token is included as written.
+    InvalidNumber(String),
+
+    /// An integer was expected, but `Infinity` or `NaN` was found. The token is included as written.
+    ExpectedInteger(String),
+
+    /// A `char` was expected, but the string found has zero or several characters.
+    ExpectedSingleChar(String),
+
+    /// A `\u` escape in a string or identifier is half of a UTF-16 surrogate pair without the other
+    /// half, so isn&rsquo;t a character. The escape is included as written.
+    InvalidEscape(String),
+
+    /// An object has the same key more than once, and the deserializer is set to deny duplicate
+    /// keys.
+    DuplicateKey(String),
+
+    /// An object deserialized into a struct has a key that isn&rsquo;t one of its fields, either
+    /// because the struct is `#[serde(deny_unknown_fields)]` or because the deserializer is set to
+    /// deny unknown fields.
+    UnknownField {
+        /// The key, with any escapes resolved.
+        field: String,
+        /// The fields of the struct.
+        expected: &'static [&'static str],
+    },
+
+    /// The path given to [`from_str_at`][] doesn&rsquo;t lead to a value in the document, because
+    /// an object doesn&rsquo;t have the key, an array is too short, or a key or index was used on
+    /// something other than an object or array.
+    ///
+    /// [`from_str_at`]: fn.from_str_at.html
+    PathNotFound {
+        /// The path up to and including the first key or index that wasn&rsquo;t found, as
+        /// written.
+        path: String,
+    },
+
+    /// The value of an object entry was asked for when there wasn&rsquo;t one, because the
+    /// `Deserialize` implementation driving the deserializer didn&rsquo;t ask for its key first.
+    ExpectedMapValue,
+
+    /// The parser produced a grammar rule where the deserializer didn&rsquo;t expect one. This is a
+    /// bug in the crate rather than a problem with the input, but is reported as an error rather
+    /// than a panic.
+    Unexpected(Rule),
+
+    /// A map being serialized has a key that can&rsquo;t be written as a string, such as a tuple
+    /// or a struct.
+    KeyMustBeString,
+
+    /// The input bytes aren't valid UTF-8.
+    InvalidUtf8(Utf8Error),
+
+    /// An error from the underlying reader or writer, converted to its message.
+    Io(String),
+}
+
+/// The kinds of value that can appear in a JSON5 document, for reporting what was found where
+/// something else was expected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueKind {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool,
+    /// Any number, including `Infinity` and `NaN`.
+    Number,
+    /// A quoted string (or an unquoted object key).
+    String,
+    /// An array.
+    Array,
+    /// An object.
+    Object,
+}
+
+impl Display for ValueKind {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(match *self {
+            ValueKind::Null => "null",
+            ValueKind::Bool => "a boolean",
+            ValueKind::Number => "a number",
+            ValueKind::String => "a string",
+            ValueKind::Array => "an array",
+            ValueKind::Object => "an object",
+        })
+    }
+}
+
+/// One-based line and column numbers of a position in the input, where columns count `char`s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Location {
+    /// The line number, starting from 1.
+    pub line: usize,
+    /// The column number, starting from 1.
+    pub column: usize,
+}
+
+/// A range of byte offsets into the input, from `start` (inclusive) to `end` (exclusive).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    /// The offset of the first byte.
+    pub start: usize,
+    /// The offset just past the last byte.
+    pub end: usize,
+}
+
+impl Error {
+    /// Where in the input the error occurred, if known.
+    pub fn location(&self) -> Option<Location> {
+        match *self {
+            Error::Parse { location, .. }
+            | Error::TrailingCharacters { location }
+            | Error::BareIdentifierNotAllowed { location, .. }
+            | Error::InvalidKey { location, .. }
+            | Error::RecursionLimitExceeded { location, .. }
+            | Error::DisabledExtension { location, .. }
+            | Error::MisplacedUnderscore { location, .. }
+            | Error::NotAnEnum { location, .. }
+            | Error::NotATuple { location, .. }
+            | Error::NotAStruct { location, .. }
+            | Error::ExpectedNull { location, .. }
+            | Error::ExpectedBool { location, .. }
+            | Error::ExpectedNumber { location, .. }
+            | Error::TypeMismatch { location, .. }
+            | Error::IntegerOutOfRange { location, .. } => Some(location),
+            _ => None,
+        }
+    }
+
+    /// The byte range of the value that caused the error, if known.
+    pub fn span(&self) -> Option<Span> {
+        match *self {
+            Error::InvalidKey { span, .. }
+            | Error::DisabledExtension { span, .. }
+            | Error::MisplacedUnderscore { span, .. }
+            | Error::NotAnEnum { span, .. }
+            | Error::NotATuple { span, .. }
+            | Error::NotAStruct { span, .. }
+            | Error::ExpectedNull { span, .. }
+            | Error::ExpectedBool { span, .. }
+            | Error::ExpectedNumber { span, .. }
+            | Error::TypeMismatch { span, .. }
+            | Error::IntegerOutOfRange { span, .. } => Some(span),
+            _ => None,
+        }
+    }
+
+    /// The line of the input on which the error occurred, if known.
+    pub fn line(&self) -> Option<usize> {
+        self.location().map(|location| location.line)
+    }
+
+    /// The column of the input at which the error occurred, if known.
+    pub fn column(&self) -> Option<usize> {
+        self.location().map(|location| location.column)
+    }
+
+    /// Displays the error followed by the line of `input` it occurred on, with carets under the
+    /// value that caused it (or just where it is, without a [`span`](#method.span)), as a command
+    /// line tool might show it. `input` must be the text the error came from. Errors without a
+    /// [`location`](#method.location) are displayed as usual, as are syntax errors, whose message
+    /// from the parser already shows the line.
+    ///
+    /// ```rust
+    /// let input = "{\n  timeout: 30,\n  port: 'eighty',\n}";
+    /// let err = json5::from_str::<std::collections::HashMap<String, u16>>(input).unwrap_err();
+    /// assert_eq!(
+    ///     err.display_with_source(input).to_string(),
+    ///     "expected a number, found a string at line 3 column 9\n   |\n 3 |   port: 'eighty',\n   |         ^^^^^^^^",
+    /// );
+    /// ```
+    pub fn display_with_source<'a>(&'a self, input: &'a str) -> impl Display + 'a {
+        WithSource { error: self, input }
+    }
+}
+
+struct WithSource<'a> {
+    error: &'a Error,
+    input: &'a str,
+}
+
+impl Display for WithSource<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self.error, formatter)?;
+        let location = match (self.error, self.error.location()) {
+            (Error::Parse { .. }, _) | (_, None) => return Ok(()),
+            (_, Some(location)) => location,
+        };
+        let line = match self.input.lines().nth(location.line - 1) {
+            Some(line) => line,
+            None => return Ok(()),
+        };
+        // Tabs are kept so that the carets line up however wide they are shown.
+        let indent: String = line
+            .chars()
+            .take(location.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let width = self.error.span().map_or(1, |span| {
+            let text = self.input.get(span.start..span.end).unwrap_or_default();
+            text.chars().take_while(|&c| c != '\n').count().max(1)
+        });
+        let number = location.line.to_string();
+        let gutter = " ".repeat(number.len() + 1);
+        write!(
+            formatter,
+            "\n{gutter} |\n {number} | {line}\n{gutter} | {indent}{carets}",
+            gutter = gutter,
+            number = number,
+            line = line,
+            indent = indent,
+            carets = "^".repeat(width),
+        )
+    }
 }
 
 impl From<pest::error::Error<Rule>> for Error {
     fn from(err: pest::error::Error<Rule>) -> Self {
-        Error::Message(err.to_string())
+        let (line, column) = match err.line_col {
+            LineColLocation::Pos(start) | LineColLocation::Span(start, _) => start,
+        };
+        Error::Parse {
+            msg: err.to_string(),
+            location: Location { line, column },
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err.to_string())
+    }
+}
+
+impl From<Utf8Error> for Error {
+    fn from(err: Utf8Error) -> Self {
+        Error::InvalidUtf8(err)
     }
 }
 
@@ -31,18 +457,162 @@ impl de::Error for Error {
     fn custom<T: Display>(msg: T) -> Self {
         Error::Message(msg.to_string())
     }
+
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+        Error::UnknownField {
+            field: field.to_string(),
+            expected,
+        }
+    }
 }
 
 impl Display for Error {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-        formatter.write_str(std::error::Error::description(self))
+        match *self {
+            Error::Message(ref msg) | Error::Parse { ref msg, .. } | Error::Io(ref msg) => {
+                formatter.write_str(msg)
+            }
+            Error::EmptyDocument => formatter.write_str("expected a JSON5 value, found nothing"),
+            Error::InputTooLarge { limit } => {
+                write!(formatter, "input is longer than the limit of {} bytes", limit)
+            }
+            Error::TrailingCharacters { location } => write!(
+                formatter,
+                "trailing characters after the JSON5 value at line {} column {}",
+                location.line, location.column
+            ),
+            Error::BareIdentifierNotAllowed {
+                ref identifier,
+                location,
+            } => write!(
+                formatter,
+                "unquoted string `{}` at line {} column {}, only object keys can be unquoted",
+                identifier, location.line, location.column
+            ),
+            Error::InvalidKey { found, location, .. } => write!(
+                formatter,
+                "expected an identifier or a string as an object key, found {} at line {} column {}",
+                found, location.line, location.column
+            ),
+            Error::RecursionLimitExceeded { limit, location } => write!(
+                formatter,
+                "nested more than {} levels deep at line {} column {}",
+                limit, location.line, location.column
+            ),
+            Error::DisabledExtension { kind, location, .. } => write!(
+                formatter,
+                "{} are not JSON5 at line {} column {}, enable them with `{}`",
+                kind,
+                location.line,
+                location.column,
+                kind.option()
+            ),
+            Error::MisplacedUnderscore { location, .. } => write!(
+                formatter,
+                "underscores in numbers are only allowed between two digits at line {} column {}",
+                location.line, location.column
+            ),
+            Error::NotAnEnum { found, location, .. } => write!(
+                formatter,
+                "expected a string or an object, found {} at line {} column {}",
+                found, location.line, location.column
+            ),
+            Error::NotATuple { found, location, .. } => write!(
+                formatter,
+                "expected an array, found {} at line {} column {}",
+                found, location.line, location.column
+            ),
+            Error::NotAStruct { found, location, .. } => write!(
+                formatter,
+                "expected an object, found {} at line {} column {}",
+                found, location.line, location.column
+            ),
+            Error::ExpectedNull { found, location, .. } => write!(
+                formatter,
+                "expected null, found {} at line {} column {}",
+                found, location.line, location.column
+            ),
+            Error::ExpectedBool { found, location, .. } => write!(
+                formatter,
+                "expected a boolean, found {} at line {} column {}",
+                found, location.line, location.column
+            ),
+            Error::ExpectedNumber { found, location, .. } => write!(
+                formatter,
+                "expected a number, found {} at line {} column {}",
+                found, location.line, location.column
+            ),
+            Error::TypeMismatch {
+                expected,
+                found,
+                location,
+                ..
+            } => write!(
+                formatter,
+                "expected {}, found {} at line {} column {}",
+                expected, found, location.line, location.column
+            ),
+            Error::WrongTupleLength { expected, found } => write!(
+                formatter,
+                "expected an array of length {}, found one of length {}",
+                expected, found
+            ),
+            Error::IntegerOutOfRange {
+                ref number,
+                expected,
+                location,
+                ..
+            } => write!(
+                formatter,
+                "expected {}, found {} at line {} column {}",
+                expected, number, location.line, location.column
+            ),
+            Error::InvalidNumber(ref s) => write!(formatter, "invalid number {}", s),
+            Error::ExpectedInteger(ref s) => write!(formatter, "expected an integer, found {}", s),
+            Error::ExpectedSingleChar(ref s) => write!(
+                formatter,
+                "expected a string containing a single character, found {:?}",
+                s
+            ),
+            Error::InvalidEscape(ref escape) => write!(formatter, "invalid escape {}", escape),
+            Error::DuplicateKey(ref key) => write!(formatter, "duplicate key {:?}", key),
+            Error::PathNotFound { ref path } => write!(formatter, "no value at path `{}`", path),
+            Error::UnknownField {
+                ref field,
+                expected,
+            } => {
+                write!(formatter, "unknown field `{}`, ", field)?;
+                match expected {
+                    [] => formatter.write_str("there are no fields"),
+                    [only] => write!(formatter, "expected `{}`", only),
+                    [first, rest @ ..] => {
+                        write!(formatter, "expected one of `{}`", first)?;
+                        rest.iter()
+                            .try_for_each(|field| write!(formatter, ", `{}`", field))
+                    }
+                }
+            }
+            Error::ExpectedMapValue => formatter.write_str("expected an object value"),
+            Error::KeyMustBeString => formatter.write_str(
+                "an object key must be a string, or a number, boolean or char to be written as one",
+            ),
+            Error::Unexpected(rule) => write!(formatter, "unexpected {:?} in the parse tree", rule),
+            Error::InvalidUtf8(ref err) => Display::fmt(err, formatter),
+        }
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for Error {
-    fn description(&self) -> &str {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match *self {
-            Error::Message(ref msg) => msg,
+            Error::InvalidUtf8(ref err) => Some(err),
+            _ => None,
         }
     }
 }
+
+// Without `std`, serde's error traits need its stand-in for `std::error::Error` instead, which is
+// `core::error::Error` only on compilers new enough to have it.
+#[cfg(not(feature = "std"))]
+impl serde::de::StdError for Error {}
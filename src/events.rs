@@ -0,0 +1,152 @@
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use pest::iterators::{Pair, Pairs};
+use serde::de::Deserialize;
+
+use crate::de::{parse_string, parse_text, Deserializer, Rule, Settings, DEFAULT_RECURSION_LIMIT};
+use crate::error::{Error, Result};
+use crate::number::Number;
+use crate::value::Value;
+
+/// Parses a JSON5 document into a sequence of [`Event`][]s, for tools such as indexers and
+/// transformation pipelines that want to walk every value in order without deserializing the
+/// document into a Rust type or building a [`Value`][] for it. Fails with the error
+/// [`from_str`][] would give for bad syntax or trailing characters, and otherwise yields an error
+/// (and then nothing) for a value that [`from_str`][] would also reject, such as an octal number.
+///
+/// Strings and keys are borrowed from the input unless they contain escapes.
+///
+/// ```rust
+/// use json5::Event;
+///
+/// let events = json5::events("{ a: [1, 'x'], b: null }")
+///     .unwrap()
+///     .collect::<json5::Result<Vec<_>>>()
+///     .unwrap();
+/// assert_eq!(
+///     events,
+///     vec![
+///         Event::StartObject,
+///         Event::Key("a".into()),
+///         Event::StartArray,
+///         Event::Number(1u64.into()),
+///         Event::String("x".into()),
+///         Event::EndArray,
+///         Event::Key("b".into()),
+///         Event::Null,
+///         Event::EndObject,
+///     ]
+/// );
+/// ```
+///
+/// [`Event`]: enum.Event.html
+/// [`Value`]: enum.Value.html
+/// [`from_str`]: fn.from_str.html
+pub fn events(input: &str) -> Result<Events<'_>> {
+    Ok(Events {
+        next: Some(parse_text(input, DEFAULT_RECURSION_LIMIT)?),
+        stack: Vec::new(),
+        failed: false,
+    })
+}
+
+/// A step through a JSON5 document, as yielded by [`events`][]. Every `StartObject` or
+/// `StartArray` is matched by an `EndObject` or `EndArray` once the entries or elements in it have
+/// been yielded, and each entry of an object is a `Key` followed by the events for its value.
+///
+/// [`events`]: fn.events.html
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event<'a> {
+    /// The `{` starting an object.
+    StartObject,
+    /// The `}` ending an object.
+    EndObject,
+    /// The `[` starting an array.
+    StartArray,
+    /// The `]` ending an array.
+    EndArray,
+    /// The key of an object entry, with any escapes resolved.
+    Key(Cow<'a, str>),
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool(bool),
+    /// Any number, including `Infinity` and `NaN`.
+    Number(Number),
+    /// A string, with any escapes resolved.
+    String(Cow<'a, str>),
+}
+
+/// An iterator over the [`Event`][]s of a JSON5 document, returned by [`events`][].
+///
+/// [`Event`]: enum.Event.html
+/// [`events`]: fn.events.html
+pub struct Events<'a> {
+    // The value to yield the events of next, if its events have not been started.
+    next: Option<Pair<'a, Rule>>,
+    // The contents of the arrays and objects the iterator is inside, innermost last.
+    stack: Vec<(Rule, Pairs<'a, Rule>)>,
+    failed: bool,
+}
+
+impl<'a> Events<'a> {
+    fn event(&mut self, pair: Pair<'a, Rule>) -> Result<Event<'a>> {
+        match pair.as_rule() {
+            Rule::object => {
+                self.stack.push((Rule::object, pair.into_inner()));
+                Ok(Event::StartObject)
+            }
+            Rule::array => {
+                self.stack.push((Rule::array, pair.into_inner()));
+                Ok(Event::StartArray)
+            }
+            Rule::null => Ok(Event::Null),
+            Rule::string => Ok(Event::String(parse_string(pair)?)),
+            rule @ (Rule::boolean | Rule::number) => {
+                match Value::deserialize(&mut Deserializer::from_pair(pair, Settings::default()))? {
+                    Value::Bool(b) => Ok(Event::Bool(b)),
+                    Value::Number(n) => Ok(Event::Number(n)),
+                    _ => Err(Error::Unexpected(rule)),
+                }
+            }
+            rule => Err(Error::Unexpected(rule)),
+        }
+    }
+
+    fn step(&mut self) -> Option<Result<Event<'a>>> {
+        if let Some(pair) = self.next.take() {
+            return Some(self.event(pair));
+        }
+        let (rule, contents) = self.stack.last_mut()?;
+        let rule = *rule;
+        match contents.next() {
+            // Entries are a key then a value, so after a key the value is next.
+            Some(key) if rule == Rule::object => {
+                self.next = contents.next();
+                Some(parse_string(key).map(Event::Key))
+            }
+            Some(element) => Some(self.event(element)),
+            None => {
+                self.stack.pop();
+                Some(Ok(if rule == Rule::object {
+                    Event::EndObject
+                } else {
+                    Event::EndArray
+                }))
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Events<'a> {
+    type Item = Result<Event<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let event = self.step();
+        self.failed = matches!(event, Some(Err(_)));
+        event
+    }
+}
@@ -0,0 +1,50 @@
+use alloc::format;
+use serde::de::Error as _;
+use serde_json::Number;
+
+use crate::error::{Error, Result};
+use crate::value::Value;
+
+/// Parses a JSON5 document into a `serde_json::Value`, for code that works with serde_json but
+/// wants to accept JSON5 input. Requires the `serde_json` feature.
+///
+/// JSON has no way of writing `NaN`, `Infinity` or `-Infinity`, so any of these in the input is an
+/// error rather than being silently replaced (e.g. by `null`). Integers are kept as integers and
+/// object entries keep their order if serde_json&rsquo;s `preserve_order` feature is enabled.
+///
+/// ```rust
+/// use serde_json::json;
+///
+/// assert_eq!(
+///     json5::to_serde_json("{ port: 0x1F90, hosts: ['a', 'b',], }"),
+///     Ok(json!({ "port": 8080, "hosts": ["a", "b"] })),
+/// );
+/// assert!(json5::to_serde_json("[NaN]").is_err());
+/// ```
+pub fn to_serde_json(input: &str) -> Result<serde_json::Value> {
+    convert(crate::from_str(input)?)
+}
+
+fn convert(value: Value) -> Result<serde_json::Value> {
+    Ok(match value {
+        Value::Null => serde_json::Value::Null,
+        Value::Bool(b) => serde_json::Value::Bool(b),
+        Value::Number(n) => serde_json::Value::Number(if let Some(v) = n.as_u64() {
+            Number::from(v)
+        } else if let Some(v) = n.as_i64() {
+            Number::from(v)
+        } else {
+            Number::from_f64(n.as_f64())
+                .ok_or_else(|| Error::custom(format!("{} can't be represented in JSON", n)))?
+        }),
+        Value::String(s) => serde_json::Value::String(s),
+        Value::Array(a) => {
+            serde_json::Value::Array(a.into_iter().map(convert).collect::<Result<_>>()?)
+        }
+        Value::Object(o) => serde_json::Value::Object(
+            o.into_iter()
+                .map(|(k, v)| Ok((k, convert(v)?)))
+                .collect::<Result<_>>()?,
+        ),
+    })
+}
@@ -80,6 +80,21 @@
 //! );
 //! ```
 //!
+//! With the `serde_json` feature, [`to_serde_json`][] does the same but rejects `NaN` and
+//! `Infinity`, which JSON can&rsquo;t represent (deserializing them into a `serde_json::Value`
+//! directly gives `null`).
+//!
+//! Or, when the shape of the input isn&rsquo;t known ahead of time, into a [`Value`][]
+//!
+//! ```rust
+//! let value: json5::Value = json5::from_str("{ servers: [{ port: 8080 }] }").unwrap();
+//! assert_eq!(value["servers"][0]["port"].as_u64(), Some(8080));
+//! ```
+//!
+//! Types that ask for bytes (such as `serde_bytes::ByteBuf`) accept either a string, giving its
+//! UTF-8 encoding, or an array of integers from 0 to 255, giving one byte per element. They are
+//! serialized as such an array, so they round-trip whether or not they are valid UTF-8.
+//!
 //! There are many ways to customize the deserialization (e.g. deserializing `camelCase` field
 //! names into a struct with `snake_case` fields). See the Serde docs, especially the
 //! [Attributes][], [Custom serialization][] and [Examples][] sections.
@@ -87,9 +102,12 @@
 //! # Serialization
 //!
 //! Similarly, implementing [`Serialize`][] on a Rust type allows you to produce a JSON5
-//! serialization of values of that type with [`to_string`][]. At present the serializer will just
+//! serialization of values of that type with [`to_string`][], or stream it into any
+//! [`io::Write`][] with [`to_writer`][]. At present the serializer will just
 //! produce JSON (since it's a valid subset of JSON5), but future work will allow specifying the
-//! output style (single over double quotes, trailing commas, indentation etc.).
+//! output style (single over double quotes, unquoted keys etc.). Indented output, optionally with
+//! trailing commas, is available through [`to_string_pretty`][] and [`PrettyConfig`][]. Other
+//! output options, such as writing integers in hexadecimal, are set with [`SerializeOptions`][].
 //!
 //! ```rust
 //! use serde_derive::Serialize;
@@ -119,7 +137,7 @@
 //! );
 //! assert_eq!(
 //!     json5::to_string(&Val::Object(map)),
-//!     Ok("{\"a\":[null,true,42,42.42,NaN,\"hello\"]}".to_owned()),
+//!     Ok("{\"a\":[null,true,42.0,42.42,NaN,\"hello\"]}".to_owned()),
 //! )
 //! ```
 //!
@@ -146,7 +164,7 @@
 //! );
 //! assert_eq!(
 //!     json5::to_string(&Value::Object(map)),
-//!     Ok("{\"a\":[null,true,42,42.42,\"hello\"]}".to_owned()),
+//!     Ok("{\"a\":[null,true,42.0,42.42,\"hello\"]}".to_owned()),
 //! )
 //! ```
 //!
@@ -154,16 +172,23 @@
 //! as `camelCase`). See the Serde docs, especially the [Attributes][], [Custom serialization][]
 //! and [Examples][] sections.
 //!
-//! # Limitations
+//! # `no_std`
 //!
-//! At the time of writing the following is unsupported:
+//! The crate works without the standard library, needing only `alloc`, if the default `std`
+//! feature is disabled. Everything except [`from_reader`][], [`to_writer`][] and the methods of
+//! the same names on the options types is still available.
 //!
-//! - deserializing into borrowed types (e.g. fields of type `&str`)
+//! ```toml
+//! [dependencies]
+//! json5 = { version = "0.2", default-features = false }
+//! ```
+//!
+//! # Limitations
 //!
-//! - serializing or deserializing [byte arrays][]
+//! At the time of writing the following is unsupported:
 //!
-//! - specifying the style of JSON5 output from the serializer (single over double quotes, trailing
-//! commas, indentation etc.)
+//! - specifying the style of JSON5 output from the serializer beyond indentation and trailing
+//!   commas (single over double quotes, unquoted keys etc.)
 //!
 //! [JSON]: https://tools.ietf.org/html/rfc7159
 //! [ECMAScript 5.1]: https://www.ecma-international.org/ecma-262/5.1/
@@ -171,20 +196,108 @@
 //! [Serde framework]: https://serde.rs/
 //! [`Deserialize`]: https://docs.serde.rs/serde/de/trait.Deserialize.html
 //! [`from_str`]: fn.from_str.html
+//! [`Value`]: enum.Value.html
+//! [`to_serde_json`]: fn.to_serde_json.html
 //! [Attributes]: https://serde.rs/attributes.html
 //! [Custom serialization]: https://serde.rs/custom-serialization.html
 //! [Examples]: https://serde.rs/examples.html
 //! [`Serialize`]: https://docs.serde.rs/serde/ser/trait.Serialize.html
 //! [`to_string`]: fn.to_string.html
-//! [byte arrays]: https://serde.rs/data-model.html#types
+//! [`io::Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
+//! [`to_string_pretty`]: fn.to_string_pretty.html
+//! [`PrettyConfig`]: struct.PrettyConfig.html
+//! [`SerializeOptions`]: struct.SerializeOptions.html
+//! [`to_writer`]: fn.to_writer.html
+//! [`from_reader`]: fn.from_reader.html
 
+#![no_std]
 #![warn(missing_docs)]
 #![warn(rust_2018_idioms)]
 
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 mod de;
+#[cfg(feature = "decimal")]
+pub mod decimal;
 mod error;
+mod events;
+#[cfg(feature = "serde_json")]
+mod json;
+mod macros;
+pub mod map;
+mod number;
+mod parse;
 mod ser;
+mod spanned;
+mod value;
+mod warning;
+
+#[cfg(feature = "std")]
+pub use crate::de::from_reader;
+pub use crate::de::{
+    from_slice, from_str, from_str_at, from_str_limited, from_str_with_warnings,
+    DeserializeOptions, Deserializer, Rule, StreamDeserializer,
+};
+pub use crate::error::{Error, Location, Result, Span, ValueKind};
+pub use crate::events::{events, Event, Events};
+#[cfg(feature = "serde_json")]
+pub use crate::json::to_serde_json;
+pub use crate::map::Map;
+pub use crate::number::Number;
+pub use crate::parse::{
+    parse, validate, AttachedComment, Comment, Node, ParsedDocument, PathSegment,
+};
+#[cfg(feature = "std")]
+pub use crate::ser::to_writer;
+pub use crate::ser::{to_string, to_string_pretty, NumberFormat, PrettyConfig, SerializeOptions};
+pub use crate::spanned::{Spanned, SpannedValue};
+pub use crate::value::{from_value, MergeStrategy, Value};
+pub use crate::warning::{Warning, WarningKind};
+
+/// The pest parser generated from the JSON5 grammar, for tools such as syntax highlighters that
+/// want to reuse it rather than write their own. Enabled by the `grammar` feature.
+///
+/// Parsing [`Rule::text`][] gives the tree of a whole document, with a node for each value,
+/// unquoted key and string part, but nothing for punctuation, whitespace or comments (which can
+/// be found between the nodes). Only the rules listed on [`Rule`][] as stable should be relied on
+/// by name. The version of pest is re-exported so that the parser&rsquo;s trait doesn&rsquo;t have
+/// to be matched with a separate dependency.
+///
+/// ```rust
+/// use json5::grammar::pest::Parser as _;
+/// use json5::grammar::{Parser, Rule};
+///
+/// let pairs = Parser::parse(Rule::text, "{ name: 'json5', stars: 1e3 }").unwrap();
+/// let mut tokens = Vec::new();
+/// for pair in pairs.flatten() {
+///     let class = match pair.as_rule() {
+///         Rule::identifier => "key",
+///         Rule::string => "string",
+///         Rule::number | Rule::boolean | Rule::null => "literal",
+///         _ => continue,
+///     };
+///     tokens.push((class, pair.as_str()));
+/// }
+/// assert_eq!(
+///     tokens,
+///     [("key", "name"), ("string", "'json5'"), ("key", "stars"), ("literal", "1e3")]
+/// );
+/// ```
+///
+/// [`Rule::text`]: enum.Rule.html#variant.text
+/// [`Rule`]: enum.Rule.html
+#[cfg(feature = "grammar")]
+pub mod grammar {
+    pub use crate::de::grammar::Parser;
+    pub use crate::de::Rule;
+    pub use pest;
+}
 
-pub use crate::de::from_str;
-pub use crate::error::{Error, Result};
-pub use crate::ser::to_string;
+// Used by the `json5!` macro.
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::string::{String, ToString};
+    pub use alloc::vec;
+}
@@ -0,0 +1,168 @@
+/// Builds a [`Value`][] from JSON5-like syntax, as far as Rust&rsquo;s tokenizer allows: keys may
+/// be identifiers, string or number literals, or any expression in parentheses, and arrays and
+/// objects may have trailing commas. Other values may be any expression that converts to a
+/// `Value` with `From`.
+///
+/// ```rust
+/// use json5::{json5, Value};
+///
+/// let port = 8080;
+/// let value = json5!({
+///     name: "server",
+///     "listen": [port, 8443],
+///     tls: { enabled: true, cert: null },
+///     (format!("{}_id", "user")): -1,
+/// });
+/// assert_eq!(value["listen"][0], Value::from(8080));
+/// assert!(value["tls"]["cert"].is_null());
+/// assert_eq!(value["user_id"].as_i64(), Some(-1));
+/// assert_eq!(value, json5::from_str(r#"{
+///     name: 'server',
+///     listen: [8080, 8443],
+///     tls: { enabled: true, cert: null },
+///     user_id: -1,
+/// }"#).unwrap());
+/// ```
+///
+/// [`Value`]: enum.Value.html
+#[macro_export]
+macro_rules! json5 {
+    ($($json:tt)+) => {
+        $crate::__json5_internal!($($json)+)
+    };
+}
+
+// Munches the tokens of an array or object one element or entry at a time, much like serde_json's
+// `json!`. The elements built so far are collected in the square brackets.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __json5_internal {
+    (@array [$($elems:expr,)*]) => {
+        $crate::__private::vec![$($elems,)*]
+    };
+    (@array [$($elems:expr),*]) => {
+        $crate::__private::vec![$($elems),*]
+    };
+    (@array [$($elems:expr,)*] null $($rest:tt)*) => {
+        $crate::__json5_internal!(@array [$($elems,)* $crate::__json5_internal!(null)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] true $($rest:tt)*) => {
+        $crate::__json5_internal!(@array [$($elems,)* $crate::__json5_internal!(true)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] false $($rest:tt)*) => {
+        $crate::__json5_internal!(@array [$($elems,)* $crate::__json5_internal!(false)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        $crate::__json5_internal!(
+            @array [$($elems,)* $crate::__json5_internal!([$($array)*])] $($rest)*
+        )
+    };
+    (@array [$($elems:expr,)*] {$($object:tt)*} $($rest:tt)*) => {
+        $crate::__json5_internal!(
+            @array [$($elems,)* $crate::__json5_internal!({$($object)*})] $($rest)*
+        )
+    };
+    (@array [$($elems:expr,)*] $next:expr, $($rest:tt)*) => {
+        $crate::__json5_internal!(@array [$($elems,)* $crate::__json5_internal!($next),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $last:expr) => {
+        $crate::__json5_internal!(@array [$($elems,)* $crate::__json5_internal!($last)])
+    };
+    (@array [$($elems:expr),*] , $($rest:tt)*) => {
+        $crate::__json5_internal!(@array [$($elems,)*] $($rest)*)
+    };
+
+    // An object is munched as `(key) (rest)` until the value is known, then inserted.
+    (@object $object:ident () ()) => {};
+    (@object $object:ident [$key:expr] ($value:expr) , $($rest:tt)*) => {
+        let _ = $object.insert($key, $value);
+        $crate::__json5_internal!(@object $object () ($($rest)*));
+    };
+    (@object $object:ident [$key:expr] ($value:expr)) => {
+        let _ = $object.insert($key, $value);
+    };
+    (@object $object:ident ($key:tt) (: null $($rest:tt)*)) => {
+        $crate::__json5_internal!(
+            @object $object [$crate::__json5_internal!(@key $key)]
+            ($crate::__json5_internal!(null)) $($rest)*
+        );
+    };
+    (@object $object:ident ($key:tt) (: true $($rest:tt)*)) => {
+        $crate::__json5_internal!(
+            @object $object [$crate::__json5_internal!(@key $key)]
+            ($crate::__json5_internal!(true)) $($rest)*
+        );
+    };
+    (@object $object:ident ($key:tt) (: false $($rest:tt)*)) => {
+        $crate::__json5_internal!(
+            @object $object [$crate::__json5_internal!(@key $key)]
+            ($crate::__json5_internal!(false)) $($rest)*
+        );
+    };
+    (@object $object:ident ($key:tt) (: [$($array:tt)*] $($rest:tt)*)) => {
+        $crate::__json5_internal!(
+            @object $object [$crate::__json5_internal!(@key $key)]
+            ($crate::__json5_internal!([$($array)*])) $($rest)*
+        );
+    };
+    (@object $object:ident ($key:tt) (: {$($inner:tt)*} $($rest:tt)*)) => {
+        $crate::__json5_internal!(
+            @object $object [$crate::__json5_internal!(@key $key)]
+            ($crate::__json5_internal!({$($inner)*})) $($rest)*
+        );
+    };
+    (@object $object:ident ($key:tt) (: $value:expr , $($rest:tt)*)) => {
+        $crate::__json5_internal!(
+            @object $object [$crate::__json5_internal!(@key $key)]
+            ($crate::__json5_internal!($value)) , $($rest)*
+        );
+    };
+    (@object $object:ident ($key:tt) (: $value:expr)) => {
+        $crate::__json5_internal!(
+            @object $object [$crate::__json5_internal!(@key $key)]
+            ($crate::__json5_internal!($value))
+        );
+    };
+    (@object $object:ident () ($key:tt $($rest:tt)*)) => {
+        $crate::__json5_internal!(@object $object ($key) ($($rest)*));
+    };
+
+    (@key $key:ident) => {
+        $crate::__private::String::from(stringify!($key))
+    };
+    (@key ($key:expr)) => {
+        $crate::__private::ToString::to_string(&$key)
+    };
+    (@key $key:literal) => {
+        $crate::__private::ToString::to_string(&$key)
+    };
+
+    (null) => {
+        $crate::Value::Null
+    };
+    (true) => {
+        $crate::Value::Bool(true)
+    };
+    (false) => {
+        $crate::Value::Bool(false)
+    };
+    ([]) => {
+        $crate::Value::Array($crate::__private::vec![])
+    };
+    ([ $($tt:tt)+ ]) => {
+        $crate::Value::Array($crate::__json5_internal!(@array [] $($tt)+))
+    };
+    ({}) => {
+        $crate::Value::Object($crate::Map::new())
+    };
+    ({ $($tt:tt)+ }) => {
+        $crate::Value::Object({
+            let mut object = $crate::Map::new();
+            $crate::__json5_internal!(@object object () ($($tt)+));
+            object
+        })
+    };
+    ($other:expr) => {
+        $crate::Value::from($other)
+    };
+}
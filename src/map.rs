@@ -0,0 +1,331 @@
+//! The [`Map`][] type used for the entries of a JSON5 object, and its iterators.
+//!
+//! [`Map`]: struct.Map.html
+
+use alloc::string::String;
+use core::fmt;
+use core::iter::FromIterator;
+use serde::ser;
+
+use crate::value::Value;
+
+#[cfg(not(feature = "preserve_order"))]
+type MapImpl<K, V> = alloc::collections::BTreeMap<K, V>;
+#[cfg(feature = "preserve_order")]
+type MapImpl<K, V> = indexmap::IndexMap<K, V>;
+
+#[cfg(not(feature = "preserve_order"))]
+use alloc::collections::btree_map as imp;
+#[cfg(feature = "preserve_order")]
+use indexmap::map as imp;
+
+// How a key is stored. Only the map sees this type, everything outside it deals in `&str` and
+// `String`.
+#[cfg(not(feature = "smol_str"))]
+pub(crate) type Key = String;
+#[cfg(feature = "smol_str")]
+pub(crate) type Key = smol_str::SmolStr;
+
+#[cfg(not(feature = "smol_str"))]
+fn into_key(key: String) -> Key {
+    key
+}
+
+#[cfg(feature = "smol_str")]
+fn into_key(key: String) -> Key {
+    Key::from(key)
+}
+
+#[cfg(not(feature = "smol_str"))]
+fn from_key(key: Key) -> String {
+    key
+}
+
+#[cfg(feature = "smol_str")]
+fn from_key(key: Key) -> String {
+    String::from(key)
+}
+
+/// Makes the keys of the objects of one `Value` as it&rsquo;s deserialized. With the `smol_str`
+/// feature short keys are stored inline, and every longer key shares the allocation of the first
+/// one equal to it, so an array of objects with the same keys holds on to each key only once.
+#[derive(Default)]
+pub(crate) struct Interner {
+    #[cfg(feature = "smol_str")]
+    keys: alloc::collections::BTreeSet<Key>,
+}
+
+impl Interner {
+    pub(crate) fn intern(&mut self, key: &str) -> Key {
+        #[cfg(not(feature = "smol_str"))]
+        return key.into();
+        #[cfg(feature = "smol_str")]
+        {
+            let new = Key::new(key);
+            if !new.is_heap_allocated() {
+                return new;
+            }
+            if let Some(old) = self.keys.get(key) {
+                return old.clone();
+            }
+            self.keys.insert(new.clone());
+            new
+        }
+    }
+
+    pub(crate) fn intern_string(&mut self, key: String) -> Key {
+        #[cfg(not(feature = "smol_str"))]
+        return key;
+        #[cfg(feature = "smol_str")]
+        return self.intern(&key);
+    }
+}
+
+/// The entries of a [`Value::Object`][], keyed by property name.
+///
+/// By default entries are kept sorted by key. With the `preserve_order` feature they are kept in
+/// the order they were inserted, which for a parsed document is the order they appear in the
+/// input. Either way iteration order is deterministic, and the API is the same so that enabling
+/// the feature can&rsquo;t break code elsewhere in the dependency graph.
+///
+/// Every object holds its own keys as `String`s, along with the map structure itself. For an array
+/// of many small objects with the same keys that makes a [`Value`][] several times larger than
+/// the same data deserialized into structs, which keep neither. With the `smol_str` feature keys
+/// of up to 23 bytes are stored inline instead, and longer ones are shared between all the objects
+/// of the document that have them, which saves most of the memory taken by keys. Either way large
+/// documents of uniform objects are best deserialized into structs where their shape is known.
+///
+/// ```rust
+/// let value: json5::Value = json5::from_str("{ b: 'x', a: 'y' }").unwrap();
+/// let object = value.as_object().unwrap();
+///
+/// # #[cfg(not(feature = "preserve_order"))]
+/// assert_eq!(object.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+/// # #[cfg(feature = "preserve_order")]
+/// # assert_eq!(object.keys().collect::<Vec<_>>(), vec!["b", "a"]);
+/// for (key, value) in object {
+///     println!("{} = {}", key, value.as_str().unwrap());
+/// }
+/// ```
+///
+/// [`Value::Object`]: enum.Value.html#variant.Object
+/// [`Value`]: enum.Value.html
+#[derive(Clone, Default, PartialEq)]
+pub struct Map {
+    map: MapImpl<Key, Value>,
+}
+
+impl Map {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Map {
+            map: MapImpl::new(),
+        }
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
+
+    /// Returns the value for `key`, if there is one.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.map.get(key)
+    }
+
+    /// Returns a mutable reference to the value for `key`, if there is one.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.map.get_mut(key)
+    }
+
+    /// Returns true if there is a value for `key`.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Inserts a value, returning the one it replaced if `key` was already present. Replacing a
+    /// value doesn&rsquo;t change where the key comes in iteration order.
+    pub fn insert(&mut self, key: String, value: Value) -> Option<Value> {
+        self.map.insert(into_key(key), value)
+    }
+
+    pub(crate) fn insert_key(&mut self, key: Key, value: Value) -> Option<Value> {
+        self.map.insert(key, value)
+    }
+
+    /// Removes and returns the value for `key`, if there is one, keeping the order of the other
+    /// entries.
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        #[cfg(not(feature = "preserve_order"))]
+        return self.map.remove(key);
+        #[cfg(feature = "preserve_order")]
+        return self.map.shift_remove(key);
+    }
+
+    /// Iterates over the entries.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            iter: self.map.iter(),
+        }
+    }
+
+    /// Iterates over the entries, the same as [`iter`](#method.iter).
+    pub fn entries(&self) -> Iter<'_> {
+        self.iter()
+    }
+
+    /// Iterates over the entries, with mutable references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
+        IterMut {
+            iter: self.map.iter_mut(),
+        }
+    }
+
+    /// Iterates over the keys.
+    pub fn keys(&self) -> Keys<'_> {
+        Keys {
+            iter: self.map.keys(),
+        }
+    }
+
+    /// Iterates over the values.
+    pub fn values(&self) -> Values<'_> {
+        Values {
+            iter: self.map.values(),
+        }
+    }
+
+    /// Iterates over mutable references to the values.
+    pub fn values_mut(&mut self) -> ValuesMut<'_> {
+        ValuesMut {
+            iter: self.map.values_mut(),
+        }
+    }
+}
+
+impl fmt::Debug for Map {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Serializes the entries in iteration order.
+impl ser::Serialize for Map {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.collect_map(self)
+    }
+}
+
+impl FromIterator<(String, Value)> for Map {
+    fn from_iter<I: IntoIterator<Item = (String, Value)>>(iter: I) -> Self {
+        Map {
+            map: iter.into_iter().map(|(k, v)| (into_key(k), v)).collect(),
+        }
+    }
+}
+
+impl Extend<(String, Value)> for Map {
+    fn extend<I: IntoIterator<Item = (String, Value)>>(&mut self, iter: I) {
+        self.map
+            .extend(iter.into_iter().map(|(k, v)| (into_key(k), v)))
+    }
+}
+
+macro_rules! iterator {
+    ($(#[$doc:meta])* $name:ident $(<$lt:lifetime>)?, $imp:ty, $item:ty $(, $map:expr)?) => {
+        $(#[$doc])*
+        pub struct $name $(<$lt>)? {
+            iter: $imp,
+        }
+
+        impl $(<$lt>)? Iterator for $name $(<$lt>)? {
+            type Item = $item;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                self.iter.next()$(.map($map))?
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.iter.size_hint()
+            }
+        }
+
+        impl $(<$lt>)? DoubleEndedIterator for $name $(<$lt>)? {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                self.iter.next_back()$(.map($map))?
+            }
+        }
+
+        impl $(<$lt>)? ExactSizeIterator for $name $(<$lt>)? {}
+    };
+}
+
+iterator!(
+    /// An iterator over the entries of a [`Map`](struct.Map.html).
+    Iter<'a>, imp::Iter<'a, Key, Value>, (&'a str, &'a Value), |(k, v)| (k.as_str(), v)
+);
+iterator!(
+    /// An iterator over the entries of a [`Map`](struct.Map.html), with mutable values.
+    IterMut<'a>,
+    imp::IterMut<'a, Key, Value>,
+    (&'a str, &'a mut Value),
+    |(k, v)| (k.as_str(), v)
+);
+iterator!(
+    /// An owning iterator over the entries of a [`Map`](struct.Map.html).
+    IntoIter, imp::IntoIter<Key, Value>, (String, Value), |(k, v)| (from_key(k), v)
+);
+iterator!(
+    /// An iterator over the keys of a [`Map`](struct.Map.html).
+    Keys<'a>, imp::Keys<'a, Key, Value>, &'a str, |k| k.as_str()
+);
+iterator!(
+    /// An iterator over the values of a [`Map`](struct.Map.html).
+    Values<'a>, imp::Values<'a, Key, Value>, &'a Value
+);
+iterator!(
+    /// An iterator over mutable references to the values of a [`Map`](struct.Map.html).
+    ValuesMut<'a>, imp::ValuesMut<'a, Key, Value>, &'a mut Value
+);
+
+impl IntoIterator for Map {
+    type Item = (String, Value);
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        IntoIter {
+            iter: self.map.into_iter(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Map {
+    type Item = (&'a str, &'a Value);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Map {
+    type Item = (&'a str, &'a mut Value);
+    type IntoIter = IterMut<'a>;
+
+    fn into_iter(self) -> IterMut<'a> {
+        self.iter_mut()
+    }
+}
@@ -0,0 +1,262 @@
+use alloc::string::ToString;
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+use core::fmt;
+use serde::{de, ser};
+
+use crate::error::Error;
+
+/// A JSON5 number, which remembers whether it was written as an integer or not.
+///
+/// Integer literals (decimal or hexadecimal, without a decimal point or exponent) that fit in an
+/// `i64` or `u64` are stored exactly. Everything else, including `Infinity` and `NaN`, is stored as
+/// an `f64`.
+///
+/// ```rust
+/// use json5::Value;
+///
+/// let value: Value = json5::from_str("[5, 5.0, -0x10]").unwrap();
+/// assert_eq!(value[0].as_i64(), Some(5));
+/// assert_eq!(value[1].as_i64(), None);
+/// assert_eq!(value[1].as_f64(), Some(5.));
+/// assert_eq!(value[2].as_i64(), Some(-16));
+/// ```
+///
+/// Numbers are equal if they are stored the same way and have the same value, so `5` and `5.0`
+/// are not equal, and as for `f64`, `NaN` is not equal to itself and `-0.0` is equal to `0.0`. Use
+/// [`total_cmp`][] for the order of numbers by value.
+///
+/// [`total_cmp`]: #method.total_cmp
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Number {
+    n: N,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum N {
+    // Always non-negative.
+    PosInt(u64),
+    // Always negative.
+    NegInt(i64),
+    Float(f64),
+}
+
+impl Number {
+    /// Returns true if the number is an integer that fits in an `i64`.
+    pub fn is_i64(&self) -> bool {
+        match self.n {
+            N::PosInt(v) => v <= i64::MAX as u64,
+            N::NegInt(_) => true,
+            N::Float(_) => false,
+        }
+    }
+
+    /// Returns true if the number is a non-negative integer that fits in a `u64`.
+    pub fn is_u64(&self) -> bool {
+        matches!(self.n, N::PosInt(_))
+    }
+
+    /// Returns true if the number was not written as an integer (or was too large to be stored as
+    /// one).
+    pub fn is_f64(&self) -> bool {
+        matches!(self.n, N::Float(_))
+    }
+
+    /// Returns the number as an `i64` if it is an integer that fits.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.n {
+            N::PosInt(v) if v <= i64::MAX as u64 => Some(v as i64),
+            N::NegInt(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the number as a `u64` if it is a non-negative integer.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.n {
+            N::PosInt(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the number as an `f64`, which may lose precision for large integers.
+    pub fn as_f64(&self) -> f64 {
+        match self.n {
+            N::PosInt(v) => v as f64,
+            N::NegInt(v) => v as f64,
+            N::Float(v) => v,
+        }
+    }
+
+    /// Orders numbers by value, however they are stored, without losing precision. Every number
+    /// has a place in the order: an integer comes just before a float with the same value, except
+    /// that `-0.0` comes just before `0`, and `NaN` comes after `Infinity`. Only numbers that are equal
+    /// (`==`), and any two `NaN`s, compare as `Equal`.
+    ///
+    /// ```rust
+    /// use json5::Number;
+    /// use std::cmp::Ordering;
+    ///
+    /// let mut numbers: Vec<Number> = vec![f64::NAN.into(), 2.5.into(), 3u64.into(), (-1i64).into()];
+    /// numbers.sort_by(Number::total_cmp);
+    /// assert_eq!(numbers[..3], [(-1i64).into(), 2.5.into(), 3u64.into()]);
+    /// assert!(numbers[3].as_f64().is_nan());
+    ///
+    /// assert_eq!(Number::from(5u64).total_cmp(&5.0.into()), Ordering::Less);
+    /// ```
+    pub fn total_cmp(&self, other: &Number) -> Ordering {
+        match (self.n, other.n) {
+            (N::Float(a), N::Float(b)) => cmp_floats(a, b),
+            (N::Float(a), _) => cmp_float_to_integer(a, other.as_i128()),
+            (_, N::Float(b)) => cmp_float_to_integer(b, self.as_i128()).reverse(),
+            _ => self.as_i128().cmp(&other.as_i128()),
+        }
+    }
+
+    // Only called for integers, which all fit in an `i128`.
+    fn as_i128(&self) -> i128 {
+        match self.n {
+            N::PosInt(v) => i128::from(v),
+            N::NegInt(v) => i128::from(v),
+            N::Float(v) => v as i128,
+        }
+    }
+}
+
+fn cmp_floats(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        // Neither is NaN, so they're ordered, unless they're zeros of different signs.
+        (false, false) => a
+            .partial_cmp(&b)
+            .unwrap_or(Ordering::Equal)
+            .then(a.is_sign_negative().cmp(&b.is_sign_negative()).reverse()),
+    }
+}
+
+// Compares exactly, by comparing the integer part of the float as an integer and then the
+// fractional part against zero. Never gives `Equal`, as the float goes just after an integer with
+// the same value (or just before one for `-0.0`).
+fn cmp_float_to_integer(a: f64, b: i128) -> Ordering {
+    // Every integer is within these bounds, which are exactly representable.
+    const MIN: f64 = -9_223_372_036_854_775_808.0;
+    const MAX: f64 = 18_446_744_073_709_551_616.0;
+    if a.is_nan() || a >= MAX {
+        return Ordering::Greater;
+    }
+    if a < MIN {
+        return Ordering::Less;
+    }
+    let whole = a.trunc();
+    (whole as i128)
+        .cmp(&b)
+        .then(a.partial_cmp(&whole).unwrap_or(Ordering::Equal))
+        .then(if a.is_sign_negative() && a == 0. {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        })
+}
+
+impl Number {
+    // Gives the number to a visitor as the type it's stored as.
+    pub(crate) fn visit<'de, V, E>(self, visitor: V) -> Result<V::Value, E>
+    where
+        V: de::Visitor<'de>,
+        E: de::Error,
+    {
+        match self.n {
+            N::PosInt(v) => visitor.visit_u64(v),
+            N::NegInt(v) => visitor.visit_i64(v),
+            N::Float(v) => visitor.visit_f64(v),
+        }
+    }
+
+    // Gives the number to a visitor wanting an integer, truncating a float towards zero as
+    // `from_str` does for a number not written as an integer.
+    pub(crate) fn visit_integer<'de, V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.n {
+            N::Float(v) if !v.is_finite() => Err(Error::ExpectedInteger(self.to_string())),
+            N::Float(v) if v < 0. => match i64::try_from(v as i128) {
+                Ok(v) => visitor.visit_i64(v),
+                Err(_) => visitor.visit_i128(v as i128),
+            },
+            N::Float(v) => match u64::try_from(v as u128) {
+                Ok(v) => visitor.visit_u64(v),
+                Err(_) => visitor.visit_u128(v as u128),
+            },
+            _ => self.visit(visitor),
+        }
+    }
+}
+
+/// Serializes as the type the number is stored as, so integers stay exact.
+impl ser::Serialize for Number {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self.n {
+            N::PosInt(v) => serializer.serialize_u64(v),
+            N::NegInt(v) => serializer.serialize_i64(v),
+            N::Float(v) => serializer.serialize_f64(v),
+        }
+    }
+}
+
+impl From<u64> for Number {
+    fn from(v: u64) -> Self {
+        Number { n: N::PosInt(v) }
+    }
+}
+
+impl From<i64> for Number {
+    fn from(v: i64) -> Self {
+        if v < 0 {
+            Number { n: N::NegInt(v) }
+        } else {
+            Number {
+                n: N::PosInt(v as u64),
+            }
+        }
+    }
+}
+
+impl From<f64> for Number {
+    fn from(v: f64) -> Self {
+        Number { n: N::Float(v) }
+    }
+}
+
+macro_rules! from_smaller {
+    ($($ty:ty => $via:ty),*) => {
+        $(
+            impl From<$ty> for Number {
+                fn from(v: $ty) -> Self {
+                    Number::from(<$via>::from(v))
+                }
+            }
+        )*
+    };
+}
+
+from_smaller!(u8 => u64, u16 => u64, u32 => u64, i8 => i64, i16 => i64, i32 => i64, f32 => f64);
+
+/// Formats the number as it would appear in JSON5, so `NaN` and `Infinity` are written as such.
+impl fmt::Display for Number {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.n {
+            N::PosInt(v) => fmt::Display::fmt(&v, formatter),
+            N::NegInt(v) => fmt::Display::fmt(&v, formatter),
+            N::Float(v) if v.is_nan() => formatter.write_str("NaN"),
+            N::Float(v) if v == f64::INFINITY => formatter.write_str("Infinity"),
+            N::Float(v) if v == f64::NEG_INFINITY => formatter.write_str("-Infinity"),
+            N::Float(v) => fmt::Debug::fmt(&v, formatter),
+        }
+    }
+}
@@ -0,0 +1,391 @@
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use pest::iterators::Pair;
+use serde::de;
+
+use crate::de::{
+    kind_of, locate, parse_text, Deserializer, Rule, Settings, DEFAULT_RECURSION_LIMIT,
+};
+use crate::error::{Location, Result, Span, ValueKind};
+use crate::spanned::{spanned_value, Spanned, SpannedValue};
+
+/// Parses a JSON5 document without deserializing it, for tools such as linters and formatters
+/// that need to know the structure of the source text rather than build a Rust value from it.
+///
+/// ```rust
+/// use json5::{Rule, ValueKind};
+///
+/// let doc = json5::parse("{ name: 'json5', tags: ['a', 'b'] }").unwrap();
+/// let root = doc.root();
+/// assert_eq!(root.kind(), ValueKind::Object);
+///
+/// let (key, value) = root.entries().nth(1).unwrap();
+/// assert_eq!(key.rule(), Rule::identifier);
+/// assert_eq!(key.as_str(), "tags");
+/// assert_eq!(value.elements().count(), 2);
+/// assert_eq!(value.span(), json5::Span { start: 23, end: 33 });
+/// ```
+pub fn parse(input: &str) -> Result<ParsedDocument<'_>> {
+    let pair = parse_text(input, DEFAULT_RECURSION_LIMIT)?;
+    let source = Rc::new(Source {
+        input,
+        comments: lex_comments(input),
+    });
+    Ok(ParsedDocument {
+        root: Node { pair, source },
+    })
+}
+
+/// Checks that a document is valid JSON5, for tools such as pre-commit hooks that don&rsquo;t want
+/// its contents. Fails with the error [`from_str`][] would give for bad syntax, trailing
+/// characters, octal and binary numbers or invalid escapes, but doesn&rsquo;t check that numbers
+/// and strings fit any particular Rust type.
+///
+/// ```rust
+/// assert!(json5::validate("{ a: [1, 2,], /* ok */ }").is_ok());
+/// assert!(json5::validate("{ a: [1, 2,,] }").is_err());
+/// ```
+///
+/// [`from_str`]: fn.from_str.html
+pub fn validate(input: &str) -> Result<()> {
+    let pair = parse_text(input, DEFAULT_RECURSION_LIMIT)?;
+    Settings::default().check_skipped(&pair)
+}
+
+/// The parse tree of a JSON5 document, as returned by [`parse`][].
+///
+/// [`parse`]: fn.parse.html
+#[derive(Clone, Debug)]
+pub struct ParsedDocument<'a> {
+    root: Node<'a>,
+}
+
+impl<'a> ParsedDocument<'a> {
+    /// The top-level value of the document.
+    pub fn root(&self) -> Node<'a> {
+        self.root.clone()
+    }
+
+    /// Every comment in the document, in order.
+    pub fn comments(&self) -> &[Comment<'a>] {
+        &self.root.source.comments
+    }
+
+    /// The comments that document a value, in order, each with the path from the root to the
+    /// value it belongs to. These are the [`leading_comments`][] and [`trailing_comment`][] of
+    /// every value, array element and object entry, so comments that aren&rsquo;t next to one
+    /// (such as in an empty object) are left out. Can fail if a key has an invalid escape.
+    ///
+    /// ```rust
+    /// use json5::PathSegment::{Index, Key};
+    ///
+    /// let doc = json5::parse("{\n  // where to listen\n  ports: [\n    80, // http\n  ],\n}").unwrap();
+    /// let comments = doc.attached_comments().unwrap();
+    /// assert_eq!(comments[0].comment.text(), " where to listen");
+    /// assert_eq!(comments[0].path, [Key("ports".to_owned())]);
+    /// assert_eq!(comments[1].comment.text(), " http");
+    /// assert_eq!(comments[1].path, [Key("ports".to_owned()), Index(0)]);
+    /// ```
+    ///
+    /// [`leading_comments`]: struct.Node.html#method.leading_comments
+    /// [`trailing_comment`]: struct.Node.html#method.trailing_comment
+    pub fn attached_comments(&self) -> Result<Vec<AttachedComment<'a>>> {
+        let mut attached = Vec::new();
+        let root = self.root();
+        attach(&mut attached, &root, &root, &mut Vec::new())?;
+        attached.sort_by_key(|a: &AttachedComment<'_>| a.comment.start);
+        attached.dedup_by_key(|a| a.comment.start);
+        Ok(attached)
+    }
+}
+
+/// A comment together with the path to the value it documents, as returned by
+/// [`ParsedDocument::attached_comments`][].
+///
+/// [`ParsedDocument::attached_comments`]: struct.ParsedDocument.html#method.attached_comments
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttachedComment<'a> {
+    /// The comment.
+    pub comment: Comment<'a>,
+    /// The keys and indices leading from the root to the value, so empty for the root itself.
+    pub path: Vec<PathSegment>,
+}
+
+/// One step of the path to a value in a document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathSegment {
+    /// The value of an object entry, by its key with any escapes resolved.
+    Key(String),
+    /// An element of an array, by its index.
+    Index(usize),
+}
+
+// `before` is the node whose leading comments belong to the value: its key if it's the value of an
+// object entry, otherwise the value itself.
+fn attach<'a>(
+    attached: &mut Vec<AttachedComment<'a>>,
+    before: &Node<'a>,
+    value: &Node<'a>,
+    path: &mut Vec<PathSegment>,
+) -> Result<()> {
+    for comment in before
+        .leading_comments()
+        .into_iter()
+        .chain(value.trailing_comment())
+    {
+        attached.push(AttachedComment {
+            comment,
+            path: path.clone(),
+        });
+    }
+    for (i, element) in value.elements().enumerate() {
+        path.push(PathSegment::Index(i));
+        attach(attached, &element, &element, path)?;
+        path.pop();
+    }
+    for (key, value) in value.entries() {
+        path.push(PathSegment::Key(key.deserialize()?));
+        attach(attached, &key, &value, path)?;
+        path.pop();
+    }
+    Ok(())
+}
+
+/// A `//` or `/* */` comment in a [`ParsedDocument`][].
+///
+/// [`ParsedDocument`]: struct.ParsedDocument.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Comment<'a> {
+    text: &'a str,
+    start: usize,
+}
+
+impl<'a> Comment<'a> {
+    /// The comment exactly as it appears in the input, including the `//`, `/*` and `*/`.
+    pub fn as_str(&self) -> &'a str {
+        self.text
+    }
+
+    /// The text of the comment without the delimiters.
+    pub fn text(&self) -> &'a str {
+        if self.is_block() {
+            &self.text[2..self.text.len() - 2]
+        } else {
+            &self.text[2..]
+        }
+    }
+
+    /// Returns true for a `/* */` comment, and false for a `//` one.
+    pub fn is_block(&self) -> bool {
+        self.text.starts_with("/*")
+    }
+
+    /// The byte range of the comment in the input.
+    pub fn span(&self) -> Span {
+        Span {
+            start: self.start,
+            end: self.start + self.text.len(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Source<'a> {
+    input: &'a str,
+    comments: Vec<Comment<'a>>,
+}
+
+/// A value in a [`ParsedDocument`][], or an object key.
+///
+/// [`ParsedDocument`]: struct.ParsedDocument.html
+#[derive(Clone, Debug)]
+pub struct Node<'a> {
+    pair: Pair<'a, Rule>,
+    source: Rc<Source<'a>>,
+}
+
+impl<'a> Node<'a> {
+    /// The grammar rule that matched the node: one of `null`, `boolean`, `string`, `number`,
+    /// `array` or `object`, or `identifier` for an unquoted object key.
+    pub fn rule(&self) -> Rule {
+        self.pair.as_rule()
+    }
+
+    /// The kind of value the node is. Object keys are strings, whether quoted or not.
+    pub fn kind(&self) -> ValueKind {
+        kind_of(&self.pair)
+    }
+
+    /// The source text of the node, exactly as it appears in the input (so including quotes,
+    /// escapes and, for arrays and objects, any comments inside).
+    pub fn as_str(&self) -> &'a str {
+        self.pair.as_str()
+    }
+
+    /// The byte range of the node in the input.
+    pub fn span(&self) -> Span {
+        locate(&self.pair.as_span()).1
+    }
+
+    /// Where the node starts in the input.
+    pub fn location(&self) -> Location {
+        locate(&self.pair.as_span()).0
+    }
+
+    /// The elements of an array, in order. Empty if the node is not an array.
+    pub fn elements(&self) -> impl Iterator<Item = Node<'a>> {
+        let pairs = match self.rule() {
+            Rule::array => Some(self.pair.clone().into_inner()),
+            _ => None,
+        };
+        let source = self.source.clone();
+        pairs.into_iter().flatten().map(move |pair| Node {
+            pair,
+            source: source.clone(),
+        })
+    }
+
+    /// The entries of an object as `(key, value)` pairs, in the order they appear (including any
+    /// duplicate keys). Empty if the node is not an object.
+    pub fn entries(&self) -> impl Iterator<Item = (Node<'a>, Node<'a>)> {
+        let mut pairs = match self.rule() {
+            Rule::object => Some(self.pair.clone().into_inner()),
+            _ => None,
+        };
+        let source = self.source.clone();
+        core::iter::from_fn(move || {
+            let pairs = pairs.as_mut()?;
+            let key = Node {
+                pair: pairs.next()?,
+                source: source.clone(),
+            };
+            let value = Node {
+                pair: pairs.next()?,
+                source: source.clone(),
+            };
+            Some((key, value))
+        })
+    }
+
+    /// The comments directly before the node, separated from it only by whitespace, in order. For
+    /// an object entry these are found before the key. A comment on the same line as the end of
+    /// the previous array element or object entry is that one&rsquo;s
+    /// [`trailing_comment`](#method.trailing_comment) instead.
+    ///
+    /// ```rust
+    /// let doc = json5::parse("{\n  // the port\n  port: 80, // not 8080\n}").unwrap();
+    /// let (key, value) = doc.root().entries().next().unwrap();
+    /// assert_eq!(key.leading_comments()[0].text(), " the port");
+    /// assert_eq!(value.trailing_comment().unwrap().text(), " not 8080");
+    /// ```
+    pub fn leading_comments(&self) -> Vec<Comment<'a>> {
+        let input = self.source.input;
+        let mut start = self.pair.as_span().start();
+        let mut comments = Vec::new();
+        for comment in self.source.comments.iter().rev() {
+            let span = comment.span();
+            if span.end > start {
+                continue;
+            }
+            if !input[span.end..start].chars().all(is_whitespace) {
+                break;
+            }
+            comments.push(*comment);
+            start = span.start;
+        }
+        comments.reverse();
+
+        // Drop the first comment if it trails the previous entry.
+        let before = input[..start].trim_end_matches(|c| is_whitespace(c) && !is_newline(c));
+        if !comments.is_empty() && before.ends_with(',') {
+            comments.remove(0);
+        }
+        comments
+    }
+
+    /// The comment after the node on the same line, if any, skipping a comma.
+    pub fn trailing_comment(&self) -> Option<Comment<'a>> {
+        let input = self.source.input;
+        let end = self.pair.as_span().end();
+        let after = input[end..].trim_start_matches(|c| is_whitespace(c) && !is_newline(c));
+        let after = after.strip_prefix(',').unwrap_or(after);
+        let after = after.trim_start_matches(|c| is_whitespace(c) && !is_newline(c));
+        let start = input.len() - after.len();
+        self.source
+            .comments
+            .iter()
+            .find(|comment| comment.start == start)
+            .copied()
+    }
+
+    /// Resolves the node into a [`SpannedValue`][], which is a [`Value`][] that keeps the span of
+    /// every element, key and value. Can fail as deserializing a `Value` can.
+    ///
+    /// [`SpannedValue`]: enum.SpannedValue.html
+    /// [`Value`]: enum.Value.html
+    pub fn to_spanned_value(&self) -> Result<Spanned<SpannedValue>> {
+        spanned_value(self)
+    }
+
+    /// Deserializes the node as a `T`, as [`from_str`][] would if the input were just this node.
+    ///
+    /// [`from_str`]: fn.from_str.html
+    pub fn deserialize<T>(&self) -> Result<T>
+    where
+        T: de::Deserialize<'a>,
+    {
+        T::deserialize(&mut Deserializer::from_pair(
+            self.pair.clone(),
+            Settings::default(),
+        ))
+    }
+}
+
+// Finds the comments in valid JSON5 text, skipping over strings.
+fn lex_comments(input: &str) -> Vec<Comment<'_>> {
+    let mut comments = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' | '\'' => {
+                while let Some((_, d)) = chars.next() {
+                    if d == '\\' {
+                        chars.next();
+                    } else if d == c {
+                        break;
+                    }
+                }
+            }
+            '/' => {
+                let end = match chars.peek() {
+                    Some((_, '/')) => input[i..]
+                        .find(is_newline)
+                        .map_or(input.len(), |len| i + len),
+                    Some((_, '*')) => input[i + 2..]
+                        .find("*/")
+                        .map_or(input.len(), |len| i + 2 + len + 2),
+                    _ => continue,
+                };
+                comments.push(Comment {
+                    text: &input[i..end],
+                    start: i,
+                });
+                while chars.peek().is_some_and(|&(j, _)| j < end) {
+                    chars.next();
+                }
+            }
+            _ => {}
+        }
+    }
+    comments
+}
+
+// As in the grammar.
+fn is_newline(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}')
+}
+
+fn is_whitespace(c: char) -> bool {
+    c.is_whitespace() || c == '\u{FEFF}'
+}
@@ -1,5 +1,11 @@
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::{f32, f64};
 use serde::ser::{self, Serialize};
-use std::{f32, f64};
+#[cfg(feature = "std")]
+use std::io;
 
 use crate::error::{Error, Result};
 
@@ -8,29 +14,384 @@ pub fn to_string<T>(value: &T) -> Result<String>
 where
     T: Serialize,
 {
-    let mut serializer = Serializer {
-        output: String::new(),
-    };
-    value.serialize(&mut serializer)?;
-    Ok(serializer.output)
+    SerializeOptions::new().to_string(value)
+}
+
+/// Attempts to serialize the input as a pretty-printed JSON5 string, with one array element or
+/// object entry per line, formatted according to `config`.
+pub fn to_string_pretty<T>(value: &T, config: &PrettyConfig) -> Result<String>
+where
+    T: Serialize,
+{
+    SerializeOptions::new()
+        .pretty(config.clone())
+        .to_string(value)
 }
 
-struct Serializer {
-    output: String,
-    // TODO settings for formatting (single vs double quotes, whitespace etc)
+/// Attempts to serialize the input as JSON5 (actually JSON) into the given writer. Output is
+/// written incrementally as each value is serialized, so wrap the writer in an
+/// [`io::BufWriter`][] if it is unbuffered (e.g. a `File`).
+///
+/// [`io::BufWriter`]: https://doc.rust-lang.org/std/io/struct.BufWriter.html
+#[cfg(feature = "std")]
+pub fn to_writer<W, T>(writer: W, value: &T) -> Result<()>
+where
+    W: io::Write,
+    T: Serialize,
+{
+    SerializeOptions::new().to_writer(writer, value)
 }
 
-impl Serializer {
+/// Adapts an `io::Write` to `fmt::Write`, holding on to the underlying IO error (if any) so that
+/// it can be reported instead of the opaque `fmt::Error`.
+#[cfg(feature = "std")]
+struct IoWriter<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<W: io::Write> fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
+
+/// Options controlling the output of the serializer, for when the defaults used by
+/// [`to_string`][] and friends aren&rsquo;t what you want.
+///
+/// ```rust
+/// use json5::{NumberFormat, PrettyConfig, SerializeOptions};
+///
+/// let options = SerializeOptions::new()
+///     .number_format(NumberFormat::Hex)
+///     .pretty(PrettyConfig::new());
+/// assert_eq!(options.to_string(&vec![255, -16]), Ok("[\n  0xFF,\n  -0x10\n]".to_owned()));
+/// ```
+///
+/// [`to_string`]: fn.to_string.html
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SerializeOptions {
+    number_format: NumberFormat,
+    pretty: Option<PrettyConfig>,
+    skip_none: bool,
+    ascii_only: bool,
+    json_compatible: bool,
+}
+
+impl SerializeOptions {
+    /// Creates the default options: compact output with decimal integers.
+    pub fn new() -> Self {
+        SerializeOptions::default()
+    }
+
+    /// Sets how integers are written. Floats are unaffected, so `NaN` and `Infinity` are still
+    /// written as such. Ignored if the output is [`json_compatible`](#method.json_compatible).
+    pub fn number_format(mut self, number_format: NumberFormat) -> Self {
+        self.number_format = number_format;
+        self
+    }
+
+    /// Pretty-prints the output, formatted according to `config`.
+    pub fn pretty(mut self, config: PrettyConfig) -> Self {
+        self.pretty = Some(config);
+        self
+    }
+
+    /// Sets whether struct fields and map entries whose value is `None` are left out rather than
+    /// written as `null`, as if every `Option` field had
+    /// `#[serde(skip_serializing_if = "Option::is_none")]`. Defaults to `false`. `None` elsewhere,
+    /// such as in an array, is still written as `null`.
+    ///
+    /// ```rust
+    /// use std::collections::BTreeMap;
+    ///
+    /// let map: BTreeMap<_, _> = vec![("a", Some(1)), ("b", None)].into_iter().collect();
+    /// let options = json5::SerializeOptions::new().skip_none(true);
+    /// assert_eq!(options.to_string(&map), Ok("{\"a\":1}".to_owned()));
+    /// assert_eq!(options.to_string(&vec![None, Some(1)]), Ok("[null,1]".to_owned()));
+    /// ```
+    pub fn skip_none(mut self, skip_none: bool) -> Self {
+        self.skip_none = skip_none;
+        self
+    }
+
+    /// Sets whether every character outside ASCII in strings and keys is written as a `\uXXXX`
+    /// escape (two for characters beyond the Basic Multilingual Plane), for systems that
+    /// can&rsquo;t handle UTF-8. Defaults to `false`, in which case such characters are written as
+    /// they are.
+    ///
+    /// ```rust
+    /// let options = json5::SerializeOptions::new().ascii_only(true);
+    /// assert_eq!(options.to_string(&"café ☕"), Ok("\"caf\\u00e9 \\u2615\"".to_owned()));
+    /// assert_eq!(json5::to_string(&"café"), Ok("\"café\"".to_owned()));
+    /// ```
+    pub fn ascii_only(mut self, ascii_only: bool) -> Self {
+        self.ascii_only = ascii_only;
+        self
+    }
+
+    /// Sets whether the output is restricted to what is also valid JSON, for when one crate has to
+    /// write both. Defaults to `false`. When enabled, `\v` and `\0` in strings are written as
+    /// `\u000b` and `\u0000`, integers are written in decimal whatever the
+    /// [`number_format`](#method.number_format), trailing commas are left out even if the
+    /// [`PrettyConfig`][] asks for them, and `NaN` and the infinities are written as `null` (as
+    /// serde_json does). Keys are always quoted, so need no change.
+    ///
+    /// ```rust
+    /// use json5::{NumberFormat, PrettyConfig, SerializeOptions};
+    ///
+    /// let options = SerializeOptions::new()
+    ///     .number_format(NumberFormat::Hex)
+    ///     .pretty(PrettyConfig::new().trailing_commas(true))
+    ///     .json_compatible(true);
+    /// assert_eq!(
+    ///     options.to_string(&(255, f64::NAN, "\u{b}")),
+    ///     Ok("[\n  255,\n  null,\n  \"\\u000b\"\n]".to_owned()),
+    /// );
+    /// ```
+    ///
+    /// [`PrettyConfig`]: struct.PrettyConfig.html
+    pub fn json_compatible(mut self, json_compatible: bool) -> Self {
+        self.json_compatible = json_compatible;
+        self
+    }
+
+    /// Attempts to serialize the input as a JSON5 string.
+    pub fn to_string<T>(&self, value: &T) -> Result<String>
+    where
+        T: Serialize,
+    {
+        let mut serializer = Serializer::new(String::new(), self.clone());
+        value.serialize(&mut serializer)?;
+        Ok(serializer.output)
+    }
+
+    /// Attempts to serialize the input as JSON5 into the given writer, as [`to_writer`][] does.
+    ///
+    /// [`to_writer`]: fn.to_writer.html
+    #[cfg(feature = "std")]
+    pub fn to_writer<W, T>(&self, writer: W, value: &T) -> Result<()>
+    where
+        W: io::Write,
+        T: Serialize,
+    {
+        let mut serializer = Serializer::new(
+            IoWriter {
+                inner: writer,
+                error: None,
+            },
+            self.clone(),
+        );
+        let result = value.serialize(&mut serializer);
+        match serializer.output.error.take() {
+            Some(err) => Err(Error::from(err)),
+            None => result,
+        }
+    }
+}
+
+/// How the serializer writes integers.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum NumberFormat {
+    /// Decimal, e.g. `255`. The default.
+    #[default]
+    Decimal,
+    /// Hexadecimal with upper case digits, e.g. `0xFF`, or `-0xFF` for negative numbers.
+    Hex,
+}
+
+/// Formatting options for [`to_string_pretty`][] and [`SerializeOptions::pretty`][].
+///
+/// The default indents by two spaces, separates lines with `\n` and omits trailing commas.
+///
+/// ```rust
+/// let config = json5::PrettyConfig::new().indent("\t").trailing_commas(true);
+/// assert_eq!(
+///     json5::to_string_pretty(&vec![1, 2], &config),
+///     Ok("[\n\t1,\n\t2,\n]".to_owned()),
+/// );
+/// ```
+///
+/// [`to_string_pretty`]: fn.to_string_pretty.html
+/// [`SerializeOptions::pretty`]: struct.SerializeOptions.html#method.pretty
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrettyConfig {
+    indent: String,
+    newline: String,
+    trailing_commas: bool,
+}
+
+impl PrettyConfig {
+    /// Creates the default configuration.
+    pub fn new() -> Self {
+        PrettyConfig {
+            indent: "  ".to_owned(),
+            newline: "\n".to_owned(),
+            trailing_commas: false,
+        }
+    }
+
+    /// Sets the string written once per level of nesting at the start of each line.
+    pub fn indent(mut self, indent: &str) -> Self {
+        self.indent = indent.to_owned();
+        self
+    }
+
+    /// Sets the line separator, e.g. `"\r\n"` for Windows-style line endings.
+    pub fn newline(mut self, newline: &str) -> Self {
+        self.newline = newline.to_owned();
+        self
+    }
+
+    /// Sets whether a comma is written after the last element of each non-empty array or object.
+    /// Then appending an element to a file changes one line rather than two. Compact output never
+    /// has trailing commas.
+    pub fn trailing_commas(mut self, trailing_commas: bool) -> Self {
+        self.trailing_commas = trailing_commas;
+        self
+    }
+}
+
+impl Default for PrettyConfig {
+    fn default() -> Self {
+        PrettyConfig::new()
+    }
+}
+
+struct Serializer<W> {
+    output: W,
+    // Whether the innermost array or object being serialized has no elements yet.
+    first: bool,
+    // The number of arrays and objects we're currently inside.
+    depth: usize,
+    options: SerializeOptions,
+    // TODO settings for formatting (single vs double quotes etc)
+}
+
+impl<W: fmt::Write> Serializer<W> {
+    fn new(output: W, options: SerializeOptions) -> Self {
+        Serializer {
+            output,
+            first: true,
+            depth: 0,
+            options,
+        }
+    }
+
+    fn write_str(&mut self, s: &str) -> Result<()> {
+        self.output.write_str(s).map_err(write_error)
+    }
+
     fn call_to_string<T>(&mut self, v: &T) -> Result<()>
     where
         T: ToString,
     {
-        self.output += &v.to_string();
+        self.write_str(&v.to_string())
+    }
+
+    // Rust formats floats with the fewest digits that read back as the same value, which for very
+    // large or small numbers is much shorter with an exponent than padded out with zeros. A whole
+    // number gets a `.0`, so that it reads back as a float rather than an integer (and `-0.0` keeps
+    // its sign).
+    fn write_float<T>(&mut self, v: T, magnitude: f64) -> Result<()>
+    where
+        T: fmt::Display + fmt::LowerExp,
+    {
+        if magnitude >= 1e16 || (magnitude != 0. && magnitude < 1e-5) {
+            self.write_str(&format!("{:e}", v))
+        } else if magnitude.fract() == 0. {
+            self.write_str(&format!("{}.0", v))
+        } else {
+            self.call_to_string(&v)
+        }
+    }
+
+    fn number_format(&self) -> NumberFormat {
+        if self.options.json_compatible {
+            NumberFormat::Decimal
+        } else {
+            self.options.number_format
+        }
+    }
+
+    fn write_signed(&mut self, v: i128) -> Result<()> {
+        match self.number_format() {
+            NumberFormat::Decimal => self.call_to_string(&v),
+            NumberFormat::Hex if v < 0 => self.write_str(&format!("-0x{:X}", v.unsigned_abs())),
+            NumberFormat::Hex => self.write_unsigned(v as u128),
+        }
+    }
+
+    fn write_unsigned(&mut self, v: u128) -> Result<()> {
+        match self.number_format() {
+            NumberFormat::Decimal => self.call_to_string(&v),
+            NumberFormat::Hex => self.write_str(&format!("0x{:X}", v)),
+        }
+    }
+
+    fn write_newline(&mut self) -> Result<()> {
+        if let Some(ref pretty) = self.options.pretty {
+            self.output
+                .write_str(&pretty.newline)
+                .map_err(write_error)?;
+            for _ in 0..self.depth {
+                self.output.write_str(&pretty.indent).map_err(write_error)?;
+            }
+        }
         Ok(())
     }
+
+    fn begin_compound(&mut self, open: &str) -> Result<()> {
+        self.first = true;
+        self.depth += 1;
+        self.write_str(open)
+    }
+
+    fn begin_element(&mut self) -> Result<()> {
+        if self.first {
+            self.first = false;
+        } else {
+            self.write_str(",")?;
+        }
+        self.write_newline()
+    }
+
+    fn begin_value(&mut self) -> Result<()> {
+        if self.options.pretty.is_some() {
+            self.write_str(": ")
+        } else {
+            self.write_str(":")
+        }
+    }
+
+    fn end_compound(&mut self, close: &str) -> Result<()> {
+        self.depth -= 1;
+        if !self.first {
+            if self
+                .options
+                .pretty
+                .as_ref()
+                .is_some_and(|p| p.trailing_commas)
+                && !self.options.json_compatible
+            {
+                self.write_str(",")?;
+            }
+            self.write_newline()?;
+        }
+        self.first = false;
+        self.write_str(close)
+    }
+}
+
+fn write_error(_: fmt::Error) -> Error {
+    Error::Message("error writing output".to_owned())
 }
 
-impl<'a> ser::Serializer for &'a mut Serializer {
+impl<W: fmt::Write> ser::Serializer for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -47,61 +408,73 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     }
 
     fn serialize_i8(self, v: i8) -> Result<()> {
-        self.call_to_string(&v)
+        self.write_signed(i128::from(v))
     }
 
     fn serialize_i16(self, v: i16) -> Result<()> {
-        self.call_to_string(&v)
+        self.write_signed(i128::from(v))
     }
 
     fn serialize_i32(self, v: i32) -> Result<()> {
-        self.call_to_string(&v)
+        self.write_signed(i128::from(v))
     }
 
     fn serialize_i64(self, v: i64) -> Result<()> {
-        self.call_to_string(&v)
+        self.write_signed(i128::from(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<()> {
+        self.write_signed(v)
     }
 
     fn serialize_u8(self, v: u8) -> Result<()> {
-        self.call_to_string(&v)
+        self.write_unsigned(u128::from(v))
     }
 
     fn serialize_u16(self, v: u16) -> Result<()> {
-        self.call_to_string(&v)
+        self.write_unsigned(u128::from(v))
     }
 
     fn serialize_u32(self, v: u32) -> Result<()> {
-        self.call_to_string(&v)
+        self.write_unsigned(u128::from(v))
     }
 
     fn serialize_u64(self, v: u64) -> Result<()> {
-        self.call_to_string(&v)
+        self.write_unsigned(u128::from(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<()> {
+        self.write_unsigned(v)
     }
 
+    // The infinities and NaN are written as the JSON5 tokens, with the sign of NaN dropped since
+    // `-NaN` reads back as the same thing. Negative zero keeps its sign, as `-0.0`.
     fn serialize_f32(self, v: f32) -> Result<()> {
-        if v == f32::INFINITY {
-            self.output += "Infinity";
+        if !v.is_finite() && self.options.json_compatible {
+            self.write_str("null")
+        } else if v == f32::INFINITY {
+            self.write_str("Infinity")
         } else if v == f32::NEG_INFINITY {
-            self.output += "-Infinity";
+            self.write_str("-Infinity")
         } else if v.is_nan() {
-            self.output += "NaN";
+            self.write_str("NaN")
         } else {
-            self.call_to_string(&v)?;
+            self.write_float(v, f64::from(v.abs()))
         }
-        Ok(())
     }
 
     fn serialize_f64(self, v: f64) -> Result<()> {
-        if v == f64::INFINITY {
-            self.output += "Infinity";
+        if !v.is_finite() && self.options.json_compatible {
+            self.write_str("null")
+        } else if v == f64::INFINITY {
+            self.write_str("Infinity")
         } else if v == f64::NEG_INFINITY {
-            self.output += "-Infinity";
+            self.write_str("-Infinity")
         } else if v.is_nan() {
-            self.output += "NaN";
+            self.write_str("NaN")
         } else {
-            self.call_to_string(&v)?;
+            self.write_float(v, v.abs())
         }
-        Ok(())
     }
 
     fn serialize_char(self, v: char) -> Result<()> {
@@ -109,14 +482,22 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     }
 
     fn serialize_str(self, v: &str) -> Result<()> {
-        self.output += "\"";
-        self.output += &escape(v);
-        self.output += "\"";
-        Ok(())
+        self.write_str("\"")?;
+        let escaped = escape(v, &self.options);
+        self.write_str(&escaped)?;
+        self.write_str("\"")
     }
 
-    fn serialize_bytes(self, _v: &[u8]) -> Result<()> {
-        unimplemented!() // TODO
+    // As an array of integers, which reads back as bytes, rather than a string, which would only
+    // be valid for UTF-8.
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        use serde::ser::SerializeSeq;
+
+        let mut seq = self.serialize_seq(Some(v.len()))?;
+        for byte in v {
+            seq.serialize_element(byte)?;
+        }
+        seq.end()
     }
 
     fn serialize_none(self) -> Result<()> {
@@ -131,8 +512,7 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     }
 
     fn serialize_unit(self) -> Result<()> {
-        self.output += "null";
-        Ok(())
+        self.write_str("null")
     }
 
     fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
@@ -165,16 +545,16 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        self.output += "{";
+        self.begin_compound("{")?;
+        self.begin_element()?;
         variant.serialize(&mut *self)?; // TODO drop the quotes where possible
-        self.output += ":";
+        self.begin_value()?;
         value.serialize(&mut *self)?;
-        self.output += "}";
-        Ok(())
+        self.end_compound("}")
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        self.output += "[";
+        self.begin_compound("[")?;
         Ok(self)
     }
 
@@ -197,14 +577,16 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        self.output += "{";
+        self.begin_compound("{")?;
+        self.begin_element()?;
         variant.serialize(&mut *self)?;
-        self.output += ":[";
+        self.begin_value()?;
+        self.begin_compound("[")?;
         Ok(self)
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
-        self.output += "{";
+        self.begin_compound("{")?;
         Ok(self)
     }
 
@@ -219,14 +601,16 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        self.output += "{";
+        self.begin_compound("{")?;
+        self.begin_element()?;
         variant.serialize(&mut *self)?;
-        self.output += ":{";
+        self.begin_value()?;
+        self.begin_compound("{")?;
         Ok(self)
     }
 }
 
-impl<'a> ser::SerializeSeq for &'a mut Serializer {
+impl<W: fmt::Write> ser::SerializeSeq for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -234,19 +618,16 @@ impl<'a> ser::SerializeSeq for &'a mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        if !self.output.ends_with('[') {
-            self.output += ",";
-        }
+        self.begin_element()?;
         value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<()> {
-        self.output += "]";
-        Ok(())
+        self.end_compound("]")
     }
 }
 
-impl<'a> ser::SerializeTuple for &'a mut Serializer {
+impl<W: fmt::Write> ser::SerializeTuple for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -262,7 +643,7 @@ impl<'a> ser::SerializeTuple for &'a mut Serializer {
     }
 }
 
-impl<'a> ser::SerializeTupleStruct for &'a mut Serializer {
+impl<W: fmt::Write> ser::SerializeTupleStruct for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -278,7 +659,7 @@ impl<'a> ser::SerializeTupleStruct for &'a mut Serializer {
     }
 }
 
-impl<'a> ser::SerializeTupleVariant for &'a mut Serializer {
+impl<W: fmt::Write> ser::SerializeTupleVariant for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -290,40 +671,51 @@ impl<'a> ser::SerializeTupleVariant for &'a mut Serializer {
     }
 
     fn end(self) -> Result<()> {
-        self.output += "]}";
-        Ok(())
+        self.end_compound("]")?;
+        self.end_compound("}")
     }
 }
 
-impl<'a> ser::SerializeMap for &'a mut Serializer {
+impl<W: fmt::Write> ser::SerializeMap for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
+    // Only entries serialized in one go can be skipped, as the key has already been written by
+    // the time a value is passed to `serialize_value`.
+    fn serialize_entry<K, V>(&mut self, key: &K, value: &V) -> Result<()>
+    where
+        K: ?Sized + Serialize,
+        V: ?Sized + Serialize,
+    {
+        if self.options.skip_none && is_none(value) {
+            return Ok(());
+        }
+        ser::SerializeMap::serialize_key(self, key)?;
+        ser::SerializeMap::serialize_value(self, value)
+    }
+
     fn serialize_key<T>(&mut self, key: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
-        if !self.output.ends_with('{') {
-            self.output += ",";
-        }
-        key.serialize(&mut **self)
+        self.begin_element()?;
+        key.serialize(MapKeySerializer { ser: &mut **self })
     }
 
     fn serialize_value<T>(&mut self, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
-        self.output += ":";
+        self.begin_value()?;
         value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<()> {
-        self.output += "}";
-        Ok(())
+        self.end_compound("}")
     }
 }
 
-impl<'a> ser::SerializeStruct for &'a mut Serializer {
+impl<W: fmt::Write> ser::SerializeStruct for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -331,8 +723,7 @@ impl<'a> ser::SerializeStruct for &'a mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        ser::SerializeMap::serialize_key(self, key)?;
-        ser::SerializeMap::serialize_value(self, value)
+        ser::SerializeMap::serialize_entry(self, key, value)
     }
 
     fn end(self) -> Result<()> {
@@ -340,7 +731,7 @@ impl<'a> ser::SerializeStruct for &'a mut Serializer {
     }
 }
 
-impl<'a> ser::SerializeStructVariant for &'a mut Serializer {
+impl<W: fmt::Write> ser::SerializeStructVariant for &mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
@@ -352,23 +743,345 @@ impl<'a> ser::SerializeStructVariant for &'a mut Serializer {
     }
 
     fn end(self) -> Result<()> {
-        self.output += "}}";
-        Ok(())
+        self.end_compound("}")?;
+        self.end_compound("}")
     }
 }
 
-fn escape(v: &str) -> String {
-    v.chars()
-        .flat_map(|c| match c {
-            '"' => vec!['\\', c],
-            '\n' => vec!['\\', 'n'],
-            '\r' => vec!['\\', 'r'],
-            '\t' => vec!['\\', 't'],
-            '/' => vec!['\\', '/'],
-            '\\' => vec!['\\', '\\'],
-            '\u{0008}' => vec!['\\', 'b'],
-            '\u{000c}' => vec!['\\', 'f'],
-            c => vec![c],
-        })
-        .collect()
+/// Writes an object key, which has to be a string. Numbers, booleans, chars and unit variants are
+/// written as strings of their usual representation, so that e.g. a `HashMap<u32, T>` reads back
+/// the same.
+struct MapKeySerializer<'a, W> {
+    ser: &'a mut Serializer<W>,
+}
+
+macro_rules! key_to_string {
+    ($($method:ident($ty:ty),)*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<()> {
+                self.ser.serialize_str(&v.to_string())
+            }
+        )*
+    };
+}
+
+macro_rules! key_float_to_string {
+    ($($method:ident($ty:ty),)*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<()> {
+                if v.is_nan() {
+                    self.ser.serialize_str("NaN")
+                } else if v.is_infinite() && v > 0. {
+                    self.ser.serialize_str("Infinity")
+                } else if v.is_infinite() {
+                    self.ser.serialize_str("-Infinity")
+                } else {
+                    self.ser.serialize_str(&v.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl<W: fmt::Write> ser::Serializer for MapKeySerializer<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = ser::Impossible<(), Error>;
+    type SerializeTuple = ser::Impossible<(), Error>;
+    type SerializeTupleStruct = ser::Impossible<(), Error>;
+    type SerializeTupleVariant = ser::Impossible<(), Error>;
+    type SerializeMap = ser::Impossible<(), Error>;
+    type SerializeStruct = ser::Impossible<(), Error>;
+    type SerializeStructVariant = ser::Impossible<(), Error>;
+
+    key_to_string! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_char(char),
+    }
+
+    key_float_to_string! {
+        serialize_f32(f32),
+        serialize_f64(f64),
+    }
+
+    fn serialize_str(self, v: &str) -> Result<()> {
+        self.ser.serialize_str(v)
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<()> {
+        Err(Error::KeyMustBeString)
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        Err(Error::KeyMustBeString)
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::KeyMustBeString)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        Err(Error::KeyMustBeString)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        Err(Error::KeyMustBeString)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<()> {
+        self.ser.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::KeyMustBeString)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(Error::KeyMustBeString)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(Error::KeyMustBeString)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(Error::KeyMustBeString)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::KeyMustBeString)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(Error::KeyMustBeString)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(Error::KeyMustBeString)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(Error::KeyMustBeString)
+    }
+}
+
+fn is_none<T>(value: &T) -> bool
+where
+    T: ?Sized + Serialize,
+{
+    value.serialize(IsNone).unwrap_or(false)
+}
+
+/// Finds out whether a value serializes as `None`, without writing anything. Compound values
+/// can&rsquo;t be `None`, so fail straight away rather than being walked.
+struct IsNone;
+
+macro_rules! not_none {
+    ($($method:ident($($ty:ty),*),)*) => {
+        $(
+            fn $method(self, $(_: $ty),*) -> Result<bool> {
+                Ok(false)
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for IsNone {
+    type Ok = bool;
+    type Error = Error;
+
+    type SerializeSeq = ser::Impossible<bool, Error>;
+    type SerializeTuple = ser::Impossible<bool, Error>;
+    type SerializeTupleStruct = ser::Impossible<bool, Error>;
+    type SerializeTupleVariant = ser::Impossible<bool, Error>;
+    type SerializeMap = ser::Impossible<bool, Error>;
+    type SerializeStruct = ser::Impossible<bool, Error>;
+    type SerializeStructVariant = ser::Impossible<bool, Error>;
+
+    not_none! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_unit(),
+        serialize_unit_struct(&'static str),
+        serialize_unit_variant(&'static str, u32, &'static str),
+    }
+
+    fn serialize_none(self) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<bool>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(false)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, _value: &T) -> Result<bool>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(false)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<bool>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(false)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(not_none())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(not_none())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(not_none())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(not_none())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(not_none())
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(not_none())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(not_none())
+    }
+}
+
+fn not_none() -> Error {
+    Error::Message("not none".to_owned())
+}
+
+// Uses the same escapes as the deserializer reads, so that any string reads back unchanged. Other
+// control characters and the line terminators that can't appear in a string are written as `\u`
+// escapes, as are vertical tab and NUL if the output has to be JSON.
+fn escape(v: &str, options: &SerializeOptions) -> String {
+    let mut escaped = String::with_capacity(v.len());
+    let mut chars = v.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '/' => escaped.push_str("\\/"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\u{0008}' => escaped.push_str("\\b"),
+            '\u{000b}' if !options.json_compatible => escaped.push_str("\\v"),
+            '\u{000c}' => escaped.push_str("\\f"),
+            // `\0` followed by a digit isn't allowed, as it would be an octal escape in
+            // JavaScript.
+            '\0' if !chars.peek().is_some_and(char::is_ascii_digit) && !options.json_compatible => {
+                escaped.push_str("\\0")
+            }
+            c if c.is_control()
+                || c == '\u{2028}'
+                || c == '\u{2029}'
+                || (options.ascii_only && !c.is_ascii()) =>
+            {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    escaped.push_str(&format!("\\u{:04x}", unit));
+                }
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
 }
@@ -0,0 +1,130 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::de::Rule;
+use crate::error::{Error, Result, Span};
+use crate::map::Map;
+use crate::number::Number;
+use crate::parse::Node;
+use crate::value::Value;
+
+/// A value together with the byte range of the input it was parsed from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spanned<T> {
+    /// The value.
+    pub value: T,
+    /// The byte range of the value in the input.
+    pub span: Span,
+}
+
+/// The same as a [`Value`][], but with the [`Span`][] of every array element, object key and
+/// object value, for tools such as editors and error reporters that need to map a path through the
+/// document back to the source text. Made with [`Node::to_spanned_value`][].
+///
+/// ```rust
+/// let doc = json5::parse("{ servers: [{ port: 80 }, { port: 'x' }] }").unwrap();
+/// let root = doc.root().to_spanned_value().unwrap();
+///
+/// let port = root.value.get("servers").unwrap().value.get_index(1).unwrap().value.get("port");
+/// assert_eq!(port.unwrap().span, json5::Span { start: 34, end: 37 });
+/// ```
+///
+/// [`Value`]: enum.Value.html
+/// [`Span`]: struct.Span.html
+/// [`Node::to_spanned_value`]: struct.Node.html#method.to_spanned_value
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpannedValue {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool(bool),
+    /// Any number, including `Infinity` and `NaN`.
+    Number(Number),
+    /// A string, with any escapes resolved.
+    String(String),
+    /// An array of values.
+    Array(Vec<Spanned<SpannedValue>>),
+    /// An object, as its entries in the order they appear in the input (including any duplicate
+    /// keys).
+    Object(Vec<(Spanned<String>, Spanned<SpannedValue>)>),
+}
+
+impl SpannedValue {
+    /// Looks up a property if the value is an object. If the key appears more than once this is
+    /// the last entry, which is the one a [`Value`][] keeps.
+    ///
+    /// [`Value`]: enum.Value.html
+    pub fn get(&self, key: &str) -> Option<&Spanned<SpannedValue>> {
+        match *self {
+            SpannedValue::Object(ref entries) => entries
+                .iter()
+                .rev()
+                .find(|(k, _)| k.value == key)
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Looks up an element if the value is an array.
+    pub fn get_index(&self, index: usize) -> Option<&Spanned<SpannedValue>> {
+        match *self {
+            SpannedValue::Array(ref elements) => elements.get(index),
+            _ => None,
+        }
+    }
+}
+
+/// Drops the spans.
+impl From<SpannedValue> for Value {
+    fn from(v: SpannedValue) -> Self {
+        match v {
+            SpannedValue::Null => Value::Null,
+            SpannedValue::Bool(b) => Value::Bool(b),
+            SpannedValue::Number(n) => Value::Number(n),
+            SpannedValue::String(s) => Value::String(s),
+            SpannedValue::Array(elements) => {
+                Value::Array(elements.into_iter().map(|e| e.value.into()).collect())
+            }
+            SpannedValue::Object(entries) => {
+                let mut map = Map::new();
+                for (key, value) in entries {
+                    map.insert(key.value, value.value.into());
+                }
+                Value::Object(map)
+            }
+        }
+    }
+}
+
+// Only leaves are deserialized directly, so that each part of the input is only read once.
+pub(crate) fn spanned_value(node: &Node<'_>) -> Result<Spanned<SpannedValue>> {
+    let value = match node.rule() {
+        Rule::array => SpannedValue::Array(
+            node.elements()
+                .map(|element| spanned_value(&element))
+                .collect::<Result<_>>()?,
+        ),
+        Rule::object => SpannedValue::Object(
+            node.entries()
+                .map(|(key, value)| {
+                    let key = Spanned {
+                        value: key.deserialize()?,
+                        span: key.span(),
+                    };
+                    Ok((key, spanned_value(&value)?))
+                })
+                .collect::<Result<_>>()?,
+        ),
+        rule => match node.deserialize::<Value>()? {
+            Value::Null => SpannedValue::Null,
+            Value::Bool(b) => SpannedValue::Bool(b),
+            Value::Number(n) => SpannedValue::Number(n),
+            Value::String(s) => SpannedValue::String(s),
+            Value::Array(_) | Value::Object(_) => return Err(Error::Unexpected(rule)),
+        },
+    };
+    Ok(Spanned {
+        value,
+        span: node.span(),
+    })
+}
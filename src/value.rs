@@ -0,0 +1,1016 @@
+use alloc::borrow::{Cow, ToOwned};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::Index;
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{self, IntoDeserializer};
+use serde::forward_to_deserialize_any;
+use serde::ser;
+
+use crate::error::{self, Error, ValueKind};
+use crate::map::{Interner, Key, Map};
+use crate::number::Number;
+
+/// Deserialize an instance of type `T` from a [`Value`][], without going back through JSON5 text.
+/// This allows parsing a document once, inspecting or merging it, and only then deserializing it
+/// (or part of it) into a Rust type. Can fail if the value doesn&rsquo;t match the structure of
+/// the target type.
+///
+/// Values are read as [`from_str`][] reads the text they came from, so e.g. `Option`s are `null` or
+/// `Some`, enums are strings or single entry objects, and numbers not written as integers are
+/// truncated towards zero when deserialized into integer types.
+///
+/// ```rust
+/// use serde_derive::Deserialize;
+///
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// struct Server {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// let value: json5::Value = json5::from_str("{ server: { host: 'localhost', port: 80 } }").unwrap();
+/// assert_eq!(
+///     json5::from_value(value["server"].clone()),
+///     Ok(Server { host: "localhost".to_owned(), port: 80 }),
+/// );
+/// ```
+///
+/// [`Value`]: enum.Value.html
+/// [`from_str`]: fn.from_str.html
+pub fn from_value<T>(value: Value) -> error::Result<T>
+where
+    T: de::DeserializeOwned,
+{
+    T::deserialize(value)
+}
+
+/// Any valid JSON5 value, for when the shape of the input isn&rsquo;t known ahead of time.
+///
+/// ```rust
+/// use json5::Value;
+///
+/// let value: Value = json5::from_str("{ name: 'json5', tags: ['config', 'serde'] }").unwrap();
+/// assert_eq!(value["name"].as_str(), Some("json5"));
+/// assert_eq!(value["tags"][1].as_str(), Some("serde"));
+/// assert!(value["missing"].is_null());
+/// ```
+///
+/// Values are equal if they have the same structure and equal contents, so two parsed documents
+/// can be compared regardless of formatting, comments or (for objects) the order of entries. The
+/// numbers in them compare as [`Number`][]s do, so `5` and `5.0` are not equal, and a value
+/// containing `NaN` is not equal to itself. `Value` isn&rsquo;t `Eq` or `Hash` for that reason,
+/// but [`total_cmp`][] orders any values, so they can be sorted and deduplicated.
+///
+/// [`Number`]: struct.Number.html
+/// [`total_cmp`]: #method.total_cmp
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool(bool),
+    /// Any number, including `Infinity` and `NaN`.
+    Number(Number),
+    /// A string, with any escapes resolved.
+    String(String),
+    /// An array of values.
+    Array(Vec<Value>),
+    /// An object, keyed by its (unquoted) property names.
+    Object(Map),
+}
+
+static NULL: Value = Value::Null;
+
+impl Value {
+    /// Returns true if the value is `null`.
+    pub fn is_null(&self) -> bool {
+        matches!(*self, Value::Null)
+    }
+
+    /// Returns the boolean if the value is one.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Returns the number if the value is one.
+    pub fn as_number(&self) -> Option<&Number> {
+        match *self {
+            Value::Number(ref n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Returns the number as an `i64` if the value is an integer that fits.
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number().and_then(Number::as_i64)
+    }
+
+    /// Returns the number as a `u64` if the value is a non-negative integer.
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_number().and_then(Number::as_u64)
+    }
+
+    /// Returns the number as an `f64` if the value is a number of any kind.
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_number().map(Number::as_f64)
+    }
+
+    /// Returns the string slice if the value is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Value::String(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements if the value is an array.
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match *self {
+            Value::Array(ref a) => Some(a),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries if the value is an object.
+    pub fn as_object(&self) -> Option<&Map> {
+        match *self {
+            Value::Object(ref o) => Some(o),
+            _ => None,
+        }
+    }
+
+    /// Looks up a property if the value is an object. Returns `None` if the value is not an object
+    /// or doesn&rsquo;t have the property.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.as_object().and_then(|o| o.get(key))
+    }
+
+    /// Looks up a value by a [JSON Pointer][], such as `/servers/0/port`: each `/` is followed by
+    /// an object key or an array index, with `~1` standing for `/` and `~0` for `~` in keys. The
+    /// empty pointer is the value itself. Returns `None` if the pointer doesn&rsquo;t start with
+    /// `/` or there is no value at it.
+    ///
+    /// ```rust
+    /// let value: json5::Value = json5::from_str("{ servers: [{ port: 80 }], 'a/b': 1 }").unwrap();
+    /// assert_eq!(value.pointer("/servers/0/port"), Some(&json5::Value::from(80)));
+    /// assert_eq!(value.pointer("/a~1b"), Some(&json5::Value::from(1)));
+    /// assert_eq!(value.pointer("/servers/1"), None);
+    /// ```
+    ///
+    /// [JSON Pointer]: https://tools.ietf.org/html/rfc6901
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .try_fold(self, |value, token| match *value {
+                Value::Object(ref o) => o.get(&unescape_token(token)),
+                Value::Array(ref a) => a.get(parse_index(token)?),
+                _ => None,
+            })
+    }
+
+    /// Looks up a value by a JSON Pointer as [`pointer`](#method.pointer) does, but for
+    /// changing it.
+    ///
+    /// ```rust
+    /// let mut value: json5::Value = json5::from_str("{ servers: [{ port: 80 }] }").unwrap();
+    /// *value.pointer_mut("/servers/0/port").unwrap() = 8080.into();
+    /// assert_eq!(value["servers"][0]["port"].as_u64(), Some(8080));
+    /// ```
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .try_fold(self, |value, token| match *value {
+                Value::Object(ref mut o) => o.get_mut(&unescape_token(token)),
+                Value::Array(ref mut a) => a.get_mut(parse_index(token)?),
+                _ => None,
+            })
+    }
+
+    /// Orders any two values. Values of different types are ordered `null`, booleans, numbers,
+    /// strings, arrays, objects. Numbers are ordered by [`Number::total_cmp`][], arrays by their
+    /// elements in turn and objects by their entries in turn, in order of key. Values compare as
+    /// `Equal` if they are equal (`==`), or differ only by the `NaN`s in them.
+    ///
+    /// ```rust
+    /// use json5::Value;
+    ///
+    /// let mut values: Vec<Value> = json5::from_str("[{ a: 1 }, 'x', [NaN], null, 2, [NaN]]").unwrap();
+    /// values.sort_by(Value::total_cmp);
+    /// values.dedup_by(|a, b| a.total_cmp(b).is_eq());
+    /// let expected: Value = json5::from_str("[null, 2, 'x', [NaN], { a: 1 }]").unwrap();
+    /// assert!(Value::Array(values).total_cmp(&expected).is_eq());
+    /// ```
+    ///
+    /// [`Number::total_cmp`]: struct.Number.html#method.total_cmp
+    pub fn total_cmp(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::Null, Value::Null) => Ordering::Equal,
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::Number(a), Value::Number(b)) => a.total_cmp(b),
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            (Value::Array(a), Value::Array(b)) => cmp_by(a.iter(), b.iter(), |a, b| a.total_cmp(b)),
+            (Value::Object(a), Value::Object(b)) => {
+                // With `preserve_order` the entries aren't sorted, and the order doesn't affect
+                // equality.
+                let mut a: Vec<_> = a.iter().collect();
+                let mut b: Vec<_> = b.iter().collect();
+                a.sort_by_key(|&(k, _)| k);
+                b.sort_by_key(|&(k, _)| k);
+                cmp_by(a.into_iter(), b.into_iter(), |(ka, va), (kb, vb)| {
+                    ka.cmp(kb).then_with(|| va.total_cmp(vb))
+                })
+            }
+            _ => self.type_order().cmp(&other.type_order()),
+        }
+    }
+
+    /// Deep-merges `other` into this value, as for layering overrides onto a base config. Where
+    /// both are objects, each entry of `other` is merged into the entry with the same key, or added
+    /// if there isn&rsquo;t one. Anything else, including arrays, is replaced by `other`, so an
+    /// override of `null` clears a setting. Use [`merge_with`][] to concatenate arrays instead.
+    ///
+    /// ```rust
+    /// let mut config: json5::Value = json5::from_str("{ server: { host: 'localhost', port: 80 }, tags: ['a'] }").unwrap();
+    /// config.merge(json5::from_str("{ server: { port: 8080 }, tags: ['b'] }").unwrap());
+    ///
+    /// let expected: json5::Value = json5::from_str("{ server: { host: 'localhost', port: 8080 }, tags: ['b'] }").unwrap();
+    /// assert_eq!(config, expected);
+    /// ```
+    ///
+    /// [`merge_with`]: #method.merge_with
+    pub fn merge(&mut self, other: Value) {
+        self.merge_with(other, MergeStrategy::Replace);
+    }
+
+    /// Deep-merges `other` into this value as [`merge`][] does, but with `strategy` deciding what
+    /// happens where both are arrays, at any depth.
+    ///
+    /// ```rust
+    /// use json5::MergeStrategy;
+    ///
+    /// let mut config: json5::Value = json5::from_str("{ plugins: ['a'] }").unwrap();
+    /// config.merge_with(json5::from_str("{ plugins: ['b'] }").unwrap(), MergeStrategy::Concat);
+    /// assert_eq!(config, json5::from_str::<json5::Value>("{ plugins: ['a', 'b'] }").unwrap());
+    /// ```
+    ///
+    /// [`merge`]: #method.merge
+    pub fn merge_with(&mut self, other: Value, strategy: MergeStrategy) {
+        match (self, other) {
+            (Value::Object(base), Value::Object(other)) => {
+                for (key, value) in other {
+                    match base.get_mut(&key) {
+                        Some(existing) => existing.merge_with(value, strategy),
+                        None => {
+                            base.insert(key, value);
+                        }
+                    }
+                }
+            }
+            (Value::Array(base), Value::Array(other)) if strategy == MergeStrategy::Concat => {
+                base.extend(other);
+            }
+            (base, other) => *base = other,
+        }
+    }
+
+    fn type_order(&self) -> u8 {
+        match *self {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Number(_) => 2,
+            Value::String(_) => 3,
+            Value::Array(_) => 4,
+            Value::Object(_) => 5,
+        }
+    }
+}
+
+/// What [`Value::merge_with`][] does where both values are arrays.
+///
+/// [`Value::merge_with`]: enum.Value.html#method.merge_with
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum MergeStrategy {
+    /// The array being merged in replaces the existing one, as for any other value. The default,
+    /// and what [`Value::merge`][] does.
+    ///
+    /// [`Value::merge`]: enum.Value.html#method.merge
+    #[default]
+    Replace,
+    /// The elements of the array being merged in are appended to the existing ones.
+    Concat,
+}
+
+// `~1` must be replaced before `~0`, so that `~01` is `~1` rather than `/`.
+fn unescape_token(token: &str) -> Cow<'_, str> {
+    if token.contains('~') {
+        Cow::Owned(token.replace("~1", "/").replace("~0", "~"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+// JSON Pointer indices are decimal digits without leading zeros. (`-`, for the element after the
+// last, never exists.)
+fn parse_index(token: &str) -> Option<usize> {
+    if token.starts_with('+') || (token.starts_with('0') && token.len() > 1) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+// Lexicographic order, as `Iterator::cmp_by` (which isn't stable) gives.
+fn cmp_by<I, F>(mut a: I, mut b: I, mut cmp: F) -> Ordering
+where
+    I: Iterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => match cmp(&x, &y) {
+                Ordering::Equal => {}
+                order => return order,
+            },
+        }
+    }
+}
+
+/// Looks up a property of an object, giving `Value::Null` if the value is not an object or
+/// doesn&rsquo;t have the property.
+impl Index<&str> for Value {
+    type Output = Value;
+
+    fn index(&self, key: &str) -> &Value {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+/// Looks up an element of an array, giving `Value::Null` if the value is not an array or the index
+/// is out of bounds.
+impl Index<usize> for Value {
+    type Output = Value;
+
+    fn index(&self, index: usize) -> &Value {
+        self.as_array().and_then(|a| a.get(index)).unwrap_or(&NULL)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Self {
+        Value::Bool(v)
+    }
+}
+
+impl From<Number> for Value {
+    fn from(v: Number) -> Self {
+        Value::Number(v)
+    }
+}
+
+macro_rules! from_number {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(v: $ty) -> Self {
+                    Value::Number(Number::from(v))
+                }
+            }
+        )*
+    };
+}
+
+from_number!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+impl From<String> for Value {
+    fn from(v: String) -> Self {
+        Value::String(v)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Self {
+        Value::String(v.to_owned())
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(v: Vec<T>) -> Self {
+        Value::Array(v.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<Map> for Value {
+    fn from(v: Map) -> Self {
+        Value::Object(v)
+    }
+}
+
+/// `None` becomes `Value::Null`.
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Value::Null, Into::into)
+    }
+}
+
+/// Serializes as the JSON5 value it stands for, so that a document can be read into a `Value`,
+/// changed and written back out.
+///
+/// ```rust
+/// let mut value: json5::Value = json5::from_str("{ name: 'json5', tags: ['a'] }").unwrap();
+/// if let Some(json5::Value::Array(tags)) = value.pointer_mut("/tags") {
+///     tags.push("b".into());
+/// }
+/// assert_eq!(json5::to_string(&value).unwrap(), r#"{"name":"json5","tags":["a","b"]}"#);
+/// ```
+impl ser::Serialize for Value {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match *self {
+            Value::Null => serializer.serialize_unit(),
+            Value::Bool(b) => serializer.serialize_bool(b),
+            Value::Number(ref n) => n.serialize(serializer),
+            Value::String(ref s) => serializer.serialize_str(s),
+            Value::Array(ref a) => a.serialize(serializer),
+            Value::Object(ref o) => o.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Value {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ValueVisitor {
+            keys: &mut Interner::default(),
+        })
+    }
+}
+
+// Deserializes a `Value` with an interner for the keys of all the objects in it.
+struct ValueVisitor<'a> {
+    keys: &'a mut Interner,
+}
+
+impl<'de> de::DeserializeSeed<'de> for ValueVisitor<'_> {
+    type Value = Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> de::Visitor<'de> for ValueVisitor<'_> {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("any JSON5 value")
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::Number(v.into()))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::Number(v.into()))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::Number(v.into()))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(v.to_owned()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(element) = seq.next_element_seed(ValueVisitor {
+            keys: &mut *self.keys,
+        })? {
+            elements.push(element);
+        }
+        Ok(Value::Array(elements))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let mut entries = Map::new();
+        while let Some(key) = map.next_key_seed(KeyVisitor {
+            keys: &mut *self.keys,
+        })? {
+            let value = map.next_value_seed(ValueVisitor {
+                keys: &mut *self.keys,
+            })?;
+            entries.insert_key(key, value);
+        }
+        Ok(Value::Object(entries))
+    }
+}
+
+struct KeyVisitor<'a> {
+    keys: &'a mut Interner,
+}
+
+impl<'de> de::DeserializeSeed<'de> for KeyVisitor<'_> {
+    type Value = Key;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Key, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de> de::Visitor<'de> for KeyVisitor<'_> {
+    type Value = Key;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a string key")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Key, E> {
+        Ok(self.keys.intern(v))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Key, E> {
+        Ok(self.keys.intern_string(v))
+    }
+}
+
+impl Value {
+    fn kind(&self) -> ValueKind {
+        match *self {
+            Value::Null => ValueKind::Null,
+            Value::Bool(_) => ValueKind::Bool,
+            Value::Number(_) => ValueKind::Number,
+            Value::String(_) => ValueKind::String,
+            Value::Array(_) => ValueKind::Array,
+            Value::Object(_) => ValueKind::Object,
+        }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Deserializes a part of a borrowed `Value` without cloning it, so its strings can be borrowed by
+/// the `Deserialize` implementation.
+///
+/// ```rust
+/// use serde::de::{Deserialize, IntoDeserializer};
+///
+/// let value: json5::Value = json5::from_str("{ servers: [{ host: 'a' }, { host: 'b' }] }").unwrap();
+/// let hosts = value["servers"]
+///     .as_array()
+///     .unwrap()
+///     .iter()
+///     .map(|server| <&str>::deserialize((&server["host"]).into_deserializer()))
+///     .collect::<json5::Result<Vec<_>>>();
+/// assert_eq!(hosts, Ok(vec!["a", "b"]));
+/// ```
+impl<'de> IntoDeserializer<'de, Error> for &'de Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! deserialize_integer {
+    ($($method:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> error::Result<V::Value>
+            where
+                V: de::Visitor<'de>,
+            {
+                match self {
+                    Value::Number(n) => n.visit_integer(visitor),
+                    _ => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+/// Deserializes a `Value` as if it were JSON5 text, for [`from_value`][].
+///
+/// [`from_value`]: fn.from_value.html
+impl<'de> de::Deserializer<'de> for Value {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> error::Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(b),
+            Value::Number(n) => n.visit(visitor),
+            Value::String(s) => visitor.visit_string(s),
+            Value::Array(a) => {
+                let mut seq = de::value::SeqDeserializer::new(a.into_iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Value::Object(o) => {
+                let mut map = de::value::MapDeserializer::new(o.into_iter());
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    deserialize_integer! {
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_i128,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_u128,
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> error::Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &str, visitor: V) -> error::Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> error::Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Value::Array(ref a) if a.len() != len => Err(Error::WrongTupleLength {
+                expected: len,
+                found: a.len(),
+            }),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> error::Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> error::Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Value::String(s) => visitor.visit_enum(s.into_deserializer()),
+            Value::Object(o) if o.len() == 1 => {
+                let (variant, value) = o.into_iter().next().unwrap();
+                visitor.visit_enum(Enum { variant, value })
+            }
+            _ => Err(de::Error::custom(format_args!(
+                "expected a string or an object with a single entry, found {}",
+                self.kind()
+            ))),
+        }
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> error::Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool f32 f64 char str string bytes byte_buf unit unit_struct seq
+        map struct identifier
+    }
+}
+
+struct Enum {
+    variant: String,
+    value: Value,
+}
+
+impl<'de> de::EnumAccess<'de> for Enum {
+    type Error = Error;
+    type Variant = Value;
+
+    fn variant_seed<V>(self, seed: V) -> error::Result<(V::Value, Value)>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant =
+            seed.deserialize(IntoDeserializer::<Error>::into_deserializer(self.variant))?;
+        Ok((variant, self.value))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Value {
+    type Error = Error;
+
+    fn unit_variant(self) -> error::Result<()> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> error::Result<T::Value>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> error::Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Value::Array(_) => de::Deserializer::deserialize_tuple(self, len, visitor),
+            _ => Err(de::Error::custom(format_args!(
+                "expected an array, found {}",
+                self.kind()
+            ))),
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> error::Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Value::Object(_) => de::Deserializer::deserialize_any(self, visitor),
+            _ => Err(de::Error::custom(format_args!(
+                "expected an object, found {}",
+                self.kind()
+            ))),
+        }
+    }
+}
+
+/// Deserializes a borrowed `Value` in the same way as an owned one, but with borrowed strings.
+impl<'de> de::Deserializer<'de> for &'de Value {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> error::Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match *self {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(b),
+            Value::Number(n) => n.visit(visitor),
+            Value::String(ref s) => visitor.visit_borrowed_str(s),
+            Value::Array(ref a) => {
+                let mut seq = de::value::SeqDeserializer::new(a.iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Value::Object(ref o) => {
+                let mut map = de::value::MapDeserializer::new(
+                    o.iter().map(|(k, v)| (BorrowedStrDeserializer::new(k), v)),
+                );
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    deserialize_integer! {
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_i128,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_u128,
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> error::Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match *self {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &str, visitor: V) -> error::Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> error::Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match *self {
+            Value::Array(ref a) if a.len() != len => Err(Error::WrongTupleLength {
+                expected: len,
+                found: a.len(),
+            }),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> error::Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> error::Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match *self {
+            Value::String(ref s) => visitor.visit_enum(BorrowedStrDeserializer::new(s)),
+            Value::Object(ref o) if o.len() == 1 => {
+                let (variant, value) = o.iter().next().unwrap();
+                visitor.visit_enum(BorrowedEnum { variant, value })
+            }
+            _ => Err(de::Error::custom(format_args!(
+                "expected a string or an object with a single entry, found {}",
+                self.kind()
+            ))),
+        }
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> error::Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool f32 f64 char str string bytes byte_buf unit unit_struct seq
+        map struct identifier
+    }
+}
+
+struct BorrowedEnum<'de> {
+    variant: &'de str,
+    value: &'de Value,
+}
+
+impl<'de> de::EnumAccess<'de> for BorrowedEnum<'de> {
+    type Error = Error;
+    type Variant = &'de Value;
+
+    fn variant_seed<V>(self, seed: V) -> error::Result<(V::Value, &'de Value)>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(BorrowedStrDeserializer::<Error>::new(self.variant))?;
+        Ok((variant, self.value))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &'de Value {
+    type Error = Error;
+
+    fn unit_variant(self) -> error::Result<()> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> error::Result<T::Value>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> error::Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match *self {
+            Value::Array(_) => de::Deserializer::deserialize_tuple(self, len, visitor),
+            _ => Err(de::Error::custom(format_args!(
+                "expected an array, found {}",
+                self.kind()
+            ))),
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> error::Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match *self {
+            Value::Object(_) => de::Deserializer::deserialize_any(self, visitor),
+            _ => Err(de::Error::custom(format_args!(
+                "expected an object, found {}",
+                self.kind()
+            ))),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+use pest::iterators::Pair;
+
+use crate::de::{locate, radix_of_literal, split_sign, Rule};
+use crate::error::{Location, Span};
+
+/// A use of something that isn&rsquo;t part of JSON5, but was accepted because the options it
+/// was read with allow it. Collected by [`from_str_with_warnings`][].
+///
+/// [`from_str_with_warnings`]: fn.from_str_with_warnings.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+    /// What was used.
+    pub kind: WarningKind,
+    /// Where the token using it starts in the input.
+    pub location: Location,
+    /// The byte range of the token using it in the input.
+    pub span: Span,
+}
+
+/// The extensions to JSON5 that a [`Warning`][] can be about.
+///
+/// [`Warning`]: struct.Warning.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WarningKind {
+    /// An integer written in octal (`0o755`), as allowed by `allow_octal_and_binary`.
+    OctalLiteral,
+    /// An integer written in binary (`0b1010`), as allowed by `allow_octal_and_binary`.
+    BinaryLiteral,
+    /// A number with underscores between its digits (`1_000`), as allowed by
+    /// `allow_underscores_in_numbers`.
+    UnderscoresInNumber,
+}
+
+impl WarningKind {
+    // The option that allows the extension.
+    pub(crate) fn option(self) -> &'static str {
+        match self {
+            WarningKind::OctalLiteral | WarningKind::BinaryLiteral => "allow_octal_and_binary",
+            WarningKind::UnderscoresInNumber => "allow_underscores_in_numbers",
+        }
+    }
+}
+
+impl Display for WarningKind {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(match *self {
+            WarningKind::OctalLiteral => "octal literals",
+            WarningKind::BinaryLiteral => "binary literals",
+            WarningKind::UnderscoresInNumber => "underscores in numbers",
+        })
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "{} are not JSON5, used at line {} column {}",
+            self.kind, self.location.line, self.location.column
+        )
+    }
+}
+
+// Every token in the document is checked, including those in values the `Deserialize`
+// implementation skipped, since they are part of the input all the same.
+pub(crate) fn warnings(pair: &Pair<'_, Rule>) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    let numbers = Some(pair.clone())
+        .into_iter()
+        .chain(pair.clone().into_inner().flatten())
+        .filter(|pair| pair.as_rule() == Rule::number);
+    for number in numbers {
+        let s = number.as_str();
+        let (location, span) = locate(&number.as_span());
+        let mut warn = |kind| {
+            warnings.push(Warning {
+                kind,
+                location,
+                span,
+            })
+        };
+        match radix_of_literal(split_sign(s).1) {
+            8 => warn(WarningKind::OctalLiteral),
+            2 => warn(WarningKind::BinaryLiteral),
+            _ => {}
+        }
+        if s.contains('_') {
+            warn(WarningKind::UnderscoresInNumber);
+        }
+    }
+    warnings
+}
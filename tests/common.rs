@@ -1,10 +1,9 @@
-use json5;
-use serde;
 use serde_derive::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use std::f64;
 
+#[allow(dead_code)]
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
 #[serde(untagged)]
 pub enum Val {
@@ -23,23 +22,23 @@ where
 {
     match json5::from_str::<T>(s) {
         Ok(value) => assert_eq!(value, v),
-        Err(err) => panic!(format!("{}", err)),
+        Err(err) => panic!("{}", err),
     }
 }
 
 #[allow(dead_code)]
-pub fn deserializes_to_nan_f32<'a>(s: &'a str) {
+pub fn deserializes_to_nan_f32(s: &str) {
     match json5::from_str::<f32>(s) {
         Ok(value) => assert!(value.is_nan()),
-        Err(err) => panic!(format!("{}", err)),
+        Err(err) => panic!("{}", err),
     }
 }
 
 #[allow(dead_code)]
-pub fn deserializes_to_nan_f64<'a>(s: &'a str) {
+pub fn deserializes_to_nan_f64(s: &str) {
     match json5::from_str::<f64>(s) {
         Ok(value) => assert!(value.is_nan()),
-        Err(err) => panic!(format!("{}", err)),
+        Err(err) => panic!("{}", err),
     }
 }
 
@@ -49,7 +48,7 @@ where
     T: ::std::fmt::Debug + ::std::cmp::PartialEq + serde::de::Deserialize<'a>,
 {
     match json5::from_str::<T>(s) {
-        Ok(val) => panic!(format!("error expected!, got {:?}", val)),
+        Ok(val) => panic!("error expected!, got {:?}", val),
         Err(err) => assert_eq!(format!("{}", err), error_expected),
     }
 }
@@ -61,6 +60,6 @@ where
 {
     match json5::to_string::<T>(&v) {
         Ok(value) => assert_eq!(value, s),
-        Err(err) => panic!(format!("{}", err)),
+        Err(err) => panic!("{}", err),
     }
 }
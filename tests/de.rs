@@ -3,6 +3,8 @@ use serde_derive::Deserialize;
 
 use std::collections::HashMap;
 use std::fmt;
+#[cfg(feature = "std")]
+use std::io;
 
 mod common;
 
@@ -16,6 +18,86 @@ fn deserializes_bool() {
     deserializes_to("false", false);
 }
 
+#[test]
+fn deserializes_number_with_error() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S {
+        a: i32,
+    }
+
+    let err = json5::from_str::<S>("{\n  a: {},\n}").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "expected a number, found an object at line 2 column 6"
+    );
+    assert_eq!(
+        err,
+        json5::Error::ExpectedNumber {
+            found: json5::ValueKind::Object,
+            location: json5::Location { line: 2, column: 6 },
+            span: json5::Span { start: 7, end: 9 },
+        }
+    );
+    deserializes_with_error(
+        "'1'",
+        0u8,
+        "expected a number, found a string at line 1 column 1",
+    );
+    deserializes_with_error(
+        "[1, null]",
+        vec![0i64],
+        "expected a number, found null at line 1 column 5",
+    );
+    deserializes_with_error(
+        "[true]",
+        vec![0u128],
+        "expected a number, found a boolean at line 1 column 2",
+    );
+    deserializes_with_error(
+        "[]",
+        0f32,
+        "expected a number, found an array at line 1 column 1",
+    );
+    deserializes_with_error(
+        "{ a: {} }",
+        0f64,
+        "expected a number, found an object at line 1 column 1",
+    );
+    deserializes_to("{ '1': 2.5 }", HashMap::from([(1u8, 2.5f32)]));
+}
+
+#[test]
+fn deserializes_bool_with_error() {
+    let err = json5::from_str::<bool>("5").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "expected a boolean, found a number at line 1 column 1"
+    );
+    assert_eq!(
+        err,
+        json5::Error::ExpectedBool {
+            found: json5::ValueKind::Number,
+            location: json5::Location { line: 1, column: 1 },
+            span: json5::Span { start: 0, end: 1 },
+        }
+    );
+    deserializes_with_error(
+        "'true'",
+        false,
+        "expected a boolean, found a string at line 1 column 1",
+    );
+    deserializes_with_error(
+        "[true, null]",
+        vec![false],
+        "expected a boolean, found null at line 1 column 8",
+    );
+
+    let err = json5::from_str::<HashMap<String, bool>>("{\n  a: 1,\n}").unwrap_err();
+    assert_eq!(err.location(), Some(json5::Location { line: 2, column: 6 }));
+
+    deserializes_to("{ true: 1 }", HashMap::from([(true, 1)]));
+}
+
 #[test]
 fn deserializes_i8() {
     let x: i8 = 42;
@@ -180,6 +262,168 @@ fn deserializes_u64() {
     deserializes_to("0.42e2", x);
 }
 
+#[test]
+fn deserializes_i128() {
+    deserializes_to("42", 42i128);
+    deserializes_to("-0x2A", -42i128);
+    deserializes_to("170141183460469231731687303715884105727", i128::MAX);
+    deserializes_to("-170141183460469231731687303715884105728", i128::MIN);
+    deserializes_to("0x7FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF", i128::MAX);
+    deserializes_to("-0x80000000000000000000000000000000", i128::MIN);
+    // One more than 2^53, which an `f64` can't represent.
+    deserializes_to("9007199254740993", 9007199254740993i128);
+    deserializes_to(
+        "{ '-9007199254740993': 0 }",
+        HashMap::from([(-9007199254740993i128, 0)]),
+    );
+
+    assert!(json5::from_str::<i128>("170141183460469231731687303715884105728").is_err());
+    assert!(json5::from_str::<i128>("-0x80000000000000000000000000000001").is_err());
+}
+
+#[test]
+fn deserializes_u128() {
+    deserializes_to("42", 42u128);
+    deserializes_to("340282366920938463463374607431768211455", u128::MAX);
+    deserializes_to("0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF", u128::MAX);
+    deserializes_to("+340282366920938463463374607431768211455", u128::MAX);
+    deserializes_to("18446744073709551617", u64::MAX as u128 + 2);
+    deserializes_to(
+        "{ '340282366920938463463374607431768211455': 0 }",
+        HashMap::from([(u128::MAX, 0)]),
+    );
+
+    assert!(json5::from_str::<u128>("340282366920938463463374607431768211456").is_err());
+    assert!(json5::from_str::<u128>("-1").is_err());
+}
+
+#[test]
+fn deserializes_positive_signed_numbers() {
+    deserializes_to("+123", 123i32);
+    deserializes_to("+123", 123u64);
+    deserializes_to("+0x2A", 42u8);
+    deserializes_to("+1.5", 1.5f64);
+    deserializes_to("+.5", 0.5f32);
+    deserializes_to("+0x2A", 42f64);
+    deserializes_to("-0x2A", -42f64);
+    deserializes_to("+Infinity", f64::INFINITY);
+    deserializes_to("+Infinity", f32::INFINITY);
+    deserializes_to_nan_f64("+NaN");
+    deserializes_to_nan_f32("+NaN");
+    deserializes_to("+123", serde_json::json!(123));
+}
+
+#[test]
+fn deserializes_integers_exactly() {
+    deserializes_to("9007199254740993", 9_007_199_254_740_993i64);
+    deserializes_to("-9007199254740993", -9_007_199_254_740_993i64);
+    deserializes_to("18446744073709551615", u64::MAX);
+    deserializes_to("0xFFFFFFFFFFFFFFFF", u64::MAX);
+    deserializes_to("-9223372036854775808", i64::MIN);
+    deserializes_to("-0x8000000000000000", i64::MIN);
+    deserializes_to("9223372036854775807", i64::MAX);
+}
+
+#[test]
+fn deserializes_integers_out_of_range_with_error() {
+    deserializes_with_error("256", 0u8, "expected a u8, found 256 at line 1 column 1");
+    deserializes_with_error("-1", 0u64, "expected a u64, found -1 at line 1 column 1");
+    deserializes_with_error(
+        "18446744073709551616",
+        0u64,
+        "expected a u64, found 18446744073709551616 at line 1 column 1",
+    );
+    deserializes_with_error(
+        "0x100",
+        0u8,
+        "expected a u8, found 0x100 at line 1 column 1",
+    );
+    deserializes_with_error(
+        "2.56e2",
+        0u8,
+        "expected a u8, found 2.56e2 at line 1 column 1",
+    );
+    deserializes_with_error(
+        "1e30",
+        0i64,
+        "expected an i64, found 1e30 at line 1 column 1",
+    );
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S {
+        a: u8,
+    }
+    let err = json5::from_str::<S>("{\n a: 300 }").unwrap_err();
+    assert_eq!(
+        err,
+        json5::Error::IntegerOutOfRange {
+            number: "300".to_owned(),
+            expected: "a u8",
+            location: json5::Location { line: 2, column: 5 },
+            span: json5::Span { start: 6, end: 9 },
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "expected a u8, found 300 at line 2 column 5"
+    );
+}
+
+#[test]
+fn deserializes_non_finite_into_integers_with_error() {
+    for input in &["Infinity", "-Infinity", "+Infinity", "NaN", "-NaN"] {
+        assert_eq!(
+            json5::from_str::<i64>(input),
+            Err(json5::Error::ExpectedInteger((*input).to_owned()))
+        );
+        assert!(json5::from_str::<u8>(input).is_err());
+        assert!(json5::from_str::<HashMap<String, u32>>(&format!("{{ a: {} }}", input)).is_err());
+    }
+    deserializes_with_error("NaN", 0u64, "expected an integer, found NaN");
+    deserializes_with_error(
+        "[1, -Infinity]",
+        vec![0i32],
+        "expected an integer, found -Infinity",
+    );
+}
+
+#[test]
+fn deserializes_number_tokens_without_panicking() {
+    // Every short token over these characters is either rejected by the grammar or parsed as a
+    // number, never accepted by one and then failed (or panicked) on by the other.
+    let alphabet = ["0", "1", "9", ".", "e", "E", "+", "-", "x", "b", "_", "F"];
+    let mut tokens = vec![String::new()];
+    for _ in 0..4 {
+        tokens = tokens
+            .iter()
+            .flat_map(|t| alphabet.iter().map(move |c| format!("{}{}", t, c)))
+            .chain(tokens.iter().cloned())
+            .collect();
+        tokens.sort();
+        tokens.dedup();
+    }
+
+    let options = json5::DeserializeOptions::new()
+        .allow_octal_and_binary(true)
+        .allow_underscores_in_numbers(true);
+    for token in &tokens {
+        let results = [
+            options.from_str::<f64>(token).map(|_| ()),
+            options.from_str::<f32>(token).map(|_| ()),
+            options.from_str::<i64>(token).map(|_| ()),
+            options.from_str::<u8>(token).map(|_| ()),
+            options.from_str::<json5::Value>(token).map(|_| ()),
+        ];
+        for result in &results {
+            assert!(
+                !matches!(result, Err(json5::Error::InvalidNumber(_))),
+                "{:?}",
+                token
+            );
+        }
+    }
+}
+
 #[test]
 fn deserializes_f32() {
     let x: f32 = 42.42;
@@ -196,10 +440,18 @@ fn deserializes_f32() {
     deserializes_to("-.4242e2", -x);
     deserializes_to("-0.4242e2", -x);
 
-    deserializes_to("Infinity", std::f32::INFINITY);
-    deserializes_to("-Infinity", std::f32::NEG_INFINITY);
+    deserializes_to("Infinity", f32::INFINITY);
+    deserializes_to("-Infinity", f32::NEG_INFINITY);
     deserializes_to_nan_f32("NaN");
     deserializes_to_nan_f32("-NaN");
+
+    deserializes_to("0.1", "0.1".parse::<f32>().unwrap());
+    deserializes_to("0x10", 16f32);
+    deserializes_to("-0xFFFFFFFF", -4294967295f32);
+    // Halfway between two f32s once rounded to f64, so narrowing from f64 would round down.
+    let s = "1.000000059604644775390625000000001";
+    deserializes_to(s, s.parse::<f32>().unwrap());
+    assert_ne!(s.parse::<f32>().unwrap(), s.parse::<f64>().unwrap() as f32);
 }
 
 #[test]
@@ -218,12 +470,37 @@ fn deserializes_f64() {
     deserializes_to("-.4242e2", -x);
     deserializes_to("-0.4242e2", -x);
 
-    deserializes_to("Infinity", std::f64::INFINITY);
-    deserializes_to("-Infinity", std::f64::NEG_INFINITY);
+    deserializes_to("Infinity", f64::INFINITY);
+    deserializes_to("-Infinity", f64::NEG_INFINITY);
     deserializes_to_nan_f64("NaN");
     deserializes_to_nan_f64("-NaN");
 }
 
+#[test]
+fn deserializes_float_without_leading_or_trailing_digit() {
+    for (s, x) in [
+        (".5", 0.5),
+        ("5.", 5.),
+        ("+.5", 0.5),
+        ("-.5", -0.5),
+        ("+5.", 5.),
+        ("5.e1", 50.),
+    ] {
+        deserializes_to(s, x);
+        deserializes_to(s, x as f32);
+        deserializes_to(s, json5::Value::Number(x.into()));
+    }
+    deserializes_to("5.", 5u8);
+    deserializes_to("[.5, 5.,]", vec![0.5, 5.]);
+
+    let zero = json5::from_str::<f64>("-0.").unwrap();
+    assert_eq!(zero, 0.);
+    assert!(zero.is_sign_negative());
+    let zero = json5::from_str::<f32>("-0.").unwrap();
+    assert_eq!(zero, 0.);
+    assert!(zero.is_sign_negative());
+}
+
 #[test]
 fn deserializes_char() {
     deserializes_to("'x'", 'x');
@@ -236,15 +513,59 @@ fn deserializes_char() {
     deserializes_to(r#""\/""#, '/');
     deserializes_to(r#""\b""#, '\u{0008}');
     deserializes_to(r#""\f""#, '\u{000c}');
+    deserializes_to(r#""\u0041""#, 'A');
+}
+
+#[test]
+fn deserializes_char_with_error() {
+    deserializes_with_error(
+        "''",
+        'x',
+        "expected a string containing a single character, found \"\"",
+    );
+    deserializes_with_error(
+        "'xy'",
+        'x',
+        "expected a string containing a single character, found \"xy\"",
+    );
+    deserializes_with_error(
+        "1",
+        'x',
+        "expected a string, found a number at line 1 column 1",
+    );
 }
 
 #[test]
-#[ignore] // TODO currently unsupported
 fn deserializes_str() {
     deserializes_to("'Hello!'", "Hello!");
     deserializes_to("\"안녕하세요\"", "안녕하세요");
 }
 
+#[test]
+fn deserializes_surrogate_pairs() {
+    deserializes_to("'\\uD834\\uDD1E'", "\u{1D11E}".to_owned());
+    deserializes_to("'a\\ud834\\udd1eb'", "a\u{1D11E}b".to_owned());
+    deserializes_to("'\\uD834\\\n\\uDD1E'", "\u{1D11E}".to_owned());
+
+    for input in &[
+        "'\\uD800'",
+        "'\\uD800x'",
+        "'\\uD800\\u0041'",
+        "'\\uDD1E\\uD834'",
+    ] {
+        assert!(matches!(
+            json5::from_str::<String>(input),
+            Err(json5::Error::InvalidEscape(_))
+        ));
+    }
+    deserializes_with_error("'\\uDC00'", String::new(), "invalid escape \\uDC00");
+    deserializes_with_error(
+        "{ \\uD800: 1 }",
+        serde_json::json!({}),
+        "invalid escape \\uD800",
+    );
+}
+
 #[test]
 fn deserializes_string() {
     deserializes_to("'Hello!'", "Hello!".to_owned());
@@ -252,12 +573,265 @@ fn deserializes_string() {
 }
 
 #[test]
-#[ignore] // TODO currently unsupported
-fn deserializes_bytes() {}
+fn deserializes_string_with_error() {
+    use json5::{Location, Span, ValueKind};
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S {
+        name: String,
+    }
+
+    assert_eq!(
+        json5::from_str::<S>("{\n  name: [1, 2],\n}"),
+        Err(json5::Error::TypeMismatch {
+            expected: ValueKind::String,
+            found: ValueKind::Array,
+            location: Location { line: 2, column: 9 },
+            span: Span { start: 10, end: 16 },
+        })
+    );
+    deserializes_with_error(
+        "{\n\n\n  name: 42 }",
+        S {
+            name: String::new(),
+        },
+        "expected a string, found a number at line 4 column 9",
+    );
+    deserializes_with_error(
+        "null",
+        String::new(),
+        "expected a string, found null at line 1 column 1",
+    );
+    deserializes_with_error(
+        "[true]",
+        vec!['x'],
+        "expected a string, found a boolean at line 1 column 2",
+    );
+    let err = json5::from_str::<Vec<String>>("['a', {}]").unwrap_err();
+    assert_eq!(err.location(), Some(Location { line: 1, column: 7 }));
+    assert_eq!(err.span(), Some(Span { start: 6, end: 8 }));
+
+    // Unquoted keys are strings too.
+    deserializes_to(
+        "{ a: 'b' }",
+        HashMap::from([("a".to_owned(), "b".to_owned())]),
+    );
+}
+
+#[test]
+fn deserializes_string_with_nul_escape() {
+    deserializes_to("'\\0'", "\u{0}".to_owned());
+    deserializes_to("\"a\\0b\"", "a\u{0}b".to_owned());
+    deserializes_to("'\\0\\0'", "\u{0}\u{0}".to_owned());
+    deserializes_to("'\\0.'", "\u{0}.".to_owned());
+
+    // Followed by a digit it would be a legacy octal escape, as would any other digit.
+    for input in &["'\\01'", "\"\\00\"", "'a\\09'", "'\\1'", "'\\7'"] {
+        assert!(
+            matches!(
+                json5::from_str::<String>(input),
+                Err(json5::Error::Parse { .. })
+            ),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn deserializes_string_parsed_by_visitor() {
+    // Like a date-time type from `chrono` or `time`, which only accepts strings and parses them
+    // itself, failing with a custom error.
+    #[derive(PartialEq, Eq, Hash, Debug)]
+    struct Date {
+        year: u16,
+        month: u8,
+        day: u8,
+    }
+
+    impl<'de> de::Deserialize<'de> for Date {
+        fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct Visitor;
+
+            impl de::Visitor<'_> for Visitor {
+                type Value = Date;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    formatter.write_str("a date")
+                }
+
+                fn visit_str<E: de::Error>(self, s: &str) -> Result<Date, E> {
+                    let mut parts = s.splitn(3, '-').map(str::parse::<u16>);
+                    match (parts.next(), parts.next(), parts.next()) {
+                        (Some(Ok(year)), Some(Ok(month)), Some(Ok(day))) if month <= 12 => {
+                            Ok(Date {
+                                year,
+                                month: month as u8,
+                                day: day as u8,
+                            })
+                        }
+                        _ => Err(E::custom(format!("invalid date `{}`", s))),
+                    }
+                }
+            }
+
+            deserializer.deserialize_str(Visitor)
+        }
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Event {
+        timestamp: Date,
+    }
+
+    let date = |year, month, day| Date { year, month, day };
+    deserializes_to("'2024-02-29'", date(2024, 2, 29));
+    // With escapes the string is no longer borrowed from the input, which makes no difference.
+    deserializes_to("'2024\\u002D02-29'", date(2024, 2, 29));
+    deserializes_to(
+        "{ timestamp: \"1970-01-01\" }",
+        Event {
+            timestamp: date(1970, 1, 1),
+        },
+    );
+    deserializes_to("{ '2000-1-2': 0 }", HashMap::from([(date(2000, 1, 2), 0)]));
+
+    assert_eq!(
+        json5::from_str::<Event>("{ timestamp: '2024-13-01' }"),
+        Err(json5::Error::Message(
+            "invalid date `2024-13-01`".to_owned()
+        ))
+    );
+    deserializes_with_error("'yesterday'", date(0, 0, 0), "invalid date `yesterday`");
+    deserializes_with_error(
+        "{ timestamp: 1700000000 }",
+        Event {
+            timestamp: date(0, 0, 0),
+        },
+        "expected a string, found a number at line 1 column 14",
+    );
+}
+
+#[test]
+fn deserializes_string_with_line_continuations() {
+    for newline in &["\n", "\r\n", "\r", "\u{2028}", "\u{2029}"] {
+        let expected = "foobar".to_owned();
+        deserializes_to(&format!("\"foo\\{}bar\"", newline), expected.clone());
+        deserializes_to(&format!("'foo\\{}bar'", newline), expected.clone());
+        deserializes_to(&format!("'foo\\{0}\\{0}bar'", newline), expected.clone());
+        deserializes_to(
+            &format!("{{ s: 'foo\\{}bar' }}", newline),
+            HashMap::from([("s".to_owned(), expected)]),
+        );
+    }
+
+    // Only the backslash and line terminator are removed, not the indentation after them, and
+    // escaped newlines are kept.
+    deserializes_to("'a\\\n  b'", "a  b".to_owned());
+    deserializes_to("'a\\\r\n\\nb'", "a\nb".to_owned());
+    // A line terminator is only allowed in a string as part of a continuation.
+    assert!(json5::from_str::<String>("'a\nb'").is_err());
+}
+
+#[test]
+fn deserializes_bytes() {
+    #[derive(Debug, PartialEq)]
+    struct Bytes(Vec<u8>);
+    impl<'de> de::Deserialize<'de> for Bytes {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: de::Deserializer<'de>,
+        {
+            struct Visitor;
+            impl<'de> de::Visitor<'de> for Visitor {
+                type Value = Bytes;
+
+                fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.write_str("bytes")
+                }
+
+                fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E> {
+                    Ok(Bytes(v.to_vec()))
+                }
+            }
+            deserializer.deserialize_bytes(Visitor)
+        }
+    }
+
+    deserializes_to("'hello'", Bytes(b"hello".to_vec()));
+    deserializes_to("'\\u00e9'", Bytes(vec![0xc3, 0xa9]));
+    deserializes_to("[]", Bytes(vec![]));
+    deserializes_to("[0, 0xff, +7, 1e2]", Bytes(vec![0, 255, 7, 100]));
+
+    deserializes_with_error::<Bytes>(
+        "[256]",
+        Bytes(vec![]),
+        "expected a byte from 0 to 255, found 256 at line 1 column 2",
+    );
+    deserializes_with_error::<Bytes>(
+        "[-1]",
+        Bytes(vec![]),
+        "expected a byte from 0 to 255, found -1 at line 1 column 2",
+    );
+    deserializes_with_error::<Bytes>(
+        "[1, 'a']",
+        Bytes(vec![]),
+        "expected a number, found a string at line 1 column 5",
+    );
+    deserializes_with_error::<Bytes>(
+        "true",
+        Bytes(vec![]),
+        "invalid type: boolean `true`, expected bytes",
+    );
+}
 
 #[test]
-#[ignore] // TODO currently unsupported
-fn deserializes_byte_buf() {}
+fn deserializes_byte_buf() {
+    #[derive(Debug, PartialEq)]
+    struct ByteBuf(Vec<u8>);
+    impl<'de> de::Deserialize<'de> for ByteBuf {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: de::Deserializer<'de>,
+        {
+            struct Visitor;
+            impl<'de> de::Visitor<'de> for Visitor {
+                type Value = ByteBuf;
+
+                fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.write_str("bytes")
+                }
+
+                fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+                    Ok(ByteBuf(v))
+                }
+            }
+            deserializer.deserialize_byte_buf(Visitor)
+        }
+    }
+
+    deserializes_to("'hello'", ByteBuf(b"hello".to_vec()));
+    deserializes_to("[104, 105]", ByteBuf(b"hi".to_vec()));
+    deserializes_with_error(
+        "[1, 300]",
+        ByteBuf(vec![]),
+        "expected a byte from 0 to 255, found 300 at line 1 column 5",
+    );
+    deserializes_with_error(
+        "[1, Infinity]",
+        ByteBuf(vec![]),
+        "expected a byte from 0 to 255, found Infinity at line 1 column 5",
+    );
+    let err = json5::from_str::<ByteBuf>("[1,\n null]").unwrap_err();
+    assert_eq!(
+        err,
+        json5::Error::ExpectedNumber {
+            found: json5::ValueKind::Null,
+            location: json5::Location { line: 2, column: 2 },
+            span: json5::Span { start: 5, end: 9 },
+        }
+    );
+}
 
 #[test]
 fn deserializes_option() {
@@ -266,9 +840,40 @@ fn deserializes_option() {
     deserializes_to("42", Some(Some(42)));
 }
 
+#[test]
+fn deserializes_option_field() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S {
+        a: Option<i32>,
+    }
+
+    deserializes_to("{ a: null }", S { a: None });
+    deserializes_to("{ a: 5 }", S { a: Some(5) });
+}
+
 #[test]
 fn deserializes_unit() {
     deserializes_to("null", ());
+    deserializes_to("[null, null]", ((), ()));
+
+    let err = json5::from_str::<()>("5").unwrap_err();
+    assert_eq!(
+        err,
+        json5::Error::ExpectedNull {
+            found: json5::ValueKind::Number,
+            location: json5::Location { line: 1, column: 1 },
+            span: json5::Span { start: 0, end: 1 },
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "expected null, found a number at line 1 column 1"
+    );
+    deserializes_with_error(
+        "[null, 'x']",
+        vec![()],
+        "expected null, found a string at line 1 column 8",
+    );
 }
 
 #[test]
@@ -276,6 +881,16 @@ fn deserializes_unit_struct() {
     #[derive(Deserialize, PartialEq, Debug)]
     struct A;
     deserializes_to("null", A);
+    deserializes_to("[null]", vec![A]);
+
+    let err = json5::from_str::<A>("\n  {}").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "expected null, found an object at line 2 column 3"
+    );
+    assert_eq!(err.location(), Some(json5::Location { line: 2, column: 3 }));
+    assert_eq!(err.span(), Some(json5::Span { start: 3, end: 5 }));
+    assert!(json5::from_str::<A>("'A'").is_err());
 }
 
 #[test]
@@ -288,6 +903,22 @@ fn deserializes_newtype_struct() {
 
     deserializes_to("42", A(42));
     deserializes_to("42", B(42.));
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Name(String);
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Id(u64);
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Wrapper(Option<Name>);
+
+    deserializes_to("'hi'", Name("hi".to_owned()));
+    deserializes_to("18446744073709551615", Id(u64::MAX));
+    deserializes_to("0xFFFFFFFFFFFFFFFF", Id(u64::MAX));
+    deserializes_to("null", Wrapper(None));
+    deserializes_to("'hi'", Wrapper(Some(Name("hi".to_owned()))));
+    deserializes_with_error("-1", Id(0), "expected a u64, found -1 at line 1 column 1");
 }
 
 #[test]
@@ -342,20 +973,92 @@ fn deserializes_seq_size_hint() {
     deserializes_to("[]", Size(0));
     deserializes_to("[42, true, 'hello']", Size(3));
     deserializes_to("[42, true, [1, 2]]", Size(3));
+
+    // The hint is of the elements remaining, so goes down as they are read.
+    #[derive(Debug, PartialEq)]
+    struct Remaining(Vec<usize>);
+    impl<'de> de::Deserialize<'de> for Remaining {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: de::Deserializer<'de>,
+        {
+            struct Visitor;
+            impl<'de> de::Visitor<'de> for Visitor {
+                type Value = Remaining;
+
+                fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.write_str("array")
+                }
+
+                fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+                where
+                    A: serde::de::SeqAccess<'de>,
+                {
+                    let mut hints = vec![seq.size_hint().unwrap()];
+                    while seq.next_element::<de::IgnoredAny>()?.is_some() {
+                        hints.push(seq.size_hint().unwrap());
+                    }
+                    Ok(Remaining(hints))
+                }
+            }
+            deserializer.deserialize_seq(Visitor)
+        }
+    }
+
+    deserializes_to("[]", Remaining(vec![0]));
+    deserializes_to("[1, [2, 3], { a: 4 }]", Remaining(vec![3, 2, 1, 0]));
+    deserializes_to(
+        "{ a: [1, 2] }",
+        HashMap::from([("a".to_owned(), Remaining(vec![2, 1, 0]))]),
+    );
 }
 
 #[test]
 fn deserializes_tuple() {
     deserializes_to("[1, 2, 3]", (1, 2, 3));
+    deserializes_to("[1, 2, 3]", [1, 2, 3]);
 }
 
 #[test]
-fn deserializes_tuple_struct() {
-    #[derive(Deserialize, PartialEq, Debug)]
-    struct A(i32, f64);
+fn deserializes_tuple_with_wrong_length() {
+    assert_eq!(
+        json5::from_str::<(i32, i32)>("[1, 2, 3]"),
+        Err(json5::Error::WrongTupleLength {
+            expected: 2,
+            found: 3
+        })
+    );
+    assert_eq!(
+        json5::from_str::<(i32, i32)>("[1]"),
+        Err(json5::Error::WrongTupleLength {
+            expected: 2,
+            found: 1
+        })
+    );
+    deserializes_with_error(
+        "[1, 2]",
+        [0u8; 3],
+        "expected an array of length 3, found one of length 2",
+    );
 
     #[derive(Deserialize, PartialEq, Debug)]
-    struct B(f64, i32);
+    struct A(i32, f64);
+    deserializes_with_error(
+        "[1, 2, 3]",
+        A(0, 0.),
+        "expected an array of length 2, found one of length 3",
+    );
+    assert!(json5::from_str::<(i32, i32)>("{ a: 1 }").is_err());
+    deserializes_to("[[1, 2], [3, 4]]", vec![(1, 2), (3, 4)]);
+}
+
+#[test]
+fn deserializes_tuple_struct() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct A(i32, f64);
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct B(f64, i32);
 
     deserializes_to("[1, 2]", A(1, 2.));
     deserializes_to("[1, 2]", B(1., 2));
@@ -404,6 +1107,156 @@ fn deserializes_map_size_hint() {
     deserializes_to("{ a: 1, 'b': 2, \"c\": [1, 2] }", Size(3));
 }
 
+#[test]
+fn deserializes_trailing_commas() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S {
+        a: Vec<i32>,
+        b: HashMap<String, (bool,)>,
+    }
+
+    deserializes_to("[1, 2,]", vec![1, 2]);
+    deserializes_to("[1,]", vec![1]);
+    deserializes_to("[[],]", vec![Vec::<i32>::new()]);
+    deserializes_to("[1 /* one */ , // end\n]", vec![1]);
+    deserializes_to("{ a: 1, }", HashMap::from([("a".to_owned(), 1)]));
+    deserializes_to("{ 'a': 1,\n}", HashMap::from([("a".to_owned(), 1)]));
+    deserializes_to("[1, 2,]", (1, 2));
+    deserializes_to(
+        "{ a: [1, 2,], b: { x: [true,], }, }",
+        S {
+            a: vec![1, 2],
+            b: HashMap::from([("x".to_owned(), (true,))]),
+        },
+    );
+
+    // The trailing comma isn't an element or entry of its own.
+    let value = json5::from_str::<json5::Value>("[[1,], { a: 1, },]").unwrap();
+    assert_eq!(value.as_array().map(Vec::len), Some(2));
+    assert_eq!(value[0].as_array().map(Vec::len), Some(1));
+    assert_eq!(value[1].as_object().map(json5::Map::len), Some(1));
+    assert_eq!(json5::parse("[1,]").unwrap().root().elements().count(), 1);
+
+    // Only one, and only after an element or entry.
+    for input in &["[,]", "{,}", "[1,,]", "{ a: 1,, }", "[1 2,]", "{ a: 1, , }"] {
+        assert!(json5::from_str::<json5::Value>(input).is_err(), "{}", input);
+    }
+}
+
+#[test]
+fn deserializes_map_with_unusual_access() {
+    // Asks for a value before its key, or only for the keys.
+    #[derive(Debug, PartialEq)]
+    struct ValueFirst;
+    #[derive(Debug, PartialEq)]
+    struct Keys(Vec<String>);
+
+    impl<'de> de::Deserialize<'de> for ValueFirst {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: de::Deserializer<'de>,
+        {
+            struct Visitor;
+            impl<'de> de::Visitor<'de> for Visitor {
+                type Value = ValueFirst;
+
+                fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.write_str("object")
+                }
+
+                fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+                where
+                    A: serde::de::MapAccess<'de>,
+                {
+                    map.next_value::<i32>()?;
+                    Ok(ValueFirst)
+                }
+            }
+            deserializer.deserialize_map(Visitor)
+        }
+    }
+
+    impl<'de> de::Deserialize<'de> for Keys {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: de::Deserializer<'de>,
+        {
+            struct Visitor;
+            impl<'de> de::Visitor<'de> for Visitor {
+                type Value = Keys;
+
+                fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.write_str("object")
+                }
+
+                fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+                where
+                    A: serde::de::MapAccess<'de>,
+                {
+                    let mut keys = Vec::new();
+                    while let Some(key) = map.next_key()? {
+                        keys.push(key);
+                    }
+                    Ok(Keys(keys))
+                }
+            }
+            deserializer.deserialize_map(Visitor)
+        }
+    }
+
+    assert_eq!(
+        json5::from_str::<ValueFirst>("{ a: 1 }"),
+        Err(json5::Error::ExpectedMapValue)
+    );
+    assert_eq!(
+        json5::from_str::<ValueFirst>("{}"),
+        Err(json5::Error::ExpectedMapValue)
+    );
+    deserializes_to(
+        "{ a: 'b', c: { d: 'e' } }",
+        Keys(vec!["a".to_owned(), "c".to_owned()]),
+    );
+
+    // A key without a value never gets past the parser.
+    assert!(matches!(
+        json5::from_str::<HashMap<String, i32>>("{ a }"),
+        Err(json5::Error::Parse { .. })
+    ));
+}
+
+#[test]
+fn deserializes_unicode_identifier_keys() {
+    let mut m = HashMap::new();
+    m.insert("café".to_owned(), 1);
+    m.insert("$x".to_owned(), 2);
+    m.insert("_y".to_owned(), 3);
+    m.insert("a$_".to_owned(), 4);
+    m.insert("αβ".to_owned(), 5);
+    m.insert("e\u{301}".to_owned(), 6);
+    m.insert("\u{915}\u{903}".to_owned(), 7);
+    m.insert("x\u{203f}1".to_owned(), 8);
+    m.insert("abc".to_owned(), 9);
+    deserializes_to(
+        "{ café: 1, $x: 2, _y: 3, a$_: 4, αβ: 5, e\u{301}: 6, \u{915}\u{903}: 7, x\u{203f}1: 8, \\u0061bc: 9 }",
+        m,
+    );
+
+    // Marks, digits and connectors can only follow the first character.
+    for input in &[
+        "{ \u{301}e: 1 }",
+        "{ 1a: 1 }",
+        "{ \u{203f}: 1 }",
+        "{ a^b: 1 }",
+        "{ a`b: 1 }",
+    ] {
+        assert!(
+            json5::from_str::<HashMap<String, i32>>(input).is_err(),
+            "{}",
+            input
+        );
+    }
+}
+
 #[test]
 fn deserializes_struct() {
     #[derive(Deserialize, PartialEq, Debug)]
@@ -416,8 +1269,71 @@ fn deserializes_struct() {
     deserializes_to("{ a: 1, 'b': 2, \"c\": 3 }", S { a: 1, b: 2, c: 3 });
 }
 
+#[test]
+fn deserializes_struct_with_missing_fields() {
+    fn seven() -> i32 {
+        7
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S {
+        a: i32,
+        #[serde(default)]
+        b: Vec<i32>,
+        #[serde(default = "seven")]
+        c: i32,
+        d: Option<String>,
+    }
+
+    let all_missing = S {
+        a: 1,
+        b: vec![],
+        c: 7,
+        d: None,
+    };
+    deserializes_to("{ a: 1 }", all_missing);
+    deserializes_to(
+        "{ d: 'x', c: 3, a: 1 }",
+        S {
+            a: 1,
+            b: vec![],
+            c: 3,
+            d: Some("x".to_owned()),
+        },
+    );
+    deserializes_to(
+        "{ unknown: [1, { b: 2 }], a: 1, other: null, c: 3, 'd': null, last: {} }",
+        S {
+            a: 1,
+            b: vec![],
+            c: 3,
+            d: None,
+        },
+    );
+    deserializes_to(
+        "{ x: 0, b: [2], y: 0, a: 1, z: 0 }",
+        S {
+            a: 1,
+            b: vec![2],
+            c: 7,
+            d: None,
+        },
+    );
+    deserializes_with_error(
+        "{ b: [2], x: 0 }",
+        S {
+            a: 0,
+            b: vec![],
+            c: 0,
+            d: None,
+        },
+        "missing field `a`",
+    );
+}
+
 #[test]
 fn deserializes_enum() {
+    #[allow(clippy::enum_variant_names)]
     #[derive(Deserialize, PartialEq, Debug)]
     enum E {
         A,
@@ -442,6 +1358,7 @@ fn deserializes_enum_with_error() {
     enum E {
         A {},
         B(),
+        C(i32),
     }
 
     #[derive(Deserialize, PartialEq, Debug)]
@@ -449,8 +1366,42 @@ fn deserializes_enum_with_error() {
         e: E,
     }
 
-    deserializes_with_error("{ e: 'A' }", S { e: E::A {} }, "expected an object");
-    deserializes_with_error("{ e: 'B' }", S { e: E::B() }, "expected an array");
+    deserializes_with_error(
+        "{ e: 'A' }",
+        S { e: E::A {} },
+        "expected an object, found a string at line 1 column 6",
+    );
+    deserializes_with_error(
+        "{ e: 'B' }",
+        S { e: E::B() },
+        "expected an array, found a string at line 1 column 6",
+    );
+    deserializes_with_error(
+        "{ e: { A: 1 } }",
+        S { e: E::A {} },
+        "expected an object, found a number at line 1 column 11",
+    );
+    deserializes_with_error(
+        "{ e: { B: {} } }",
+        S { e: E::B() },
+        "expected an array, found an object at line 1 column 11",
+    );
+    deserializes_with_error(
+        "{ e: 'C' }",
+        S { e: E::C(0) },
+        "invalid type: unit variant, expected newtype variant",
+    );
+    deserializes_with_error(
+        "{ e: [] }",
+        S { e: E::B() },
+        "expected a string or an object, found an array at line 1 column 6",
+    );
+
+    let err: Box<dyn std::error::Error> = json5::from_str::<S>("{ e: null }").unwrap_err().into();
+    assert_eq!(
+        err.to_string(),
+        "expected a string or an object, found null at line 1 column 6"
+    );
 }
 
 #[test]
@@ -462,6 +1413,29 @@ fn deserializes_ignored() {
     }
 
     deserializes_to("{ a: 1, ignored: 42, b: 2 }", S { a: 1, b: 2 });
+
+    // Ignored values are skipped without being decoded, so an out of range number in them isn't
+    // an error, but they are still checked for escapes that aren't valid anywhere.
+    let big = format!(
+        "{{ a: 1, big: '{}', nested: [{{ c: '\\uD834\\uDD1E' }}, 1e400], b: 2 }}",
+        "\\n".repeat(100_000)
+    );
+    deserializes_to(&big, S { a: 1, b: 2 });
+    for (input, error) in &[
+        ("{ a: 1, b: 2, c: '\\uD800' }", "invalid escape \\uD800"),
+        (
+            "{ a: 1, b: 2, c: [{ d: 'x\\uDC00' }] }",
+            "invalid escape \\uDC00",
+        ),
+        (
+            "{ a: 1, b: 2, c: { '\\uD800': 1 } }",
+            "invalid escape \\uD800",
+        ),
+        ("{ a: 1, b: 2, \\uD800: 1 }", "invalid escape \\uD800"),
+    ] {
+        deserializes_with_error(input, S { a: 0, b: 0 }, error);
+        deserializes_with_error(input, serde_json::json!({}), error);
+    }
 }
 
 #[test]
@@ -487,15 +1461,1668 @@ fn deserialize_error_messages() {
     }
     deserializes_with_error("'B'", E::A, "unknown variant `B`, expected `A`");
 
-    deserializes_with_error("0xffffffffff", 42, "error parsing hex");
+    deserializes_with_error(
+        "0xffffffffff",
+        42,
+        "expected an i32, found 0xffffffffff at line 1 column 1",
+    );
 
-    let mut over_i64 = i64::max_value().to_string();
-    over_i64.push_str("0");
+    let mut over_i64 = i64::MAX.to_string();
+    over_i64.push('0');
     deserializes_with_error(
         over_i64.as_str(),
-        serde_json::json!(42),
-        "error parsing integer",
+        42u64,
+        "expected a u64, found 92233720368547758070 at line 1 column 1",
+    );
+
+    deserializes_with_error(
+        "1e309",
+        42,
+        "expected an i32, found 1e309 at line 1 column 1",
+    );
+    deserializes_with_error("1e309", 42f64, "error parsing number: too large");
+    deserializes_with_error("1e39", 42f32, "error parsing number: too large");
+
+    assert_eq!(
+        json5::Error::Unexpected(json5::Rule::array).to_string(),
+        "unexpected array in the parse tree"
+    );
+}
+
+#[test]
+fn deserializes_edge_cases_without_panicking() {
+    for input in &[
+        "'\\b\\f\\n\\r\\t\\v\\0\\q\\'\\\"\\\\\\é'",
+        "'\\x41\\u0042\\\n'",
+        "{ \\u0061: [true, false, null, +1, -Infinity, NaN, .5, 0x0] }",
+        "[{}, [], '', \"\", {a:{b:{c:[[[]]]}}}]",
+        "'\\uD800'",
+        "[1,,]",
+        "{ 'a': }",
+        "tru",
+    ] {
+        let _ = json5::from_str::<json5::Value>(input);
+        let _ = json5::from_str::<serde_json::Value>(input);
+        let _ = json5::from_str::<String>(input);
+    }
+}
+
+#[test]
+fn deserializes_twice_with_error() {
+    let mut deserializer = json5::Deserializer::from_str("[1, 2]").unwrap();
+    assert_eq!(
+        <Vec<u8> as de::Deserialize>::deserialize(&mut deserializer),
+        Ok(vec![1, 2])
+    );
+    for result in [
+        <Vec<u8> as de::Deserialize>::deserialize(&mut deserializer).map(drop),
+        <Option<u8> as de::Deserialize>::deserialize(&mut deserializer).map(drop),
+        <String as de::Deserialize>::deserialize(&mut deserializer).map(drop),
+        <(u8, u8) as de::Deserialize>::deserialize(&mut deserializer).map(drop),
+    ] {
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "the value has already been deserialized"
+        );
+    }
+}
+
+#[test]
+fn deserializes_with_custom_error() {
+    #[derive(Debug, PartialEq)]
+    struct Even(u32);
+    impl<'de> de::Deserialize<'de> for Even {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: de::Deserializer<'de>,
+        {
+            let n = u32::deserialize(deserializer)?;
+            if n % 2 == 0 {
+                Ok(Even(n))
+            } else {
+                Err(de::Error::custom(format!("{} is odd", n)))
+            }
+        }
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S {
+        counts: Vec<Even>,
+    }
+
+    deserializes_to(
+        "{ counts: [2, 4] }",
+        S {
+            counts: vec![Even(2), Even(4)],
+        },
+    );
+    assert_eq!(
+        json5::from_str::<S>("{ counts: [2, 3] }"),
+        Err(json5::Error::Message("3 is odd".to_owned()))
+    );
+    deserializes_with_error("7", Even(0), "7 is odd");
+    deserializes_with_error(
+        "{ counts: 1 }",
+        S { counts: vec![] },
+        "invalid type: integer `1`, expected a sequence",
+    );
+}
+
+#[test]
+fn deserializes_with_trailing_characters() {
+    let trailing = |line, column| json5::Error::TrailingCharacters {
+        location: json5::Location { line, column },
+    };
+    assert_eq!(json5::from_str::<i32>("5 6").unwrap_err(), trailing(1, 3));
+    assert_eq!(
+        json5::from_str::<i32>("5 garbage").unwrap_err(),
+        trailing(1, 3)
+    );
+    assert_eq!(
+        json5::from_str::<Vec<i32>>("[1]]").unwrap_err(),
+        trailing(1, 4)
+    );
+    assert_eq!(
+        json5::from_str::<HashMap<String, i32>>("{ a: 1 } // done\n /* really */ { b: 2 }")
+            .unwrap_err(),
+        trailing(2, 15)
+    );
+    assert_eq!(
+        json5::from_str::<i32>("1 /* unterminated").unwrap_err(),
+        trailing(1, 3)
+    );
+    deserializes_with_error(
+        "'a'\n,",
+        String::new(),
+        "trailing characters after the JSON5 value at line 2 column 1",
+    );
+    assert_eq!(
+        json5::from_str::<i32>("5 6").unwrap_err().location(),
+        Some(json5::Location { line: 1, column: 3 })
+    );
+
+    deserializes_to("5 // fine\n /* also fine */ ", 5);
+    assert!(matches!(
+        json5::from_str::<Vec<i32>>("[1 2]"),
+        Err(json5::Error::Parse { .. })
+    ));
+}
+
+#[test]
+fn deserializes_bare_identifier_with_error() {
+    let bare = |identifier: &str, line, column| json5::Error::BareIdentifierNotAllowed {
+        identifier: identifier.to_owned(),
+        location: json5::Location { line, column },
+    };
+    assert_eq!(
+        json5::from_str::<String>("hello").unwrap_err(),
+        bare("hello", 1, 1)
+    );
+    assert_eq!(
+        json5::from_str::<json5::Value>("// config\n  hello world").unwrap_err(),
+        bare("hello", 2, 3)
+    );
+    assert_eq!(
+        json5::from_str::<Option<bool>>("nullable").unwrap_err(),
+        bare("nullable", 1, 1)
+    );
+    assert_eq!(
+        json5::from_str::<String>("\\u0061b").unwrap_err(),
+        bare("\\u0061b", 1, 1)
+    );
+    deserializes_with_error(
+        " debug",
+        String::new(),
+        "unquoted string `debug` at line 1 column 2, only object keys can be unquoted",
+    );
+
+    // Keywords are still values, and a bare word anywhere but the top level is a syntax error.
+    deserializes_to("Infinity", f64::INFINITY);
+    assert!(matches!(
+        json5::from_str::<bool>("true false"),
+        Err(json5::Error::TrailingCharacters { .. })
+    ));
+    assert!(matches!(
+        json5::from_str::<Vec<String>>("[hello]"),
+        Err(json5::Error::Parse { .. })
+    ));
+    assert!(matches!(
+        json5::from_str::<HashMap<String, String>>("{ a: hello }"),
+        Err(json5::Error::Parse { .. })
+    ));
+}
+
+#[test]
+fn deserializes_object_keys() {
+    use json5::{Location, Span, ValueKind};
+
+    let keys = |input| json5::from_str::<HashMap<String, i32>>(input);
+    let expected = HashMap::from([
+        ("1".to_owned(), 0),
+        ("a".to_owned(), 1),
+        ("$_b".to_owned(), 2),
+    ]);
+    assert_eq!(keys("{ '1': 0, a: 1, \"$_b\": 2 }"), Ok(expected.clone()));
+    assert_eq!(keys("{ \"1\": 0, 'a': 1, $_b: 2 }"), Ok(expected));
+    // Keywords are identifiers, so they can be keys.
+    assert_eq!(
+        keys("{ null: 0, true: 1, Infinity: 2, NaN: 3 }")
+            .unwrap()
+            .len(),
+        4
+    );
+    deserializes_to("{ '1': 'a' }", HashMap::from([(1, "a".to_owned())]));
+
+    let invalid = |found, column, start, end| json5::Error::InvalidKey {
+        found,
+        location: Location { line: 1, column },
+        span: Span { start, end },
+    };
+    assert_eq!(keys("{ 1: 0 }"), Err(invalid(ValueKind::Number, 3, 2, 3)));
+    assert_eq!(
+        keys("{ a: 1, -1.5e3: 0 }"),
+        Err(invalid(ValueKind::Number, 9, 8, 14))
+    );
+    assert_eq!(
+        keys("{ 0x10: 0 }"),
+        Err(invalid(ValueKind::Number, 3, 2, 6))
+    );
+    assert_eq!(keys("{ [1]: 0 }"), Err(invalid(ValueKind::Array, 3, 2, 5)));
+    assert_eq!(
+        json5::from_str::<Vec<HashMap<String, i32>>>("[{},\n{ 2: 0 }]").unwrap_err(),
+        json5::Error::InvalidKey {
+            found: ValueKind::Number,
+            location: Location { line: 2, column: 3 },
+            span: Span { start: 7, end: 8 },
+        }
+    );
+    deserializes_with_error(
+        "{ 1: 'a' }",
+        HashMap::<i32, String>::new(),
+        "expected an identifier or a string as an object key, found a number at line 1 column 3",
+    );
+
+    // Anything else that isn't a key is still a syntax error.
+    assert!(matches!(
+        keys("{ a b: 0 }"),
+        Err(json5::Error::Parse { .. })
+    ));
+    assert!(matches!(keys("{ : 0 }"), Err(json5::Error::Parse { .. })));
+    assert!(matches!(keys("{ 'a' 0 }"), Err(json5::Error::Parse { .. })));
+}
+
+#[test]
+fn deserializes_empty_document() {
+    for input in &[
+        "",
+        "  \n\t",
+        "// nothing here\n",
+        "/* a */ /* b */",
+        "\u{FEFF}",
+    ] {
+        assert_eq!(
+            json5::from_str::<i32>(input),
+            Err(json5::Error::EmptyDocument)
+        );
+        assert_eq!(
+            json5::from_str::<Option<i32>>(input),
+            Err(json5::Error::EmptyDocument)
+        );
+    }
+    deserializes_with_error("", 0, "expected a JSON5 value, found nothing");
+    assert_eq!(
+        json5::from_slice::<json5::Value>(b" "),
+        Err(json5::Error::EmptyDocument)
+    );
+    assert_eq!(
+        json5::from_str::<i32>("// a\n,").unwrap_err().location(),
+        Some(json5::Location { line: 2, column: 1 })
+    );
+}
+
+#[test]
+fn deserialize_error_locations() {
+    let err = json5::from_str::<serde_json::Value>("{ a: 1,\n b: }").unwrap_err();
+    assert_eq!(err.location(), Some(json5::Location { line: 2, column: 5 }));
+    assert_eq!(err.line(), Some(2));
+    assert_eq!(err.column(), Some(5));
+
+    let err = json5::from_str::<u8>("256").unwrap_err();
+    assert_eq!(err.location(), Some(json5::Location { line: 1, column: 1 }));
+    assert_eq!(err.span(), Some(json5::Span { start: 0, end: 3 }));
+
+    let err = json5::from_str::<char>("'ab'").unwrap_err();
+    assert_eq!(err.location(), None);
+    assert_eq!(err.span(), None);
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    enum E {
+        A {},
+        B(),
+    }
+
+    let err = json5::from_str::<Vec<E>>("[\n  'A',\n  { B: {} },\n]").unwrap_err();
+    assert_eq!(err.location(), Some(json5::Location { line: 2, column: 3 }));
+    assert_eq!(err.span(), Some(json5::Span { start: 4, end: 7 }));
+
+    let err = json5::from_str::<Vec<E>>("[{ B: [] }, { B: {} }]").unwrap_err();
+    assert_eq!(
+        err.location(),
+        Some(json5::Location {
+            line: 1,
+            column: 18
+        })
+    );
+    assert_eq!(err.span(), Some(json5::Span { start: 17, end: 19 }));
+
+    let err = json5::from_str::<Vec<E>>("[{ B: [] },\n 42]").unwrap_err();
+    assert_eq!(err.location(), Some(json5::Location { line: 2, column: 2 }));
+    assert_eq!(err.span(), Some(json5::Span { start: 13, end: 15 }));
+}
+
+#[test]
+fn deserializes_from_slice() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S {
+        a: String,
+    }
+
+    assert_eq!(
+        json5::from_slice(b"{ a: 'hello' }"),
+        Ok(S {
+            a: "hello".to_owned()
+        })
+    );
+
+    let err = json5::from_slice::<S>(b"{ a: '\xff' }").unwrap_err();
+    match err {
+        json5::Error::InvalidUtf8(ref utf8) => assert_eq!(utf8.valid_up_to(), 6),
+        _ => panic!("expected an InvalidUtf8 error, got {:?}", err),
+    }
+    assert!(std::error::Error::source(&err).is_some());
+}
+
+// JSON5 counts a byte order mark as whitespace, so one at the start of a file saved by a Windows
+// editor is skipped like any other.
+#[test]
+fn deserializes_with_byte_order_mark() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Config {
+        name: String,
+    }
+
+    let config = || Config {
+        name: "\u{FEFF}x".to_owned(),
+    };
+    assert_eq!(
+        json5::from_str("\u{FEFF}{ name: '\u{FEFF}x' }"),
+        Ok(config())
+    );
+    assert_eq!(
+        json5::from_slice(b"\xEF\xBB\xBF{ name: '\xEF\xBB\xBFx' }"),
+        Ok(config())
+    );
+    assert_eq!(
+        json5::from_str::<json5::Value>("\u{FEFF}// config\n[1]"),
+        Ok(json5::Value::Array(vec![json5::Value::from(1)]))
+    );
+    assert_eq!(
+        json5::from_str::<i32>("\u{FEFF}1 2"),
+        Err(json5::Error::TrailingCharacters {
+            location: json5::Location { line: 1, column: 4 },
+        })
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn deserializes_from_reader() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S {
+        a: Vec<i32>,
+    }
+
+    assert_eq!(
+        json5::from_reader(&b"{ a: [1, 2] }"[..]),
+        Ok(S { a: vec![1, 2] })
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn deserializes_from_reader_with_io_error() {
+    struct FailingReader;
+
+    impl io::Read for FailingReader {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("connection reset"))
+        }
+    }
+
+    assert_eq!(
+        json5::from_reader::<_, i32>(FailingReader),
+        Err(json5::Error::Io("connection reset".to_owned()))
     );
+}
+
+#[test]
+fn deserializes_flattened_struct() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Inner {
+        b: i32,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S {
+        a: i32,
+        #[serde(flatten)]
+        inner: Inner,
+        #[serde(flatten)]
+        extra: HashMap<String, json5::Value>,
+    }
 
-    deserializes_with_error("1e309", 42, "error parsing number: too large");
+    let mut extra = HashMap::new();
+    extra.insert("c".to_owned(), json5::Value::String("x".to_owned()));
+    extra.insert(
+        "d".to_owned(),
+        json5::Value::Array(vec![json5::Value::Number(1.into())]),
+    );
+    deserializes_to(
+        "{ c: 'x', a: 1, d: [1], b: 2 }",
+        S {
+            a: 1,
+            inner: Inner { b: 2 },
+            extra,
+        },
+    );
+}
+
+#[test]
+fn deserializes_stream() {
+    let input = "1 /* two */ 2\n// three\n3\n";
+    let mut stream = json5::StreamDeserializer::<i32>::new(input);
+    assert_eq!(stream.next(), Some(Ok(1)));
+    assert_eq!(stream.byte_offset(), 1);
+    assert_eq!(stream.next(), Some(Ok(2)));
+    assert_eq!(stream.next(), Some(Ok(3)));
+    assert_eq!(stream.next(), None);
+    assert_eq!(stream.byte_offset(), input.len());
+
+    let values = json5::StreamDeserializer::<json5::Value>::new("{a:1}[2]'three'")
+        .collect::<json5::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(values.len(), 3);
+
+    assert_eq!(json5::StreamDeserializer::<i32>::new("").next(), None);
+    assert_eq!(
+        json5::StreamDeserializer::<i32>::new(" // c\n").next(),
+        None
+    );
+}
+
+#[test]
+fn deserializes_stream_with_error() {
+    let mut stream = json5::StreamDeserializer::<i32>::new("1\n'two'\n3\n{ a: }\n5");
+    assert_eq!(stream.next(), Some(Ok(1)));
+    assert!(stream.next().unwrap().is_err());
+    assert_eq!(stream.next(), Some(Ok(3)));
+    let err = stream.next().unwrap().unwrap_err();
+    assert_eq!(err.location(), Some(json5::Location { line: 4, column: 6 }));
+    assert_eq!(stream.next(), None);
+}
+
+#[test]
+fn deserializes_stream_with_options() {
+    let input = "{ a: 1 }\n{ a: 0x1_0, a: 2 }\n[[[0]]]\n";
+    let mut stream = json5::StreamDeserializer::<json5::Value>::new(input);
+    assert!(stream.next().unwrap().is_ok());
+    assert!(matches!(
+        stream.next(),
+        Some(Err(json5::Error::DisabledExtension { .. }))
+    ));
+
+    let options = json5::DeserializeOptions::new()
+        .allow_underscores_in_numbers(true)
+        .deny_duplicate_keys(true);
+    let mut stream = options.stream::<json5::Value>(input);
+    assert!(stream.next().unwrap().is_ok());
+    assert_eq!(
+        stream.next(),
+        Some(Err(json5::Error::DuplicateKey("a".to_owned())))
+    );
+    assert!(stream.next().unwrap().is_ok());
+    assert_eq!(stream.next(), None);
+
+    // The limits are checked over the whole input, before any document is read.
+    let mut stream = options
+        .clone()
+        .recursion_limit(2)
+        .stream::<json5::Value>(input);
+    assert!(matches!(
+        stream.next(),
+        Some(Err(json5::Error::RecursionLimitExceeded {
+            limit: 2,
+            location: json5::Location { line: 3, column: 3 },
+        }))
+    ));
+    assert_eq!(stream.next(), None);
+    let mut stream = options.max_input_size(16).stream::<json5::Value>(input);
+    assert_eq!(
+        stream.next(),
+        Some(Err(json5::Error::InputTooLarge { limit: 16 }))
+    );
+    assert_eq!(stream.next(), None);
+}
+
+#[test]
+fn deserializes_duplicate_keys() {
+    use serde::Deserialize;
+
+    fn deny<'a, T: Deserialize<'a>>(s: &'a str) -> json5::Result<T> {
+        let deserializer = json5::Deserializer::from_str(s)?;
+        T::deserialize(&mut deserializer.deny_duplicate_keys(true))
+    }
+
+    let mut m = HashMap::new();
+    m.insert("a".to_owned(), 2);
+    deserializes_to("{ a: 1, a: 2 }", m);
+    assert_eq!(
+        deny::<HashMap<String, i32>>("{ a: 1, b: 2 }")
+            .ok()
+            .map(|m| m.len()),
+        Some(2)
+    );
+
+    let duplicate = json5::Error::DuplicateKey("a".to_owned());
+    assert_eq!(
+        deny::<HashMap<String, i32>>("{ a: 1, a: 2 }").unwrap_err(),
+        duplicate
+    );
+    assert_eq!(
+        deny::<HashMap<String, i32>>("{ a: 1, '\\u0061': 2 }").unwrap_err(),
+        duplicate
+    );
+    assert_eq!(
+        deny::<Vec<HashMap<String, i32>>>("[{}, { a: 1, \"a\": 2 }]").unwrap_err(),
+        duplicate
+    );
+    assert_eq!(
+        deny::<json5::Value>("{ a: { a: 1, a: 2 } }").unwrap_err(),
+        duplicate
+    );
+    assert_eq!(
+        json5::Error::DuplicateKey("a".to_owned()).to_string(),
+        "duplicate key \"a\""
+    );
+}
+
+#[test]
+fn deserializes_untagged_enum() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    #[serde(untagged)]
+    enum E {
+        Int(i64),
+        Float(f64),
+        Text(String),
+        List(Vec<E>),
+        Point { x: i32, y: i32 },
+        Nothing(()),
+    }
+
+    deserializes_to("42", E::Int(42));
+    deserializes_to("0x2A", E::Int(42));
+    deserializes_to("4.2", E::Float(4.2));
+    deserializes_to("'hi'", E::Text("hi".to_owned()));
+    deserializes_to("null", E::Nothing(()));
+    deserializes_to(
+        "[1, 'a', { y: 2, x: 1, }]",
+        E::List(vec![
+            E::Int(1),
+            E::Text("a".to_owned()),
+            E::Point { x: 1, y: 2 },
+        ]),
+    );
+    deserializes_with_error(
+        "true",
+        E::Nothing(()),
+        "data did not match any variant of untagged enum E",
+    );
+}
+
+#[test]
+fn deserializes_internally_tagged_enum() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    #[serde(tag = "type")]
+    enum Plugin {
+        Http { port: u16 },
+        File { path: String },
+        Noop,
+    }
+
+    deserializes_to("{ type: 'Http', port: 8080 }", Plugin::Http { port: 8080 });
+    deserializes_to(
+        "{ path: '/tmp', \"type\": 'File' }",
+        Plugin::File {
+            path: "/tmp".to_owned(),
+        },
+    );
+    deserializes_to("{ type: 'Noop' }", Plugin::Noop);
+    deserializes_to(
+        "[{ type: 'Noop' }, { type: 'Http', port: 1 }]",
+        vec![Plugin::Noop, Plugin::Http { port: 1 }],
+    );
+    deserializes_with_error("{ port: 8080 }", Plugin::Noop, "missing field `type`");
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    #[serde(tag = "t", content = "c")]
+    enum Adjacent {
+        A(i32),
+        B { b: bool },
+    }
+
+    deserializes_to("{ t: 'A', c: 1 }", Adjacent::A(1));
+    deserializes_to("{ c: { b: true }, t: 'B' }", Adjacent::B { b: true });
+}
+
+#[test]
+fn deserializes_adjacently_tagged_enum() {
+    use serde_derive::Serialize;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    #[serde(tag = "t", content = "c")]
+    enum Message {
+        Ping,
+        Text(String),
+        Move(i32, i32),
+        Resize { width: u32, height: u32 },
+    }
+
+    let all = vec![
+        Message::Ping,
+        Message::Text("hi".to_owned()),
+        Message::Move(-1, 2),
+        Message::Resize {
+            width: 3,
+            height: 4,
+        },
+    ];
+    deserializes_to("{ t: 'Ping' }", Message::Ping);
+    deserializes_to("{ c: 'hi', t: 'Text' }", Message::Text("hi".to_owned()));
+    deserializes_to("{ t: 'Move', c: [-1, 2] }", Message::Move(-1, 2));
+    deserializes_to(
+        "{ t: 'Resize', /* size */ c: { height: 4, width: 3, }, }",
+        Message::Resize {
+            width: 3,
+            height: 4,
+        },
+    );
+    deserializes_to(
+        "[{ t: 'Ping' }, { t: 'Text', c: 'hi' }, { t: 'Move', c: [-1, 2] }, { 'c': { width: 3, height: 4 }, \"t\": 'Resize' }]",
+        all,
+    );
+
+    for message in &[Message::Ping, Message::Move(5, 6)] {
+        let s = json5::to_string(message).unwrap();
+        assert_eq!(json5::from_str::<Message>(&s).as_ref(), Ok(message));
+        let value = json5::from_str::<json5::Value>(&s).unwrap();
+        assert_eq!(json5::from_value::<Message>(value).as_ref(), Ok(message));
+    }
+
+    deserializes_with_error(
+        "{ t: 'Jump', c: 1 }",
+        Message::Ping,
+        "unknown variant `Jump`, expected one of `Ping`, `Text`, `Move`, `Resize`",
+    );
+    deserializes_with_error("{ c: 'hi' }", Message::Ping, "missing field `t`");
+    deserializes_with_error("{ t: 'Text' }", Message::Ping, "missing field `c`");
+    assert!(json5::from_str::<Message>("{ t: 'Move', c: [1] }").is_err());
+}
+
+#[test]
+fn deserializes_octal_and_binary() {
+    use serde::Deserialize;
+
+    fn allow<'a, T: Deserialize<'a>>(s: &'a str) -> json5::Result<T> {
+        let deserializer = json5::Deserializer::from_str(s)?;
+        T::deserialize(&mut deserializer.allow_octal_and_binary(true))
+    }
+
+    assert_eq!(allow("0o777"), Ok(0o777u32));
+    assert_eq!(allow("0O17"), Ok(15i8));
+    assert_eq!(allow("-0o10"), Ok(-8i64));
+    assert_eq!(allow("0b1010"), Ok(10u8));
+    assert_eq!(allow("+0B11"), Ok(3i32));
+    assert_eq!(allow("-0b1"), Ok(-1.));
+    assert_eq!(allow("[0b1, 0o2, 0x3, 4]"), Ok(vec![1, 2, 3, 4]));
+    assert_eq!(allow("0b11"), Ok(serde_json::json!(3)));
+    assert_eq!(
+        allow::<u8>("0b100000000").unwrap_err().to_string(),
+        "expected a u8, found 0b100000000 at line 1 column 1"
+    );
+    assert_eq!(
+        allow::<i8>("0o200").unwrap_err().to_string(),
+        "expected an i8, found 0o200 at line 1 column 1"
+    );
+
+    deserializes_with_error(
+        "0o777",
+        0,
+        "octal literals are not JSON5 at line 1 column 1, enable them with `allow_octal_and_binary`",
+    );
+    deserializes_with_error(
+        "[1, 0b1]",
+        serde_json::json!(null),
+        "binary literals are not JSON5 at line 1 column 5, enable them with `allow_octal_and_binary`",
+    );
+    deserializes_with_error(
+        "{ a: 0b1 }",
+        HashMap::<String, f64>::new(),
+        "binary literals are not JSON5 at line 1 column 6, enable them with `allow_octal_and_binary`",
+    );
+    assert!(json5::from_str::<i32>("0o8").is_err());
+    assert!(json5::from_str::<i32>("0b2").is_err());
+
+    // Whether a document is accepted doesn't depend on which parts of it are deserialized.
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S {
+        a: i32,
+    }
+    let disabled = |kind, column| {
+        format!(
+            "{} are not JSON5 at line 1 column {}, enable them with `allow_octal_and_binary`",
+            kind, column
+        )
+    };
+    for (input, error) in &[
+        ("{ a: 1, x: 0o7 }", disabled("octal literals", 12)),
+        (
+            "{ a: 1, x: [{ y: -0b1 }] }",
+            disabled("binary literals", 18),
+        ),
+        ("{ x: 0o7, a: 1 }", disabled("octal literals", 6)),
+    ] {
+        deserializes_with_error(input, S { a: 0 }, error);
+        assert!(json5::from_str::<json5::Value>(input).is_err());
+    }
+    assert_eq!(
+        json5::from_str::<S>("{ a: 1, x: 0o7 }"),
+        Err(json5::Error::DisabledExtension {
+            kind: json5::WarningKind::OctalLiteral,
+            location: json5::Location {
+                line: 1,
+                column: 12
+            },
+            span: json5::Span { start: 11, end: 14 },
+        })
+    );
+    assert!(json5::from_str_at::<i32>("{ a: 1, x: 0o7 }", "a").is_err());
+    assert!(json5::from_str::<(i32, ())>("[1, null]").is_ok());
+    assert_eq!(allow("{ a: 1, x: 0o7 }"), Ok(S { a: 1 }));
+}
+
+#[test]
+fn deserializes_underscores_in_numbers() {
+    use serde::Deserialize;
+
+    fn allow<'a, T: Deserialize<'a>>(s: &'a str) -> json5::Result<T> {
+        let deserializer = json5::Deserializer::from_str(s)?;
+        T::deserialize(&mut deserializer.allow_underscores_in_numbers(true))
+    }
+
+    assert_eq!(allow("1_000_000"), Ok(1_000_000u32));
+    assert_eq!(allow("-9_223_372_036_854_775_808"), Ok(i64::MIN));
+    assert_eq!(allow("+1_0"), Ok(10u8));
+    assert_eq!(allow("0xFF_FF"), Ok(0xFFFFu16));
+    assert_eq!(allow("1_000.000_1"), Ok(1000.0001f64));
+    assert_eq!(allow(".5_5e1_0"), Ok(0.55e10f32));
+    assert_eq!(allow("[1_0, 2_0.5]"), Ok(serde_json::json!([10, 20.5])));
+    assert_eq!(
+        allow("{ a: 1_2 }"),
+        Ok(HashMap::from([("a".to_owned(), 12)]))
+    );
+    assert_eq!(
+        json5::DeserializeOptions::new()
+            .allow_underscores_in_numbers(true)
+            .allow_octal_and_binary(true)
+            .from_str("[0b1_0, 0o1_0]"),
+        Ok(vec![2, 8])
+    );
+
+    for input in &[
+        "1_", "1__0", "0x_FF", "1_.5", "1._5", "1e_5", "1_e5", "0xF_",
+    ] {
+        assert_eq!(
+            allow::<f64>(input).unwrap_err().to_string(),
+            "underscores in numbers are only allowed between two digits at line 1 column 1",
+            "{}",
+            input
+        );
+    }
+    let err = allow::<HashMap<String, f64>>("{ a:\n  -1__0 }").unwrap_err();
+    assert_eq!(
+        err,
+        json5::Error::MisplacedUnderscore {
+            location: json5::Location { line: 2, column: 3 },
+            span: json5::Span { start: 7, end: 12 },
+        }
+    );
+    assert!(allow::<f64>("_1").is_err());
+    assert!(allow::<f64>("0_1").is_err());
+
+    deserializes_with_error(
+        "1_000",
+        0,
+        "underscores in numbers are not JSON5 at line 1 column 1, enable them with `allow_underscores_in_numbers`",
+    );
+    deserializes_with_error(
+        "{ a: [1.0_1] }",
+        serde_json::json!(null),
+        "underscores in numbers are not JSON5 at line 1 column 7, enable them with `allow_underscores_in_numbers`",
+    );
+    assert!(json5::validate("1_000").is_err());
+
+    // Including in a field that isn't deserialized.
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S {
+        a: i32,
+    }
+    deserializes_with_error(
+        "{ a: 1, x: 1_0 }",
+        S { a: 0 },
+        "underscores in numbers are not JSON5 at line 1 column 12, enable them with `allow_underscores_in_numbers`",
+    );
+    deserializes_with_error(
+        "{ a: 1, x: { y: [1.0_1] } }",
+        S { a: 0 },
+        "underscores in numbers are not JSON5 at line 1 column 18, enable them with `allow_underscores_in_numbers`",
+    );
+    assert_eq!(allow("{ a: 1, x: 1_0 }"), Ok(S { a: 1 }));
+    assert_eq!(
+        allow::<S>("{ a: 1, x: 1__0 }").unwrap_err().to_string(),
+        "underscores in numbers are only allowed between two digits at line 1 column 12"
+    );
+}
+
+#[test]
+fn deserializes_with_warnings() {
+    use json5::{Location, Span, Warning, WarningKind};
+
+    let options = json5::DeserializeOptions::new()
+        .allow_octal_and_binary(true)
+        .allow_underscores_in_numbers(true);
+    let warning = |kind, column, start, end| Warning {
+        kind,
+        location: Location { line: 2, column },
+        span: Span { start, end },
+    };
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S {
+        mode: u32,
+        flags: Vec<u8>,
+    }
+    let (s, warnings) = options
+        .from_str_with_warnings::<S>("{\n mode: 0o7_55, flags: [0b1, 2, 0x1_0], skipped: 1_0 }")
+        .unwrap();
+    assert_eq!(
+        s,
+        S {
+            mode: 0o755,
+            flags: vec![1, 2, 16]
+        }
+    );
+    assert_eq!(
+        warnings,
+        vec![
+            warning(WarningKind::OctalLiteral, 8, 9, 15),
+            warning(WarningKind::UnderscoresInNumber, 8, 9, 15),
+            warning(WarningKind::BinaryLiteral, 24, 25, 28),
+            warning(WarningKind::UnderscoresInNumber, 32, 33, 38),
+            warning(WarningKind::UnderscoresInNumber, 49, 50, 53),
+        ]
+    );
+    assert_eq!(
+        warnings[2].to_string(),
+        "binary literals are not JSON5, used at line 2 column 24"
+    );
+
+    assert_eq!(
+        options.from_str_with_warnings::<u8>("0b1"),
+        Ok((
+            1,
+            vec![Warning {
+                kind: WarningKind::BinaryLiteral,
+                location: Location { line: 1, column: 1 },
+                span: Span { start: 0, end: 3 },
+            }]
+        ))
+    );
+    assert_eq!(
+        options.from_str_with_warnings::<Vec<f64>>("[1, 0x10, 1e3, Infinity]"),
+        Ok((vec![1., 16., 1000., f64::INFINITY], vec![]))
+    );
+
+    // Without the options, extensions are still an error, even in a skipped value.
+    let strict = json5::DeserializeOptions::new();
+    assert!(strict.from_str_with_warnings::<u32>("0o755").is_err());
+    assert!(strict
+        .from_str_with_warnings::<S>("{ mode: 1, flags: [], x: 1_000 }")
+        .is_err());
+
+    // The free function allows them all, and warns about each.
+    assert_eq!(
+        json5::from_str_with_warnings::<u32>("0o755"),
+        Ok((
+            0o755,
+            vec![Warning {
+                kind: WarningKind::OctalLiteral,
+                location: Location { line: 1, column: 1 },
+                span: Span { start: 0, end: 5 },
+            }]
+        ))
+    );
+    let (s, warnings) = json5::from_str_with_warnings::<S>(
+        "{\n mode: 0o7_55, flags: [0b1, 2, 0x1_0], skipped: 1_0 }",
+    )
+    .unwrap();
+    assert_eq!(s.mode, 0o755);
+    assert_eq!(warnings.len(), 5);
+    assert_eq!(
+        json5::from_str_with_warnings::<Vec<u8>>("[1, 2]"),
+        Ok((vec![1, 2], vec![]))
+    );
+    // Underscores still have to be between digits.
+    assert!(json5::from_str_with_warnings::<u32>("1__0").is_err());
+}
+
+#[test]
+fn deserializes_null_as_empty_collection() {
+    use std::collections::BTreeSet;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S {
+        list: Vec<i32>,
+        map: HashMap<String, i32>,
+        set: BTreeSet<String>,
+        optional: Option<Vec<i32>>,
+        nested: Vec<Vec<i32>>,
+    }
+
+    let lenient = json5::DeserializeOptions::new().null_as_empty_collection(true);
+    let input = "{ list: null, map: null, set: null, optional: null, nested: [null, [1]] }";
+    assert_eq!(
+        lenient.from_str::<S>(input),
+        Ok(S {
+            list: vec![],
+            map: HashMap::new(),
+            set: BTreeSet::new(),
+            optional: None,
+            nested: vec![vec![], vec![1]],
+        })
+    );
+    assert_eq!(lenient.from_str::<Vec<i32>>("null"), Ok(vec![]));
+    assert_eq!(
+        lenient.from_str::<HashMap<String, i32>>("null"),
+        Ok(HashMap::new())
+    );
+    assert_eq!(lenient.from_str::<Vec<i32>>("[1, 2]"), Ok(vec![1, 2]));
+    assert_eq!(
+        lenient.from_str::<HashMap<String, i32>>("{ a: 1 }"),
+        Ok(HashMap::from([("a".to_owned(), 1)]))
+    );
+    // Only collections, not other things `null` isn't.
+    assert!(lenient.from_str::<(i32, i32)>("null").is_err());
+    assert!(lenient.from_str::<String>("null").is_err());
+    assert!(lenient.from_str::<S>("null").is_err());
+
+    deserializes_with_error(
+        "null",
+        Vec::<i32>::new(),
+        "invalid type: unit value, expected a sequence",
+    );
+    deserializes_with_error(
+        "{ a: null }",
+        HashMap::<String, HashMap<String, i32>>::new(),
+        "invalid type: unit value, expected a map",
+    );
+}
+
+#[test]
+fn deserializes_with_unknown_fields_denied() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Inner {
+        #[serde(rename = "x-y")]
+        x: i32,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S {
+        a: i32,
+        #[serde(default, alias = "bee")]
+        b: Option<Inner>,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    enum E {
+        V { c: bool },
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    #[serde(deny_unknown_fields)]
+    struct Strict {
+        a: i32,
+    }
+
+    let options = json5::DeserializeOptions::new().deny_unknown_fields(true);
+    assert_eq!(
+        options.from_str("{ a: 1, bee: { 'x-y': 2 } }"),
+        Ok(S {
+            a: 1,
+            b: Some(Inner { x: 2 })
+        })
+    );
+    assert_eq!(
+        options.from_str::<S>("{ a: 1, c: 2 }"),
+        Err(json5::Error::UnknownField {
+            field: "c".to_owned(),
+            expected: &["a", "b", "bee"],
+        })
+    );
+    assert_eq!(
+        options
+            .from_str::<S>("{ a: 1, b: { 'x-y': 2, x: 3 } }")
+            .unwrap_err()
+            .to_string(),
+        "unknown field `x`, expected `x-y`"
+    );
+    assert_eq!(
+        options
+            .from_str::<E>("{ V: { c: true, '\\u0064': false } }")
+            .unwrap_err()
+            .to_string(),
+        "unknown field `d`, expected `c`"
+    );
+    assert_eq!(
+        options.from_str::<HashMap<String, i32>>("{ c: 1 }"),
+        Ok(HashMap::from([("c".to_owned(), 1)]))
+    );
+
+    deserializes_to("{ a: 1, c: 2 }", S { a: 1, b: None });
+    assert_eq!(
+        json5::from_str::<Strict>("{ a: 1, b: 2 }"),
+        Err(json5::Error::UnknownField {
+            field: "b".to_owned(),
+            expected: &["a"],
+        })
+    );
+}
+
+#[test]
+fn deserializes_borrowed_str() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S<'a> {
+        name: &'a str,
+    }
+
+    let input = "{ name: \"abc\" }";
+    let s: S = json5::from_str(input).unwrap();
+    assert_eq!(s, S { name: "abc" });
+    assert!(input.as_bytes().as_ptr_range().contains(&s.name.as_ptr()));
+
+    deserializes_to("{ name: 'it\"s' }", S { name: "it\"s" });
+    deserializes_to("{ 'name': '' }", S { name: "" });
+    deserializes_with_error(
+        "{ name: 'a\\nb' }",
+        S { name: "" },
+        "invalid type: string \"a\\nb\", expected a borrowed string",
+    );
+    deserializes_with_error(
+        "{ name: 'a\\\nb' }",
+        S { name: "" },
+        "invalid type: string \"ab\", expected a borrowed string",
+    );
+
+    let mut map = HashMap::new();
+    map.insert("key", "value");
+    deserializes_to("{ key: 'value' }", map);
+
+    // A `Cow` takes the borrowed path where it can, but still copes with escapes.
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct C<'a> {
+        #[serde(borrow)]
+        a: std::borrow::Cow<'a, str>,
+        #[serde(borrow)]
+        b: std::borrow::Cow<'a, str>,
+    }
+    let c: C = json5::from_str("{ a: 'x', b: '\\u0079' }").unwrap();
+    assert!(matches!(c.a, std::borrow::Cow::Borrowed("x")));
+    assert_eq!(c.b, "y");
+}
+
+#[test]
+fn deserializes_borrowed_keys() {
+    let input = "{ plain: 1, 'single': 2, \"double\": 3, 'it\"s': 4, $_: 5 }";
+    let map: HashMap<&str, u32> = json5::from_str(input).unwrap();
+    assert_eq!(
+        map,
+        HashMap::from([
+            ("plain", 1),
+            ("single", 2),
+            ("double", 3),
+            ("it\"s", 4),
+            ("$_", 5)
+        ])
+    );
+    let input_range = input.as_bytes().as_ptr_range();
+    assert!(map.keys().all(|key| input_range.contains(&key.as_ptr())));
+
+    // Keys with escapes can't be borrowed, whether they are identifiers or strings.
+    deserializes_with_error(
+        "{ \\u0061: 1 }",
+        HashMap::<&str, u32>::new(),
+        "invalid type: string \"a\", expected a borrowed string",
+    );
+    deserializes_with_error(
+        "{ 'a\\tb': 1 }",
+        HashMap::<&str, u32>::new(),
+        "invalid type: string \"a\\tb\", expected a borrowed string",
+    );
+
+    // Struct fields are matched against the borrowed key, including with escapes resolved.
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S<'a> {
+        #[serde(borrow)]
+        name: &'a str,
+        id: u32,
+    }
+    deserializes_to("{ name: 'x', 'id': 1 }", S { name: "x", id: 1 });
+    deserializes_to("{ n\\u0061me: 'x', \"\\x69d\": 1 }", S { name: "x", id: 1 });
+}
+
+#[test]
+fn deserializes_with_options() {
+    let default = json5::DeserializeOptions::new();
+    let strict = json5::DeserializeOptions::new().deny_duplicate_keys(true);
+    let lenient = json5::DeserializeOptions::new().allow_octal_and_binary(true);
+
+    assert_eq!(
+        default
+            .from_str("{ a: 1, a: 2 }")
+            .map(|m: HashMap<String, i32>| m["a"]),
+        Ok(2)
+    );
+    assert_eq!(
+        strict.from_str::<HashMap<String, i32>>("{ a: 1, a: 2 }"),
+        Err(json5::Error::DuplicateKey("a".to_owned()))
+    );
+    assert!(default.from_str::<u32>("0o17").is_err());
+    assert_eq!(lenient.from_str("0o17"), Ok(15));
+    assert_eq!(lenient.from_slice(b"[0b11]"), Ok(vec![3]));
+    #[cfg(feature = "std")]
+    assert_eq!(lenient.from_reader(&b"0b11"[..]), Ok(3));
+    assert_eq!(
+        strict.from_slice::<i32>(b"\xff").unwrap_err().to_string(),
+        json5::from_slice::<i32>(b"\xff").unwrap_err().to_string()
+    );
+    assert_eq!(
+        default.from_str::<i32>(""),
+        Err(json5::Error::EmptyDocument)
+    );
+
+    // Options apply all the way down.
+    assert!(strict
+        .from_str::<Vec<serde_json::Value>>("[{ b: [{ c: 1, c: 1 }] }]")
+        .is_err());
+    assert_eq!(
+        lenient.from_str("{ a: [0b1, { b: 0o7 }] }"),
+        Ok(serde_json::json!({ "a": [1, { "b": 7 }] }))
+    );
+}
+
+#[test]
+fn deserializes_map_with_non_string_keys() {
+    let mut map = HashMap::new();
+    map.insert(1u32, "a".to_owned());
+    map.insert(2u32, "b".to_owned());
+    deserializes_to("{ \"1\": \"a\", \"2\": \"b\" }", map);
+
+    let mut map = HashMap::new();
+    map.insert(-1i64, true);
+    deserializes_to("{ '-1': true }", map);
+
+    let mut map = std::collections::BTreeMap::new();
+    map.insert(true, 1.5f64);
+    map.insert(false, 2.);
+    deserializes_to("{ true: 1.5, 'false': 2 }", map);
+
+    #[derive(Deserialize, PartialEq, Eq, Hash, Debug)]
+    enum Level {
+        Info,
+        Warn,
+    }
+    #[derive(Deserialize, PartialEq, Eq, Hash, Debug)]
+    struct Port(u16);
+
+    let mut map = HashMap::new();
+    map.insert(Level::Info, 1);
+    map.insert(Level::Warn, 2);
+    deserializes_to("{ Info: 1, 'Warn': 2 }", map);
+
+    let mut map = HashMap::new();
+    map.insert(Port(80), 'h');
+    deserializes_to("{ '80': 'h' }", map);
+
+    let mut map = HashMap::new();
+    map.insert('x', 0);
+    deserializes_to("{ x: 0 }", map);
+
+    deserializes_with_error(
+        "{ a: 'x' }",
+        HashMap::<u32, String>::new(),
+        "invalid value: string \"a\", expected u32",
+    );
+    deserializes_with_error(
+        "{ '256': 'x' }",
+        HashMap::<u8, String>::new(),
+        "invalid value: string \"256\", expected u8",
+    );
+}
+
+#[test]
+fn deserializes_deeply_nested_with_error() {
+    let nested = |open: &str, close: &str, depth| open.repeat(depth) + &close.repeat(depth);
+    let exceeded = |limit, column| json5::Error::RecursionLimitExceeded {
+        limit,
+        location: json5::Location { line: 1, column },
+    };
+
+    assert!(json5::from_str::<json5::Value>(&nested("[", "]", 128)).is_ok());
+    assert_eq!(
+        json5::from_str::<json5::Value>(&nested("[", "]", 129)).unwrap_err(),
+        exceeded(128, 129)
+    );
+    assert_eq!(
+        json5::from_str::<json5::Value>(&nested("{a:", "}", 129)).unwrap_err(),
+        exceeded(128, 385)
+    );
+
+    // Far more than would fit on the stack, even with a tiny limit.
+    let deep = nested("[", "]", 100_000);
+    assert_eq!(
+        json5::from_str::<json5::Value>(&deep).unwrap_err(),
+        exceeded(128, 129)
+    );
+    assert!(json5::parse(&deep).is_err());
+    assert!(json5::events(&deep).is_err());
+    assert!(json5::StreamDeserializer::<json5::Value>::new(&deep)
+        .next()
+        .unwrap()
+        .is_err());
+    let options = json5::DeserializeOptions::new().recursion_limit(2);
+    assert!(options.from_str::<Vec<Vec<i32>>>("[[1], [2, 3]]").is_ok());
+    assert_eq!(
+        options.from_str::<json5::Value>("[[[1]]]").unwrap_err(),
+        exceeded(2, 3)
+    );
+
+    // Brackets in strings and comments don't count.
+    let quoted = format!(
+        "['{}', \"{}\" /* {} */]",
+        "[".repeat(200),
+        "{\\\"".repeat(200),
+        "[".repeat(200)
+    );
+    assert!(json5::from_str::<Vec<String>>(&quoted).is_ok());
+    assert!(json5::from_str::<Vec<String>>("// [[[\n['a']").is_ok());
+
+    deserializes_with_error(
+        &nested("[", "]", 200),
+        json5::Value::Null,
+        "nested more than 128 levels deep at line 1 column 129",
+    );
+}
+
+#[test]
+fn deserializes_with_input_size_limit() {
+    let too_large = |limit| json5::Error::InputTooLarge { limit };
+
+    assert_eq!(json5::from_str_limited("[1, 2, 3]", 9), Ok(vec![1, 2, 3]));
+    assert_eq!(
+        json5::from_str_limited::<Vec<i32>>("[1, 2, 3]", 8),
+        Err(too_large(8))
+    );
+    assert_eq!(
+        too_large(8).to_string(),
+        "input is longer than the limit of 8 bytes"
+    );
+    // Checked before parsing, so invalid input that is too long fails the same way.
+    assert_eq!(
+        json5::from_str_limited::<i32>("{ not: json5", 4),
+        Err(too_large(4))
+    );
+
+    let options = json5::DeserializeOptions::new()
+        .max_input_size(16)
+        .recursion_limit(2)
+        .allow_octal_and_binary(true);
+    assert_eq!(options.from_str("[0o17]"), Ok(vec![15]));
+    assert_eq!(
+        options.from_str::<json5::Value>("[[[]]]").unwrap_err(),
+        json5::Error::RecursionLimitExceeded {
+            limit: 2,
+            location: json5::Location { line: 1, column: 3 },
+        }
+    );
+    assert_eq!(
+        options.from_str::<json5::Value>(&"0".repeat(17)),
+        Err(too_large(16))
+    );
+    assert_eq!(
+        options.from_slice::<json5::Value>(&[0xff; 17]),
+        Err(too_large(16))
+    );
+    assert_eq!(
+        options.from_str_at::<json5::Value>("{ a: [1, 2, 3, 4] }", "a"),
+        Err(too_large(16))
+    );
+    assert_eq!(
+        options.from_str_with_warnings::<json5::Value>(&" ".repeat(17)),
+        Err(too_large(16))
+    );
+    #[cfg(feature = "std")]
+    {
+        assert_eq!(options.from_reader(&b"[1, 2]"[..]), Ok(vec![1, 2]));
+        assert_eq!(
+            options.from_reader::<_, json5::Value>(io::repeat(b' ')),
+            Err(too_large(16))
+        );
+    }
+}
+
+#[test]
+fn deserializes_tuples_from_objects() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Point(i32, i32);
+
+    let options = json5::DeserializeOptions::new().tuples_from_objects(true);
+    assert_eq!(
+        options.from_str::<(String, i32)>("{ '0': 'a', '1': 2 }"),
+        Ok(("a".to_owned(), 2))
+    );
+    assert_eq!(
+        options.from_str::<(String, i32)>("['a', 2]"),
+        Ok(("a".to_owned(), 2))
+    );
+    assert_eq!(
+        options.from_str::<Point>("{ \"0\": 1, \"1\": -1, }"),
+        Ok(Point(1, -1))
+    );
+    assert_eq!(
+        options.from_str::<Vec<(u8,)>>("[{ '0': 1 }, [2]]"),
+        Ok(vec![(1,), (2,)])
+    );
+
+    assert_eq!(
+        options
+            .from_str::<(i32, i32)>("{ '1': 1, '0': 0 }")
+            .unwrap_err()
+            .to_string(),
+        "expected key `0` for a tuple written as an object, found `1`"
+    );
+    assert_eq!(
+        options
+            .from_str::<(i32, i32, i32)>("{ '0': 0, '2': 2 }")
+            .unwrap_err()
+            .to_string(),
+        "expected key `1` for a tuple written as an object, found `2`"
+    );
+    assert_eq!(
+        options
+            .from_str::<(i32, i32)>("{ '0': 0, '01': 1 }")
+            .unwrap_err()
+            .to_string(),
+        "expected key `1` for a tuple written as an object, found `01`"
+    );
+    assert_eq!(
+        options.from_str::<(i32, i32)>("{ '0': 0 }"),
+        Err(json5::Error::WrongTupleLength {
+            expected: 2,
+            found: 1
+        })
+    );
+    assert_eq!(
+        options.from_str::<(i32,)>("{ '0': 0, '1': 1 }"),
+        Err(json5::Error::WrongTupleLength {
+            expected: 1,
+            found: 2
+        })
+    );
+
+    // Only tuples are affected, and only with the option.
+    assert!(options.from_str::<Vec<i32>>("{ '0': 0 }").is_err());
+    assert!(json5::from_str::<(String, i32)>("{ '0': 'a', '1': 2 }").is_err());
+}
+
+#[test]
+fn deserializes_any_integer_as_integer() {
+    use serde_json::json;
+
+    let any = |s| json5::from_str::<serde_json::Value>(s).unwrap();
+    assert!(any("1").is_u64());
+    assert_eq!(any("1"), json!(1));
+    assert!(any("1.0").is_f64());
+    assert_eq!(any("0xFF"), json!(255));
+    assert!(any("0xFF").is_u64());
+    assert!(any("1e3").is_f64());
+    assert_eq!(any("1e3"), json!(1000.0));
+    assert!(any("-1").is_i64());
+    assert_eq!(any("18446744073709551615"), json!(u64::MAX));
+    assert_eq!(any("-9223372036854775808"), json!(i64::MIN));
+    assert_eq!(any("-0x10"), json!(-16));
+
+    // Integers too big for a `u64` or `i64` are approximated.
+    assert_eq!(any("18446744073709551616"), json!(18446744073709551616.0));
+    assert_eq!(any("-9223372036854775809"), json!(-9223372036854775809.0));
+
+    let number = |s| match json5::from_str::<json5::Value>(s).unwrap() {
+        json5::Value::Number(n) => n,
+        v => panic!("expected a number, found {:?}", v),
+    };
+    assert_eq!(number("1").as_u64(), Some(1));
+    assert!(number("1.0").is_f64());
+    assert_eq!(number("0xFF").as_u64(), Some(255));
+    assert!(number("1e3").is_f64());
+    assert_eq!(number("-1").as_i64(), Some(-1));
+}
+
+#[test]
+#[cfg(feature = "decimal")]
+fn deserializes_decimal() {
+    use rust_decimal::Decimal;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Payment {
+        #[serde(deserialize_with = "json5::decimal::deserialize")]
+        amount: Decimal,
+        note: String,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Amount(#[serde(deserialize_with = "json5::decimal::deserialize")] Decimal);
+
+    let decimal = |s: &str| s.parse::<Decimal>().unwrap();
+    let amount = |s| json5::from_str::<Amount>(s).map(|a| a.0.to_string());
+
+    assert_eq!(
+        json5::from_str("{ amount: 12345678901234567890.12, note: 'rent' }"),
+        Ok(Payment {
+            amount: decimal("12345678901234567890.12"),
+            note: "rent".to_owned(),
+        })
+    );
+    // Without it the number goes through an `f64`.
+    assert_ne!(
+        json5::from_str::<Decimal>("12345678901234567890.12"),
+        Ok(decimal("12345678901234567890.12"))
+    );
+
+    assert_eq!(amount("-0.10"), Ok("-0.10".to_owned()));
+    assert_eq!(amount("+1"), Ok("1".to_owned()));
+    assert_eq!(amount(".5"), Ok("0.5".to_owned()));
+    assert_eq!(amount("1.5e2"), Ok("150".to_owned()));
+    assert_eq!(amount("0x1F"), Ok("31".to_owned()));
+    assert_eq!(amount("'2.50'"), Ok("2.50".to_owned()));
+    assert!(amount("Infinity").is_err());
+    assert!(amount("NaN").is_err());
+    assert!(amount("true").is_err());
+    assert!(amount("79228162514264337593543950336").is_err());
+
+    // Extensions still have to be enabled.
+    let options = json5::DeserializeOptions::new();
+    assert!(options.from_str::<Amount>("1_000.5").is_err());
+    assert_eq!(
+        options
+            .allow_underscores_in_numbers(true)
+            .from_str::<Amount>("1_000.5"),
+        Ok(Amount(decimal("1000.5")))
+    );
+
+    // A `Value` only has the `f64`.
+    let value = json5::from_str::<json5::Value>("{ amount: 1.5, note: '' }").unwrap();
+    assert_eq!(
+        json5::from_value::<Payment>(value).map(|p| p.amount),
+        Ok(decimal("1.5"))
+    );
+}
+
+#[test]
+fn deserializes_smart_pointers() {
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S {
+        name: String,
+        child: Option<Box<S>>,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Wrapper(Box<S>);
+
+    let leaf = |name: &str| S {
+        name: name.to_owned(),
+        child: None,
+    };
+    let input = "{ name: 'root', child: { name: 'a', child: null } }";
+    let root = || S {
+        name: "root".to_owned(),
+        child: Some(Box::new(leaf("a"))),
+    };
+    deserializes_to(input, Box::new(root()));
+    deserializes_to(input, Rc::new(root()));
+    deserializes_to(input, Arc::new(root()));
+    deserializes_to(input, Wrapper(Box::new(root())));
+    deserializes_to("[1, null]", vec![Rc::new(Some(1)), Rc::new(None)]);
+    deserializes_to("'x'", Arc::<str>::from("x"));
+    deserializes_to("[1, 2]", Box::<[u8]>::from([1, 2]));
+
+    deserializes_with_error(
+        "{ name: 1, child: null }",
+        Box::new(leaf("")),
+        "expected a string, found a number at line 1 column 9",
+    );
+}
+
+#[test]
+fn deserializes_at_path() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    let input = "{
+        servers: [{ host: 'a', port: 80 }, { host: 'b', port: 8080 }],
+        'dotted.key': { '[x]': true },
+        dup: 1, dup: 2,
+        rest: { port: 'not a number' },
+    }";
+    assert_eq!(json5::from_str_at::<u16>(input, "servers[0].port"), Ok(80));
+    assert_eq!(
+        json5::from_str_at(input, "servers[1]"),
+        Ok(Server {
+            host: "b".to_owned(),
+            port: 8080
+        })
+    );
+    assert_eq!(
+        json5::from_str_at::<Vec<Server>>(input, "servers").map(|s| s.len()),
+        Ok(2)
+    );
+    assert_eq!(
+        json5::from_str_at::<bool>(input, "['dotted.key'][\"[x]\"]"),
+        Ok(true)
+    );
+    assert_eq!(json5::from_str_at::<i32>(input, "dup"), Ok(2));
+    assert_eq!(json5::from_str_at::<i32>("[[1, 2], [3]]", "[1][0]"), Ok(3));
+    assert_eq!(json5::from_str_at::<i32>("7", ""), Ok(7));
+    assert_eq!(
+        json5::from_str_at::<&str>("{ a: { b: 'borrowed' } }", "a.b"),
+        Ok("borrowed")
+    );
+
+    let not_found = |path: &str| {
+        Err::<json5::Value, _>(json5::Error::PathNotFound {
+            path: path.to_owned(),
+        })
+    };
+    assert_eq!(
+        json5::from_str_at(input, "servers[2].port"),
+        not_found("servers[2]")
+    );
+    assert_eq!(
+        json5::from_str_at(input, "servers[0].name"),
+        not_found("servers[0].name")
+    );
+    assert_eq!(
+        json5::from_str_at(input, "missing.port"),
+        not_found("missing")
+    );
+    assert_eq!(
+        json5::from_str_at(input, "servers.host"),
+        not_found("servers.host")
+    );
+    assert_eq!(json5::from_str_at(input, "dup[0]"), not_found("dup[0]"));
+    assert_eq!(
+        json5::from_str_at::<json5::Value>(input, "servers[9]")
+            .unwrap_err()
+            .to_string(),
+        "no value at path `servers[9]`"
+    );
+
+    for path in &[
+        ".a", "a.", "a..b", "a[", "a[x]", "a[-1]", "a['b]", "a['b'", "[0]b", "a]",
+    ] {
+        assert_eq!(
+            json5::from_str_at::<json5::Value>(input, path)
+                .unwrap_err()
+                .to_string(),
+            format!("invalid path `{}`", path)
+        );
+    }
+
+    // Only the selected value is deserialized, but the whole document must be valid.
+    assert!(json5::from_str_at::<String>(input, "rest.port").is_ok());
+    assert!(json5::from_str_at::<u16>(input, "rest.port").is_err());
+    assert!(json5::from_str_at::<u16>("{ a: 1, b: [,] }", "a").is_err());
+    let options = json5::DeserializeOptions::new().allow_octal_and_binary(true);
+    assert_eq!(
+        options.from_str_at::<u32>("{ mode: 0o755 }", "mode"),
+        Ok(0o755)
+    );
+}
+
+#[test]
+fn displays_error_with_source() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Config {
+        name: String,
+        port: u16,
+    }
+
+    let input = "{\n  name: 'x',\n  port: true,\n}";
+    let err = json5::from_str::<Config>(input).unwrap_err();
+    assert_eq!(
+        err.display_with_source(input).to_string(),
+        "expected a number, found a boolean at line 3 column 9\n   |\n 3 |   port: true,\n   |         ^^^^"
+    );
+
+    // The gutter fits the line number, tabs and wide characters are lined up, and a value
+    // spanning lines is only underlined on the first.
+    let input = format!("{}{{\n\té: [1,\n  2] }}", "\n".repeat(9));
+    let err = json5::from_str::<HashMap<String, i32>>(&input).unwrap_err();
+    assert_eq!(
+        err.display_with_source(&input).to_string(),
+        "expected a number, found an array at line 11 column 5\n    |\n 11 | \té: [1,\n    | \t   ^^^"
+    );
+
+    // Without a span there is a single caret.
+    let input = "[1]\r\n  x";
+    let err = json5::from_str::<json5::Value>(input).unwrap_err();
+    assert_eq!(
+        err.display_with_source(input).to_string(),
+        "trailing characters after the JSON5 value at line 2 column 3\n   |\n 2 |   x\n   |   ^"
+    );
+
+    // Syntax errors already show the line, and other errors have no location.
+    let input = "{\n  a: ,\n}";
+    let err = json5::from_str::<json5::Value>(input).unwrap_err();
+    assert_eq!(err.display_with_source(input).to_string(), err.to_string());
+    assert!(err.to_string().contains("2 |   a: ,"));
+    let err = json5::from_str::<(i32,)>("[1, 2]").unwrap_err();
+    assert_eq!(
+        err.display_with_source("[1, 2]").to_string(),
+        err.to_string()
+    );
 }
@@ -104,7 +104,7 @@ fn deserializes_example_nan() {
 
     match json5::from_str::<NanF64>(&contents) {
         Ok(value) => assert!(value.nan.is_nan() && value.neg_nan.is_nan()),
-        Err(err) => panic!(format!("{}", err)),
+        Err(err) => panic!("{}", err),
     }
 
     #[derive(Deserialize, PartialEq, Debug)]
@@ -115,7 +115,7 @@ fn deserializes_example_nan() {
 
     match json5::from_str::<NanF32>(&contents) {
         Ok(value) => assert!(value.nan.is_nan() && value.neg_nan.is_nan()),
-        Err(err) => panic!(format!("{}", err)),
+        Err(err) => panic!("{}", err),
     }
 
     let mut map = HashMap::new();
@@ -124,11 +124,11 @@ fn deserializes_example_nan() {
     match json5::from_str::<Val>(&contents) {
         Ok(value) => match value {
             Val::Object(v) => {
-                let nan = match v.get(&"nan".to_string()).unwrap() {
+                let nan = match v.get("nan").unwrap() {
                     Val::Number(n) => n,
                     _ => panic!("not NaN"),
                 };
-                let neg_nan = match v.get(&"neg_nan".to_string()).unwrap() {
+                let neg_nan = match v.get("neg_nan").unwrap() {
                     Val::Number(n) => n,
                     _ => panic!("not NaN"),
                 };
@@ -136,7 +136,7 @@ fn deserializes_example_nan() {
             }
             _ => panic!("not NaN"),
         },
-        Err(err) => panic!(format!("{}", err)),
+        Err(err) => panic!("{}", err),
     }
 
     deserializes_to::<Value>(
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::fs;
+
+use json5::{DeserializeOptions, Value};
+use serde_derive::Deserialize;
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+enum Enum {
+    Unit,
+    Newtype(i8),
+    Tuple(u16, char),
+    Struct { a: Option<f32> },
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+struct Struct {
+    a: Vec<u8>,
+    b: (i64, String),
+    c: HashMap<u32, Enum>,
+    d: Box<[bool]>,
+}
+
+// The same as the cargo-fuzz target in `fuzz/fuzz_targets/from_slice.rs`, which is run over the
+// corpus and mutations of it here so that every test run checks that none of them panic.
+fn exercise(data: &[u8]) {
+    let lenient = DeserializeOptions::new()
+        .allow_octal_and_binary(true)
+        .allow_underscores_in_numbers(true)
+        .null_as_empty_collection(true)
+        .tuples_from_objects(true)
+        .recursion_limit(16);
+    let strict = DeserializeOptions::new()
+        .deny_duplicate_keys(true)
+        .deny_unknown_fields(true);
+    let _ = json5::from_slice::<Value>(data);
+    let _ = json5::from_slice::<Struct>(data);
+    let _ = json5::from_slice::<Vec<Enum>>(data);
+    let _ = lenient.from_slice::<Struct>(data);
+    let _ = lenient.from_slice::<HashMap<String, Vec<String>>>(data);
+    let _ = strict.from_slice::<Struct>(data);
+    let _ = strict.from_slice::<Value>(data);
+
+    let s = match std::str::from_utf8(data) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    let _ = json5::from_str_with_warnings::<Value>(s);
+    let _ = json5::from_str_at::<Value>(s, "a[0].b");
+    let _ = json5::validate(s);
+    if let Err(err) = json5::from_str::<Struct>(s) {
+        let _ = err.to_string();
+        let _ = err.display_with_source(s).to_string();
+    }
+    let _ = json5::StreamDeserializer::<Value>::new(s).take(8).count();
+    let _ = strict.stream::<Value>(s).take(8).count();
+    if let Ok(events) = json5::events(s) {
+        let _ = events.count();
+    }
+    if let Ok(doc) = json5::parse(s) {
+        let _ = doc.attached_comments();
+        let _ = doc.root().to_spanned_value();
+        let _ = doc.root().leading_comments();
+        let _ = doc.root().trailing_comment();
+    }
+    if let Ok(value) = json5::from_str::<Value>(s) {
+        let _ = json5::to_string(&value);
+        let _ = value.pointer("/a/0");
+        let _ = json5::from_value::<Struct>(value);
+    }
+}
+
+// A tiny xorshift generator, so that the mutations are the same on every run.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 as usize
+    }
+}
+
+const INTERESTING: &[&[u8]] = &[
+    b"[",
+    b"]",
+    b"{",
+    b"}",
+    b":",
+    b",",
+    b"'",
+    b"\"",
+    b"\\",
+    b"\\u",
+    b"\\x",
+    b"/*",
+    b"*/",
+    b"//",
+    b"\n",
+    b"\r",
+    b"0x",
+    b"-",
+    b"+",
+    b".",
+    b"e",
+    b"_",
+    b"0",
+    b"9",
+    b"n",
+    b"\xc3\xa9",
+    b"\xe2\x80\xa8",
+    b"\xef\xbb\xbf",
+    b"\xf0\x9f\x98\x80",
+    b"\xff",
+    b"\x00",
+];
+
+fn mutate(seed: &[u8], rng: &mut Rng) -> Vec<u8> {
+    let mut data = seed.to_vec();
+    for _ in 0..1 + rng.next() % 4 {
+        let at = rng.next() % (data.len() + 1);
+        match rng.next() % 3 {
+            0 if at < data.len() => {
+                data.remove(at);
+            }
+            1 if at < data.len() => data[at] = rng.next() as u8,
+            _ => {
+                let insert = INTERESTING[rng.next() % INTERESTING.len()];
+                data.splice(at..at, insert.iter().copied());
+            }
+        }
+    }
+    data
+}
+
+fn corpus() -> Vec<Vec<u8>> {
+    let mut paths = fs::read_dir("fuzz/corpus/from_slice")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| fs::read(path).unwrap())
+        .collect()
+}
+
+#[test]
+fn reads_fuzz_corpus_without_panicking() {
+    for seed in corpus() {
+        for end in 0..=seed.len() {
+            exercise(&seed[..end]);
+        }
+    }
+}
+
+#[test]
+fn reads_mutated_fuzz_corpus_without_panicking() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for seed in corpus() {
+        for _ in 0..200 {
+            let data = mutate(&seed, &mut rng);
+            exercise(&data);
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Deserializes a document of several megabytes with an allocator that records the largest
+//! allocation, to check that the input is never copied as a whole.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct Largest;
+
+static LARGEST: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Largest {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LARGEST.fetch_max(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        LARGEST.fetch_max(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Largest = Largest;
+
+#[test]
+fn deserializes_large_document_without_copying_it() {
+    let entry = format!("  // entry\n  '{}',\n", "x".repeat(4096));
+    let input = format!("[\n{}]", entry.repeat(512));
+    assert!(input.len() > 2 << 20);
+    let bytes = input.as_bytes();
+
+    LARGEST.store(0, Ordering::Relaxed);
+    let strings: Vec<&str> = json5::from_slice(bytes).unwrap();
+    let largest = LARGEST.load(Ordering::Relaxed);
+
+    assert_eq!(strings.len(), 512);
+    assert!(strings
+        .iter()
+        .all(|s| bytes.as_ptr_range().contains(&s.as_ptr())));
+    assert!(
+        largest < input.len() / 8,
+        "allocated {} bytes at once for {} bytes of input",
+        largest,
+        input.len()
+    );
+}
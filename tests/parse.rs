@@ -0,0 +1,322 @@
+use json5::{Location, Rule, Span, ValueKind};
+
+#[test]
+fn parses_document() {
+    let input = "// config\n{\n  a: [1, 'two', null],\n  \"b\": { c: true },\n}\n";
+    let doc = json5::parse(input).unwrap();
+    let root = doc.root();
+
+    assert_eq!(root.rule(), Rule::object);
+    assert_eq!(root.kind(), ValueKind::Object);
+    assert_eq!(root.location(), Location { line: 2, column: 1 });
+    assert_eq!(&input[root.span().start..root.span().end], root.as_str());
+    assert_eq!(root.elements().count(), 0);
+
+    let entries: Vec<_> = root.entries().collect();
+    assert_eq!(entries.len(), 2);
+
+    let (a, array) = &entries[0];
+    assert_eq!(a.rule(), Rule::identifier);
+    assert_eq!(a.kind(), ValueKind::String);
+    assert_eq!(a.as_str(), "a");
+    assert_eq!(a.location(), Location { line: 3, column: 3 });
+    assert_eq!(array.as_str(), "[1, 'two', null]");
+    let rules: Vec<_> = array.elements().map(|n| n.rule()).collect();
+    assert_eq!(rules, vec![Rule::number, Rule::string, Rule::null]);
+    assert_eq!(array.entries().count(), 0);
+
+    let (b, object) = &entries[1];
+    assert_eq!(b.rule(), Rule::string);
+    assert_eq!(b.as_str(), "\"b\"");
+    assert_eq!(b.span(), Span { start: 37, end: 40 });
+    let (c, t) = object.entries().next().unwrap();
+    assert_eq!((c.as_str(), t.rule()), ("c", Rule::boolean));
+}
+
+#[test]
+fn parses_and_deserializes_nodes() {
+    let doc = json5::parse("{ a: [1, 2], b: 'x\\ty' }").unwrap();
+    let mut entries = doc.root().entries();
+
+    let (key, value) = entries.next().unwrap();
+    assert_eq!(key.deserialize::<String>(), Ok("a".to_owned()));
+    assert_eq!(value.deserialize::<Vec<u8>>(), Ok(vec![1, 2]));
+
+    let (_, value) = entries.next().unwrap();
+    assert_eq!(value.as_str(), "'x\\ty'");
+    assert_eq!(value.deserialize::<String>(), Ok("x\ty".to_owned()));
+    assert!(value.deserialize::<i32>().is_err());
+    assert!(entries.next().is_none());
+}
+
+#[test]
+fn parses_with_error() {
+    assert_eq!(
+        json5::parse(" // nothing").unwrap_err(),
+        json5::Error::EmptyDocument
+    );
+    let err = json5::parse("[1,\n 2,,]").unwrap_err();
+    assert_eq!(err.location(), Some(Location { line: 2, column: 4 }));
+    assert!(json5::parse("1 2").is_err());
+}
+
+#[test]
+fn parses_comments() {
+    let input = "// header\n/* config */\n{\n  // the name\n  name: 'a // b', // trailing\n  /* one */ /* two */ tags: [\n    1, /* first */\n    // second\n    2\n  ],\n}\n";
+    let doc = json5::parse(input).unwrap();
+
+    let all: Vec<&str> = doc.comments().iter().map(|c| c.as_str()).collect();
+    assert_eq!(
+        all,
+        vec![
+            "// header",
+            "/* config */",
+            "// the name",
+            "// trailing",
+            "/* one */",
+            "/* two */",
+            "/* first */",
+            "// second"
+        ]
+    );
+    assert_eq!(doc.comments()[1].text(), " config ");
+    assert!(doc.comments()[1].is_block() && !doc.comments()[0].is_block());
+    assert_eq!(doc.comments()[0].span(), Span { start: 0, end: 9 });
+
+    let root = doc.root();
+    let leading: Vec<&str> = root.leading_comments().iter().map(|c| c.text()).collect();
+    assert_eq!(leading, vec![" header", " config "]);
+    assert_eq!(root.trailing_comment(), None);
+
+    let mut entries = root.entries();
+    let (key, value) = entries.next().unwrap();
+    let leading: Vec<&str> = key.leading_comments().iter().map(|c| c.text()).collect();
+    assert_eq!(leading, vec![" the name"]);
+    assert_eq!(value.as_str(), "'a // b'");
+    assert_eq!(value.trailing_comment().unwrap().text(), " trailing");
+
+    let (key, value) = entries.next().unwrap();
+    let leading: Vec<&str> = key.leading_comments().iter().map(|c| c.text()).collect();
+    assert_eq!(leading, vec![" one ", " two "]);
+    assert_eq!(key.trailing_comment(), None);
+
+    let elements: Vec<_> = value.elements().collect();
+    assert!(elements[0].leading_comments().is_empty());
+    assert_eq!(elements[0].trailing_comment().unwrap().text(), " first ");
+    let leading: Vec<&str> = elements[1]
+        .leading_comments()
+        .iter()
+        .map(|c| c.text())
+        .collect();
+    assert_eq!(leading, vec![" second"]);
+    assert_eq!(elements[1].trailing_comment(), None);
+}
+
+#[test]
+fn parses_attached_comments() {
+    use json5::PathSegment::{Index, Key};
+
+    let input = "// the config
+        {
+          /* name */ name: 'a', // trailing name
+          'quoted\\u0020key': 1,
+          list: [
+            // first
+            { x: null /* x */ },
+            [/* nothing */],
+            3, // third
+          ],
+          // before the end of the object, so not attached to anything
+        } // after everything";
+    let doc = json5::parse(input).unwrap();
+    let attached: Vec<(Vec<json5::PathSegment>, &str)> = doc
+        .attached_comments()
+        .unwrap()
+        .into_iter()
+        .map(|a| (a.path, a.comment.text()))
+        .collect();
+    let key = |k: &str| Key(k.to_owned());
+    assert_eq!(
+        attached,
+        vec![
+            (vec![], " the config"),
+            (vec![key("name")], " name "),
+            (vec![key("name")], " trailing name"),
+            (vec![key("list"), Index(0)], " first"),
+            (vec![key("list"), Index(0), key("x")], " x "),
+            (vec![key("list"), Index(2)], " third"),
+            (vec![], " after everything"),
+        ]
+    );
+    assert_eq!(doc.comments().len(), 9);
+
+    let doc = json5::parse("{ /* a */ 'a\\u0020b': 1 }").unwrap();
+    assert_eq!(doc.attached_comments().unwrap()[0].path, vec![key("a b")]);
+    assert!(json5::parse("1")
+        .unwrap()
+        .attached_comments()
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn validates() {
+    for input in &[
+        "{ a: [1, 'two', null,], }",
+        "// just a number\n-0x2A",
+        "1e400",
+        "'\\uD834\\uDD1E'",
+    ] {
+        assert_eq!(json5::validate(input), Ok(()), "{}", input);
+    }
+
+    assert_eq!(json5::validate(" "), Err(json5::Error::EmptyDocument));
+    assert!(matches!(
+        json5::validate("[1,,]"),
+        Err(json5::Error::Parse { .. })
+    ));
+    assert!(matches!(
+        json5::validate("1 2"),
+        Err(json5::Error::TrailingCharacters { .. })
+    ));
+    for input in &[
+        "0o17",
+        "[0b1]",
+        "{ a: { b: [-0o7] } }",
+        "'\\uD800'",
+        "[{ '\\uDC00': 1 }]",
+        "{ \\uD800: 1 }",
+    ] {
+        assert_eq!(
+            json5::validate(input).map_err(|e| e.to_string()),
+            json5::from_str::<json5::Value>(input)
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+        );
+        assert!(json5::validate(input).is_err());
+    }
+}
+
+#[test]
+fn parses_spanned_values() {
+    use json5::{Spanned, SpannedValue, Value};
+
+    let input = "{ a: [1, 'x\\n'], 'b': { c: null }, a: true }";
+    let root = json5::parse(input)
+        .unwrap()
+        .root()
+        .to_spanned_value()
+        .unwrap();
+    let at = |span: Span| &input[span.start..span.end];
+    assert_eq!(at(root.span), input);
+
+    let entries = match root.value {
+        SpannedValue::Object(ref entries) => entries,
+        _ => panic!("expected an object"),
+    };
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[1].0.value, "b");
+    assert_eq!(at(entries[1].0.span), "'b'");
+
+    let array = &entries[0].1;
+    assert_eq!(at(array.span), "[1, 'x\\n']");
+    assert_eq!(
+        array.value.get_index(1),
+        Some(&Spanned {
+            value: SpannedValue::String("x\n".to_owned()),
+            span: Span { start: 9, end: 14 },
+        })
+    );
+    assert_eq!(array.value.get_index(2), None);
+    assert_eq!(array.value.get("a"), None);
+
+    let c = root.value.get("b").unwrap().value.get("c").unwrap();
+    assert_eq!((&c.value, at(c.span)), (&SpannedValue::Null, "null"));
+    assert_eq!(at(root.value.get("a").unwrap().span), "true");
+
+    assert_eq!(
+        Value::from(root.value),
+        json5::from_str::<Value>(input).unwrap()
+    );
+    assert!(json5::parse("[1, 300, 0o7]")
+        .unwrap()
+        .root()
+        .to_spanned_value()
+        .is_err());
+}
+
+#[test]
+fn parses_events() {
+    use json5::Event::{self, *};
+    use std::borrow::Cow;
+
+    fn events(input: &str) -> json5::Result<Vec<Event<'_>>> {
+        json5::events(input)?.collect()
+    }
+
+    let input = "// config
+        {
+            name: 'json5',
+            'quoted\\tkey': [true, -0x10, 2.5, Infinity, [], {}],
+            nested: { deeper: [null, \"\\u00e9\"] },
+        }";
+    let all = events(input).unwrap();
+    assert_eq!(
+        all,
+        vec![
+            StartObject,
+            Key("name".into()),
+            String("json5".into()),
+            Key("quoted\tkey".into()),
+            StartArray,
+            Bool(true),
+            Number((-16i64).into()),
+            Number(2.5.into()),
+            Number(f64::INFINITY.into()),
+            StartArray,
+            EndArray,
+            StartObject,
+            EndObject,
+            EndArray,
+            Key("nested".into()),
+            StartObject,
+            Key("deeper".into()),
+            StartArray,
+            Null,
+            String("é".into()),
+            EndArray,
+            EndObject,
+            EndObject,
+        ]
+    );
+    // Only strings with escapes are copied.
+    assert!(matches!(all[1], Key(Cow::Borrowed("name"))));
+    assert!(matches!(all[3], Key(Cow::Owned(_))));
+    assert!(matches!(all[19], String(Cow::Owned(_))));
+
+    assert_eq!(events("'top'").unwrap(), vec![String("top".into())]);
+    assert_eq!(events("[]").unwrap(), vec![StartArray, EndArray]);
+    match &events("NaN").unwrap()[..] {
+        [Number(n)] => assert!(n.as_f64().is_nan()),
+        other => panic!("{:?}", other),
+    }
+
+    // Syntax errors are found before any events, other errors at the value they're about.
+    assert!(matches!(
+        json5::events("[1, 2,,]"),
+        Err(json5::Error::Parse { .. })
+    ));
+    assert!(matches!(
+        json5::events("1 2"),
+        Err(json5::Error::TrailingCharacters { .. })
+    ));
+    let mut octal = json5::events("[1, 0o7, 2]").unwrap();
+    assert_eq!(octal.next(), Some(Ok(StartArray)));
+    assert_eq!(octal.next(), Some(Ok(Number(1u64.into()))));
+    assert!(matches!(octal.next(), Some(Err(_))));
+    assert_eq!(octal.next(), None);
+    assert_eq!(
+        events("['\\uD800']"),
+        Err(json5::Error::InvalidEscape("\\uD800".to_owned()))
+    );
+}
@@ -1,6 +1,8 @@
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
 use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::io;
 
 mod common;
 
@@ -60,14 +62,54 @@ fn serializes_u64() {
     serializes_to(x, "42");
 }
 
+#[test]
+fn serializes_i128() {
+    let x: i128 = 42;
+    serializes_to(x, "42");
+}
+
+#[test]
+fn serializes_u128() {
+    let x: u128 = 42;
+    serializes_to(x, "42");
+}
+
+#[test]
+fn round_trips_128_bit_integers() {
+    let output = json5::to_string(&u128::MAX).unwrap();
+    assert_eq!(output, "340282366920938463463374607431768211455");
+    assert_eq!(json5::from_str::<u128>(&output), Ok(u128::MAX));
+
+    let output = json5::to_string(&i128::MIN).unwrap();
+    assert_eq!(output, "-170141183460469231731687303715884105728");
+    assert_eq!(json5::from_str::<i128>(&output), Ok(i128::MIN));
+
+    let pretty = json5::to_string_pretty(&[i128::MIN], &json5::PrettyConfig::new()).unwrap();
+    assert_eq!(json5::from_str::<Vec<i128>>(&pretty), Ok(vec![i128::MIN]));
+
+    let hex = json5::SerializeOptions::new().number_format(json5::NumberFormat::Hex);
+    let output = hex.to_string(&(u128::MAX, i128::MIN)).unwrap();
+    assert_eq!(
+        output,
+        "[0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF,-0x80000000000000000000000000000000]"
+    );
+    assert_eq!(
+        json5::from_str::<(u128, i128)>(&output),
+        Ok((u128::MAX, i128::MIN))
+    );
+}
+
 #[test]
 fn serializes_f32() {
     let x: f32 = 42.42;
     serializes_to(x, "42.42");
 
-    serializes_to(std::f32::INFINITY, "Infinity");
-    serializes_to(std::f32::NEG_INFINITY, "-Infinity");
-    serializes_to(std::f32::NAN, "NaN");
+    serializes_to(f32::INFINITY, "Infinity");
+    serializes_to(f32::NEG_INFINITY, "-Infinity");
+    serializes_to(f32::NAN, "NaN");
+    serializes_to(-f32::NAN, "NaN");
+    serializes_to(-0.0f32, "-0.0");
+    serializes_to(0.0f32, "0.0");
 }
 
 #[test]
@@ -75,9 +117,32 @@ fn serializes_f64() {
     let x: f64 = 42.42;
     serializes_to(x, "42.42");
 
-    serializes_to(std::f64::INFINITY, "Infinity");
-    serializes_to(std::f64::NEG_INFINITY, "-Infinity");
-    serializes_to(std::f64::NAN, "NaN");
+    serializes_to(f64::INFINITY, "Infinity");
+    serializes_to(f64::NEG_INFINITY, "-Infinity");
+    serializes_to(f64::NAN, "NaN");
+    serializes_to(-f64::NAN, "NaN");
+    serializes_to(-0.0f64, "-0.0");
+    serializes_to(0.0f64, "0.0");
+}
+
+#[test]
+fn round_trips_special_floats() {
+    for &x in &[
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        0.,
+        -0.,
+        f64::MIN_POSITIVE,
+        f64::MAX,
+    ] {
+        let back: f64 = json5::from_str(&json5::to_string(&x).unwrap()).unwrap();
+        assert_eq!(back.to_bits(), x.to_bits(), "{}", x);
+    }
+    let back: f64 = json5::from_str(&json5::to_string(&-f64::NAN).unwrap()).unwrap();
+    assert!(back.is_nan());
+
+    let back: f32 = json5::from_str(&json5::to_string(&-0.0f32).unwrap()).unwrap();
+    assert_eq!(back.to_bits(), (-0.0f32).to_bits());
 }
 
 #[test]
@@ -92,6 +157,19 @@ fn serializes_char() {
     serializes_to('/', r#""\/""#);
     serializes_to('\u{0008}', r#""\b""#);
     serializes_to('\u{000c}', r#""\f""#);
+    serializes_to('\u{2028}', r#""\u2028""#);
+    serializes_to('\u{2029}', r#""\u2029""#);
+}
+
+#[test]
+fn round_trips_char() {
+    for &c in &[
+        'x', ' ', '\'', '"', '\\', '/', '\n', '\r', '\t', '\0', '\u{7f}', 'é', '자', '😀',
+        '\u{2028}', '\u{2029}',
+    ] {
+        let s = json5::to_string(&c).unwrap();
+        assert_eq!(json5::from_str::<char>(&s), Ok(c), "{}", s);
+    }
 }
 
 #[test]
@@ -103,6 +181,35 @@ fn serializes_str() {
     serializes_to("\\", "\"\\\\\"");
 }
 
+#[test]
+fn serializes_str_with_escapes() {
+    serializes_to("a\u{000b}b", r#""a\vb""#);
+    serializes_to("\0", r#""\0""#);
+    serializes_to("\0a", r#""\0a""#);
+    serializes_to("\x001", r#""\u00001""#);
+    serializes_to("\u{1}\u{1f}\u{7f}\u{9f}", r#""\u0001\u001f\u007f\u009f""#);
+    serializes_to("\u{2028}\u{2029}", r#""\u2028\u2029""#);
+    serializes_to("\u{a0}é", "\"\u{a0}é\"");
+}
+
+#[test]
+fn round_trips_special_characters() {
+    let mut s: String = (0..=0x20u8).map(char::from).collect();
+    s.push_str("\"'\\/\u{7f}\u{80}\u{9f}\u{a0}\u{2028}\u{2029}\u{feff}é😀\x00\x001");
+    for options in &[
+        json5::SerializeOptions::new(),
+        json5::SerializeOptions::new().ascii_only(true),
+    ] {
+        let serialized = options.to_string(&s).unwrap();
+        assert_eq!(
+            json5::from_str::<String>(&serialized),
+            Ok(s.clone()),
+            "{}",
+            serialized
+        );
+    }
+}
+
 #[test]
 fn serializes_string() {
     serializes_to("Hello!".to_owned(), "\"Hello!\"");
@@ -113,12 +220,84 @@ fn serializes_string() {
 }
 
 #[test]
-#[ignore] // TODO currently unsupported
-fn serializes_bytes() {}
+fn serializes_bytes() {
+    #[derive(PartialEq, Debug)]
+    struct Bytes(&'static [u8]);
+    impl serde::Serialize for Bytes {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+
+    serializes_to(Bytes(b"hi"), "[104,105]");
+    serializes_to(Bytes(&[]), "[]");
+    serializes_to(Bytes(&[0, 255, 0xc3]), "[0,255,195]");
+    assert_eq!(
+        json5::to_string_pretty(&Bytes(&[1, 2]), &json5::PrettyConfig::new()),
+        Ok("[\n  1,\n  2\n]".to_owned())
+    );
+}
 
 #[test]
-#[ignore] // TODO currently unsupported
-fn serializes_byte_buf() {}
+fn serializes_byte_buf() {
+    // The same as `#[serde(with = "serde_bytes")]` on a `Vec<u8>`.
+    mod as_bytes {
+        use serde::de::{self, Deserializer, Visitor};
+        use serde::Serializer;
+        use std::fmt;
+
+        pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(bytes)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Vec<u8>, D::Error> {
+            struct BytesVisitor;
+            impl Visitor<'_> for BytesVisitor {
+                type Value = Vec<u8>;
+
+                fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.write_str("bytes")
+                }
+
+                fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+                    Ok(v.to_vec())
+                }
+
+                fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+                    Ok(v)
+                }
+            }
+            deserializer.deserialize_byte_buf(BytesVisitor)
+        }
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct S {
+        #[serde(with = "as_bytes")]
+        data: Vec<u8>,
+    }
+
+    // Not valid UTF-8, so couldn't be written as a string.
+    let s = S {
+        data: vec![0xff, 0, b'a', 0x80],
+    };
+    serializes_to(
+        S {
+            data: s.data.clone(),
+        },
+        "{\"data\":[255,0,97,128]}",
+    );
+    let text = json5::to_string(&s).unwrap();
+    assert_eq!(json5::from_str::<S>(&text), Ok(s));
+    assert_eq!(
+        json5::from_str::<S>("{ data: 'ab' }"),
+        Ok(S {
+            data: b"ab".to_vec()
+        })
+    );
+}
 
 #[test]
 fn serializes_option() {
@@ -148,7 +327,7 @@ fn serializes_newtype_struct() {
     struct B(f64);
 
     serializes_to(A(42), "42");
-    serializes_to(B(42.), "42");
+    serializes_to(B(42.), "42.0");
 }
 
 #[test]
@@ -168,7 +347,7 @@ fn serializes_seq() {
             Val::Bool(true),
             Val::String("hello".to_owned()),
         ],
-        "[42,true,\"hello\"]",
+        "[42.0,true,\"hello\"]",
     )
 }
 
@@ -185,8 +364,14 @@ fn serializes_tuple_struct() {
     #[derive(Serialize, PartialEq, Debug)]
     struct B(f64, i32);
 
-    serializes_to(A(1, 2.), "[1,2]");
-    serializes_to(B(1., 2), "[1,2]");
+    serializes_to(A(1, 2.), "[1,2.0]");
+    serializes_to(B(1., 2), "[1.0,2]");
+}
+
+#[test]
+fn serializes_nested_empty_seq() {
+    let v: Vec<Vec<i32>> = vec![vec![], vec![1], vec![]];
+    serializes_to(v, "[[],[1],[]]");
 }
 
 #[test]
@@ -200,6 +385,71 @@ fn serializes_map() {
     serializes_to(outer, "{\"a\":{\"b\":true}}");
 }
 
+#[test]
+fn serializes_map_with_non_string_keys() {
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Debug)]
+    enum Key {
+        A,
+        B,
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Debug)]
+    struct Id(u8);
+
+    let mut numbers = HashMap::new();
+    numbers.insert(1u32, "one".to_owned());
+    numbers.insert(u32::MAX, "max".to_owned());
+    let s = json5::to_string(&numbers).unwrap();
+    assert_eq!(json5::from_str::<HashMap<u32, String>>(&s), Ok(numbers));
+
+    let negative: BTreeMap<i64, ()> = vec![(-1, ()), (2, ())].into_iter().collect();
+    serializes_to(negative, "{\"-1\":null,\"2\":null}");
+    let flags: BTreeMap<bool, u8> = vec![(false, 0), (true, 1)].into_iter().collect();
+    serializes_to(flags, "{\"false\":0,\"true\":1}");
+    let chars: BTreeMap<char, u8> = vec![('"', 0)].into_iter().collect();
+    serializes_to(chars, "{\"\\\"\":0}");
+
+    // Floats can't be the keys of a `HashMap` or `BTreeMap`, but can be of a map serialized by
+    // hand.
+    struct Floats(Vec<(f64, i32)>);
+    impl serde::Serialize for Floats {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeMap;
+
+            let mut map = serializer.serialize_map(Some(self.0.len()))?;
+            for (k, v) in &self.0 {
+                map.serialize_entry(k, v)?;
+            }
+            map.end()
+        }
+    }
+    let floats = Floats(vec![(1.5, 0), (f64::NAN, 1), (f64::NEG_INFINITY, 2)]);
+    assert_eq!(
+        json5::to_string(&floats).unwrap(),
+        "{\"1.5\":0,\"NaN\":1,\"-Infinity\":2}"
+    );
+
+    let variants: BTreeMap<Key, u8> = vec![(Key::A, 0), (Key::B, 1)].into_iter().collect();
+    let s = json5::to_string(&variants).unwrap();
+    assert_eq!(s, "{\"A\":0,\"B\":1}");
+    assert_eq!(json5::from_str::<BTreeMap<Key, u8>>(&s), Ok(variants));
+    let ids: BTreeMap<Id, u8> = vec![(Id(7), 0)].into_iter().collect();
+    serializes_to(ids, "{\"7\":0}");
+
+    let tuples: BTreeMap<(i32, i32), u8> = vec![((1, 2), 0)].into_iter().collect();
+    assert_eq!(
+        json5::to_string(&tuples),
+        Err(json5::Error::KeyMustBeString)
+    );
+    let options: BTreeMap<Option<i32>, u8> = vec![(None, 0)].into_iter().collect();
+    assert_eq!(
+        json5::to_string(&options),
+        Err(json5::Error::KeyMustBeString)
+    );
+}
+
 #[test]
 fn serializes_struct() {
     #[derive(Serialize, PartialEq, Debug)]
@@ -227,3 +477,416 @@ fn serializes_enum() {
     serializes_to(E::C(3, 5), "{\"C\":[3,5]}");
     serializes_to(E::D { a: 7, b: 11 }, "{\"D\":{\"a\":7,\"b\":11}}");
 }
+
+#[test]
+fn round_trips_enum() {
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    enum E {
+        A,
+        B(i32),
+        C(i32, i32),
+        D {
+            a: i32,
+            b: i32,
+        },
+        #[serde(rename = "with \"quotes\"")]
+        Renamed,
+        Nested(Box<E>),
+    }
+
+    let all = vec![
+        E::A,
+        E::B(2),
+        E::C(3, 5),
+        E::D { a: 7, b: 11 },
+        E::Renamed,
+        E::Nested(Box::new(E::D { a: 1, b: 2 })),
+    ];
+    for value in all {
+        for s in &[
+            json5::to_string(&value).unwrap(),
+            json5::to_string_pretty(&value, &json5::PrettyConfig::new()).unwrap(),
+        ] {
+            assert_eq!(json5::from_str::<E>(s).as_ref(), Ok(&value), "{}", s);
+        }
+    }
+    serializes_to(E::Renamed, r#""with \"quotes\"""#);
+    serializes_to(
+        E::Nested(Box::new(E::C(1, 2))),
+        "{\"Nested\":{\"C\":[1,2]}}",
+    );
+    let s = json5::to_string(&vec![E::A, E::B(1)]).unwrap();
+    assert_eq!(json5::from_str::<Vec<E>>(&s), Ok(vec![E::A, E::B(1)]));
+}
+
+#[test]
+fn round_trips_struct() {
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct S {
+        a: i32,
+        b: f64,
+        c: String,
+        d: Vec<bool>,
+    }
+
+    let s = S {
+        a: 1,
+        b: 2.5,
+        c: "quote \" and \\ backslash".to_owned(),
+        d: vec![true, false],
+    };
+    let serialized = json5::to_string(&s).unwrap();
+    assert_eq!(json5::from_str::<S>(&serialized), Ok(s));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn serializes_to_writer() {
+    #[derive(Serialize, PartialEq, Debug)]
+    struct S {
+        a: Vec<i32>,
+        b: Option<String>,
+    }
+
+    let mut buffer = Vec::new();
+    json5::to_writer(
+        &mut buffer,
+        &S {
+            a: vec![1, 2],
+            b: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "{\"a\":[1,2],\"b\":null}"
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn serializes_to_writer_with_io_error() {
+    struct FailingWriter;
+
+    impl io::Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    assert_eq!(
+        json5::to_writer(FailingWriter, &vec![1, 2, 3]),
+        Err(json5::Error::Io("disk full".to_owned())),
+    );
+}
+
+#[test]
+fn serializes_pretty() {
+    #[derive(Serialize, PartialEq, Debug)]
+    struct S {
+        a: Vec<i32>,
+        b: HashMap<String, i32>,
+        c: Vec<i32>,
+    }
+
+    let s = S {
+        a: vec![1, 2],
+        b: HashMap::new(),
+        c: vec![],
+    };
+    let config = json5::PrettyConfig::default();
+    assert_eq!(
+        json5::to_string_pretty(&s, &config),
+        Ok("{\n  \"a\": [\n    1,\n    2\n  ],\n  \"b\": {},\n  \"c\": []\n}".to_owned()),
+    );
+    assert_eq!(json5::to_string_pretty(&1, &config), Ok("1".to_owned()));
+}
+
+#[test]
+fn serializes_pretty_with_config() {
+    let config = json5::PrettyConfig::new()
+        .indent("\t")
+        .newline("\r\n")
+        .trailing_commas(true);
+    assert_eq!(
+        json5::to_string_pretty(&vec![vec![1], vec![]], &config),
+        Ok("[\r\n\t[\r\n\t\t1,\r\n\t],\r\n\t[],\r\n]".to_owned()),
+    );
+}
+
+#[test]
+fn serializes_pretty_with_trailing_commas() {
+    #[derive(Serialize, PartialEq, Debug)]
+    struct S {
+        tags: Vec<&'static str>,
+    }
+
+    let config = json5::PrettyConfig::new().trailing_commas(true);
+    let before = json5::to_string_pretty(&S { tags: vec!["a"] }, &config).unwrap();
+    let after = json5::to_string_pretty(
+        &S {
+            tags: vec!["a", "b"],
+        },
+        &config,
+    )
+    .unwrap();
+    assert_eq!(before, "{\n  \"tags\": [\n    \"a\",\n  ],\n}");
+
+    // Adding an element only adds a line, leaving every existing line untouched.
+    let before: Vec<&str> = before.lines().collect();
+    let mut added: Vec<&str> = after.lines().collect();
+    assert_eq!(added.remove(3), "    \"b\",");
+    assert_eq!(added, before);
+
+    assert_eq!(
+        json5::to_string(&S { tags: vec!["a"] }),
+        Ok("{\"tags\":[\"a\"]}".to_owned())
+    );
+    assert_eq!(
+        json5::to_string_pretty(&S { tags: vec![] }, &config),
+        Ok("{\n  \"tags\": [],\n}".to_owned())
+    );
+}
+
+#[test]
+fn serializes_pretty_enum() {
+    #[derive(Serialize, PartialEq, Debug)]
+    enum E {
+        A,
+        B(i32),
+        C(i32, i32),
+        D { a: i32 },
+    }
+
+    let config = json5::PrettyConfig::default();
+    assert_eq!(
+        json5::to_string_pretty(&E::A, &config),
+        Ok("\"A\"".to_owned())
+    );
+    assert_eq!(
+        json5::to_string_pretty(&E::B(2), &config),
+        Ok("{\n  \"B\": 2\n}".to_owned())
+    );
+    assert_eq!(
+        json5::to_string_pretty(&E::C(3, 5), &config),
+        Ok("{\n  \"C\": [\n    3,\n    5\n  ]\n}".to_owned())
+    );
+    assert_eq!(
+        json5::to_string_pretty(&E::D { a: 7 }, &config),
+        Ok("{\n  \"D\": {\n    \"a\": 7\n  }\n}".to_owned())
+    );
+}
+
+#[test]
+fn serializes_hex_integers() {
+    let options = json5::SerializeOptions::new().number_format(json5::NumberFormat::Hex);
+    assert_eq!(options.to_string(&255u8), Ok("0xFF".to_owned()));
+    assert_eq!(options.to_string(&0u32), Ok("0x0".to_owned()));
+    assert_eq!(options.to_string(&-1i8), Ok("-0x1".to_owned()));
+    assert_eq!(
+        options.to_string(&i64::MIN),
+        Ok("-0x8000000000000000".to_owned())
+    );
+    assert_eq!(
+        options.to_string(&u64::MAX),
+        Ok("0xFFFFFFFFFFFFFFFF".to_owned())
+    );
+    assert_eq!(
+        options.to_string(&vec![1.5, f64::NAN, f64::INFINITY]),
+        Ok("[1.5,NaN,Infinity]".to_owned())
+    );
+
+    let mut flags = HashMap::new();
+    flags.insert("mask".to_owned(), 0xdecafu32);
+    let s = options.to_string(&flags).unwrap();
+    assert_eq!(s, "{\"mask\":0xDECAF}");
+    assert_eq!(json5::from_str::<HashMap<String, u32>>(&s), Ok(flags));
+    assert_eq!(json5::from_str::<i64>("-0x8000000000000000"), Ok(i64::MIN));
+
+    assert_eq!(
+        json5::SerializeOptions::new().to_string(&255u8),
+        Ok("255".to_owned())
+    );
+}
+
+#[test]
+fn serializes_with_none_skipped() {
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    enum E {
+        V { x: Option<i32>, y: Option<i32> },
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct S {
+        a: Option<i32>,
+        b: Option<String>,
+        c: Option<Option<bool>>,
+        d: Vec<Option<i32>>,
+        e: Option<E>,
+        f: (),
+    }
+
+    let s = S {
+        a: None,
+        b: Some("x".to_owned()),
+        c: Some(None),
+        d: vec![None, Some(1)],
+        e: Some(E::V {
+            x: None,
+            y: Some(2),
+        }),
+        f: (),
+    };
+    let options = json5::SerializeOptions::new().skip_none(true);
+    assert_eq!(
+        options.to_string(&s),
+        Ok("{\"b\":\"x\",\"c\":null,\"d\":[null,1],\"e\":{\"V\":{\"y\":2}},\"f\":null}".to_owned())
+    );
+    assert_eq!(
+        json5::to_string(&s),
+        Ok("{\"a\":null,\"b\":\"x\",\"c\":null,\"d\":[null,1],\"e\":{\"V\":{\"x\":null,\"y\":2}},\"f\":null}".to_owned())
+    );
+    assert_eq!(
+        options
+            .clone()
+            .pretty(json5::PrettyConfig::new())
+            .to_string(&E::V { x: None, y: None }),
+        Ok("{\n  \"V\": {}\n}".to_owned())
+    );
+
+    let mut map = HashMap::new();
+    map.insert("only".to_owned(), None::<i32>);
+    assert_eq!(options.to_string(&map), Ok("{}".to_owned()));
+}
+
+#[test]
+fn serializes_floats_shortest() {
+    serializes_to(0.1, "0.1");
+    serializes_to(1e100, "1e100");
+    serializes_to(-1.5e-7, "-1.5e-7");
+    serializes_to(1e15, "1000000000000000.0");
+    serializes_to(1e16, "1e16");
+    serializes_to(0.00001, "0.00001");
+    serializes_to(f64::MAX, "1.7976931348623157e308");
+    serializes_to(5e-324, "5e-324");
+    serializes_to(1e30f32, "1e30");
+    serializes_to(0.3f32, "0.3");
+}
+
+#[test]
+fn round_trips_random_floats() {
+    // xorshift64, so the test is the same every run.
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    for _ in 0..10_000 {
+        let x = f64::from_bits(next());
+        if x.is_finite() {
+            let s = json5::to_string(&x).unwrap();
+            let back: f64 = json5::from_str(&s).unwrap();
+            assert_eq!(back.to_bits(), x.to_bits(), "{}", s);
+        }
+        let y = f32::from_bits(next() as u32);
+        if y.is_finite() {
+            let s = json5::to_string(&y).unwrap();
+            let back: f32 = json5::from_str(&s).unwrap();
+            assert_eq!(back.to_bits(), y.to_bits(), "{}", s);
+        }
+    }
+}
+
+#[test]
+fn serializes_ascii_only() {
+    let options = json5::SerializeOptions::new().ascii_only(true);
+    assert_eq!(options.to_string(&"é"), Ok("\"\\u00e9\"".to_owned()));
+    assert_eq!(json5::to_string(&"é"), Ok("\"é\"".to_owned()));
+    assert_eq!(
+        options.to_string(&'😀'),
+        Ok("\"\\ud83d\\ude00\"".to_owned())
+    );
+    assert_eq!(
+        options.to_string(&"tab\there \u{7f}"),
+        Ok("\"tab\\there \\u007f\"".to_owned())
+    );
+
+    let mut map = HashMap::new();
+    map.insert("naïve", "Ω");
+    assert_eq!(
+        options.to_string(&map),
+        Ok("{\"na\\u00efve\":\"\\u03a9\"}".to_owned())
+    );
+
+    // The escapes read back as the original characters.
+    let s = "ascii, é, 中文, 😀";
+    assert_eq!(
+        json5::from_str::<String>(&options.to_string(&s).unwrap()),
+        Ok(s.to_owned())
+    );
+}
+
+#[test]
+fn serializes_json_compatible() {
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    struct S {
+        #[serde(rename = "key with spaces")]
+        spaced: &'static str,
+        ints: Vec<i64>,
+        floats: Vec<f64>,
+        nested: BTreeMap<&'static str, Vec<()>>,
+    }
+
+    let s = S {
+        spaced: "\u{b}\0\x001\u{1}/\"\\\u{2028}é",
+        ints: vec![255, -16, 0],
+        floats: vec![f64::NAN, f64::INFINITY, f64::NEG_INFINITY, -0.0, 1e300, 0.5],
+        nested: vec![("a", vec![(), ()]), ("b", vec![])]
+            .into_iter()
+            .collect(),
+    };
+    let compatible = json5::SerializeOptions::new()
+        .number_format(json5::NumberFormat::Hex)
+        .json_compatible(true);
+    let configs = [
+        compatible.clone(),
+        compatible
+            .clone()
+            .pretty(json5::PrettyConfig::new().trailing_commas(true)),
+        compatible.clone().ascii_only(true),
+    ];
+    for options in &configs {
+        let output = options.to_string(&s).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["key with spaces"], s.spaced, "{}", output);
+        assert_eq!(json["ints"], serde_json::json!([255, -16, 0]));
+        assert_eq!(
+            json["floats"],
+            serde_json::json!([null, null, null, -0.0, 1e300, 0.5])
+        );
+        assert_eq!(
+            json["nested"],
+            serde_json::json!({ "a": [null, null], "b": [] })
+        );
+    }
+    assert_eq!(
+        compatible.to_string(&"\u{b}\0"),
+        Ok(r#""\u000b\u0000""#.to_owned())
+    );
+
+    // Without it, the output uses the JSON5 extensions and isn't JSON.
+    let json5_only = json5::SerializeOptions::new()
+        .number_format(json5::NumberFormat::Hex)
+        .pretty(json5::PrettyConfig::new().trailing_commas(true));
+    let output = json5_only.to_string(&s).unwrap();
+    assert!(serde_json::from_str::<serde_json::Value>(&output).is_err());
+    assert_eq!(json5::to_string(&"\u{b}\0"), Ok(r#""\v\0""#.to_owned()));
+}
@@ -0,0 +1,39 @@
+#![cfg(feature = "serde_json")]
+
+use serde_json::json;
+
+#[test]
+fn converts_to_serde_json() {
+    assert_eq!(json5::to_serde_json("null"), Ok(json!(null)));
+    assert_eq!(
+        json5::to_serde_json("[1, -1, 1.5, 0xFF, 'a', true]"),
+        Ok(json!([1, -1, 1.5, 255, "a", true]))
+    );
+    assert_eq!(
+        json5::to_serde_json("{ a: { b: [] }, 'c': {}, }"),
+        Ok(json!({ "a": { "b": [] }, "c": {} }))
+    );
+
+    let value = json5::to_serde_json("[1, 1.0]").unwrap();
+    assert!(value[0].is_u64());
+    assert!(value[1].is_f64());
+}
+
+#[test]
+fn converts_to_serde_json_with_error() {
+    for input in &["NaN", "[Infinity]", "{ a: { b: -Infinity } }"] {
+        assert!(json5::to_serde_json(input).is_err(), "{}", input);
+    }
+    assert_eq!(
+        json5::to_serde_json("[1, -NaN]").unwrap_err().to_string(),
+        "NaN can't be represented in JSON"
+    );
+    assert_eq!(
+        json5::to_serde_json("-Infinity").unwrap_err().to_string(),
+        "-Infinity can't be represented in JSON"
+    );
+    assert!(json5::to_serde_json("{ a: }")
+        .unwrap_err()
+        .location()
+        .is_some());
+}
@@ -0,0 +1,586 @@
+use json5::{Map, Number, Value};
+
+mod common;
+
+use crate::common::deserializes_to;
+
+#[test]
+fn deserializes_value() {
+    deserializes_to("null", Value::Null);
+    deserializes_to("true", Value::Bool(true));
+    deserializes_to("42", Value::Number(42.into()));
+    deserializes_to("-0x2A", Value::Number((-42).into()));
+    deserializes_to("4.2", Value::Number(4.2.into()));
+    deserializes_to("'hello'", Value::String("hello".to_owned()));
+    deserializes_to(
+        "[1, 'a', [], {}]",
+        Value::Array(vec![
+            Value::Number(1.into()),
+            Value::String("a".to_owned()),
+            Value::Array(vec![]),
+            Value::Object(Map::new()),
+        ]),
+    );
+
+    let mut map = Map::new();
+    map.insert("a".to_owned(), Value::Null);
+    map.insert("b".to_owned(), Value::Array(vec![Value::Bool(false)]));
+    deserializes_to("{ a: null, 'b': [false,], }", Value::Object(map));
+}
+
+#[test]
+fn deserializes_value_nan() {
+    match json5::from_str::<Value>("NaN") {
+        Ok(value) => assert!(value.as_f64().unwrap().is_nan()),
+        Err(err) => panic!("{}", err),
+    }
+}
+
+#[test]
+fn value_accessors() {
+    let value: Value = json5::from_str("{ a: 'x', b: 2, c: true, d: [null] }").unwrap();
+
+    assert_eq!(value.get("a").and_then(Value::as_str), Some("x"));
+    assert_eq!(value["b"].as_f64(), Some(2.));
+    assert_eq!(value["c"].as_bool(), Some(true));
+    assert!(value["d"][0].is_null());
+    assert_eq!(value["d"].as_array().map(Vec::len), Some(1));
+    assert_eq!(value.as_object().map(Map::len), Some(4));
+
+    assert_eq!(value.get("missing"), None);
+    assert_eq!(value["missing"], Value::Null);
+    assert_eq!(value["d"][1], Value::Null);
+    assert_eq!(value["a"]["nested"], Value::Null);
+    assert_eq!(value["a"].as_f64(), None);
+}
+
+#[test]
+#[cfg(not(feature = "preserve_order"))]
+fn value_object_keys_are_sorted() {
+    let value: Value = json5::from_str("{ z: 1, a: 2, m: 3 }").unwrap();
+    let keys: Vec<&str> = value.as_object().unwrap().keys().collect();
+    assert_eq!(keys, vec!["a", "m", "z"]);
+}
+
+#[test]
+#[cfg(feature = "preserve_order")]
+fn value_object_keys_preserve_order() {
+    let value: Value = json5::from_str("{ z: 1, a: 2, m: 3 }").unwrap();
+    let keys: Vec<&str> = value.as_object().unwrap().keys().collect();
+    assert_eq!(keys, vec!["z", "a", "m"]);
+}
+
+#[test]
+fn value_numbers() {
+    let value: Value = json5::from_str("[1, 1.0, 0xFF, 1e3, -1, NaN]").unwrap();
+
+    assert_eq!(value[0], Value::Number(Number::from(1)));
+    assert_eq!(value[0].as_i64(), Some(1));
+    assert_eq!(value[0].as_number().map(Number::is_f64), Some(false));
+
+    assert_eq!(value[1], Value::Number(Number::from(1.)));
+    assert_eq!(value[1].as_i64(), None);
+    assert_eq!(value[1].as_f64(), Some(1.));
+    assert_ne!(value[0], value[1]);
+
+    assert_eq!(value[2].as_u64(), Some(255));
+    assert_eq!(value[3].as_i64(), None);
+    assert_eq!(value[3].as_f64(), Some(1000.));
+    assert_eq!(value[4].as_i64(), Some(-1));
+    assert_eq!(value[4].as_u64(), None);
+
+    assert!(value[5].as_f64().unwrap().is_nan());
+
+    let big = Number::from(u64::MAX);
+    assert!(big.is_u64() && !big.is_i64());
+    assert_eq!(big.as_i64(), None);
+
+    let numbers: Vec<String> = value
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_number().unwrap().to_string())
+        .collect();
+    assert_eq!(numbers, vec!["1", "1.0", "255", "1000.0", "-1", "NaN"]);
+}
+
+#[test]
+fn value_object_iteration() {
+    let mut value: Value = json5::from_str("{ b: 'x', a: ['y', { c: 'z' }], d: 1 }").unwrap();
+
+    fn strings<'a>(value: &'a Value, out: &mut Vec<&'a str>) {
+        match *value {
+            Value::String(ref s) => out.push(s),
+            Value::Array(ref a) => a.iter().for_each(|v| strings(v, out)),
+            Value::Object(ref o) => o.values().for_each(|v| strings(v, out)),
+            _ => {}
+        }
+    }
+    let mut found = Vec::new();
+    strings(&value, &mut found);
+    let object = value.as_object().unwrap();
+
+    #[cfg(not(feature = "preserve_order"))]
+    {
+        assert_eq!(found, vec!["y", "z", "x"]);
+        assert_eq!(object.keys().collect::<Vec<_>>(), vec!["a", "b", "d"]);
+    }
+    #[cfg(feature = "preserve_order")]
+    {
+        assert_eq!(found, vec!["x", "y", "z"]);
+        assert_eq!(object.keys().collect::<Vec<_>>(), vec!["b", "a", "d"]);
+    }
+
+    assert_eq!(object.iter().len(), 3);
+    assert!(object.entries().eq(object.iter()));
+    assert_eq!(
+        object.iter().map(|(k, _)| k).collect::<Vec<_>>(),
+        object.keys().collect::<Vec<_>>()
+    );
+    assert_eq!(object.into_iter().count(), 3);
+    assert!(object.contains_key("d") && !object.contains_key("e"));
+
+    if let Value::Object(ref mut object) = value {
+        for (_, v) in object.iter_mut() {
+            if v.as_i64() == Some(1) {
+                *v = Value::Null;
+            }
+        }
+        assert_eq!(object.remove("b"), Some(Value::String("x".to_owned())));
+        assert_eq!(object.remove("b"), None);
+    }
+    assert!(value["d"].is_null());
+
+    let owned: Vec<(String, Value)> = match value {
+        Value::Object(object) => object.into_iter().collect(),
+        _ => unreachable!(),
+    };
+    assert_eq!(owned.len(), 2);
+
+    let map: Map = owned.into_iter().collect();
+    assert_eq!(map.len(), 2);
+    assert_eq!(format!("{:?}", Map::new()), "{}");
+}
+
+#[test]
+fn value_object_repeated_keys() {
+    // Keys long enough not to be stored inline with the `smol_str` feature, so that the later
+    // objects share the first one's keys.
+    let long = "a_key_that_is_longer_than_23_bytes";
+    let input = format!(
+        "[{{ {0}: 1, b: 2 }}, {{ {0}: 3, b: 4 }}, {{ '{0}': {{ {0}: 5 }} }}]",
+        long
+    );
+    let mut value: Value = json5::from_str(&input).unwrap();
+    assert_eq!(value[0][long].as_i64(), Some(1));
+    assert_eq!(value[1][long].as_i64(), Some(3));
+    assert_eq!(value[2][long][long].as_i64(), Some(5));
+    assert_eq!(
+        json5::from_str::<Value>(&json5::to_string(&value).unwrap()).unwrap(),
+        value
+    );
+
+    match value.pointer_mut("/1") {
+        Some(Value::Object(object)) => {
+            let old = object.insert(long.to_owned(), Value::Null);
+            assert_eq!(old.and_then(|v| v.as_i64()), Some(3));
+            assert_eq!(object.keys().collect::<Vec<_>>(), vec![long, "b"]);
+            assert_eq!(object.remove(long), Some(Value::Null));
+        }
+        _ => unreachable!(),
+    }
+    assert_eq!(value[0].as_object().unwrap().len(), 2);
+}
+
+#[test]
+fn value_equality() {
+    let parse = |s| json5::from_str::<Value>(s).unwrap();
+
+    let a = parse("{ a: [1, { b: 'x', c: null }], d: true } // comment");
+    let b = parse("{'d': true, \"a\": [0x1, { c: null, b: \"x\" }]}");
+    assert_eq!(a, b);
+    assert_eq!(a.clone(), a);
+    assert_ne!(a, parse("{ a: [1, { b: 'x', c: false }], d: true }"));
+    assert_ne!(a, parse("{ a: [1, { b: 'x', c: null }] }"));
+    assert_ne!(a, parse("{ a: [{ b: 'x', c: null }, 1], d: true }"));
+
+    // Numbers only compare equal to numbers stored the same way, and `NaN` not even to itself.
+    assert_ne!(parse("[5]"), parse("[5.0]"));
+    assert_eq!(parse("[-0.0]"), parse("[0.0]"));
+    let nan = parse("{ a: [NaN] }");
+    assert_ne!(nan, nan.clone());
+}
+
+#[test]
+fn value_total_cmp() {
+    use std::cmp::Ordering::{Equal, Greater, Less};
+
+    let parse = |s| json5::from_str::<Value>(s).unwrap();
+
+    let sorted = parse(
+        "[
+            null, false, true,
+            -Infinity, -18446744073709551616.5, -9223372036854775808, -1.5, -1, -0.0, 0, 0.0, 0.5,
+            9007199254740992, 9007199254740992.0, 9007199254740993, 9223372036854775807,
+            18446744073709551616.0, Infinity, NaN,
+            '', 'a', 'b',
+            [], [null], [1, 2], [1, 2, 3], [2],
+            {}, { a: 1 }, { a: 1, b: 1 }, { a: 2 }, { b: 0 },
+        ]",
+    );
+    let sorted = sorted.as_array().unwrap();
+    for (i, a) in sorted.iter().enumerate() {
+        for (j, b) in sorted.iter().enumerate() {
+            assert_eq!(a.total_cmp(b), i.cmp(&j), "{:?} and {:?}", a, b);
+        }
+    }
+
+    let max = Number::from(u64::MAX);
+    assert_eq!(max.total_cmp(&Number::from(i64::MAX)), Greater);
+    assert_eq!(
+        max.total_cmp(&Number::from(18446744073709549568.0)),
+        Greater
+    );
+    assert_eq!(max.total_cmp(&Number::from(18446744073709551616.0)), Less);
+
+    assert_eq!(parse("[NaN]").total_cmp(&parse("[-NaN]")), Equal);
+    assert_eq!(
+        parse("{ a: [1, { b: 'x', c: null }] }")
+            .total_cmp(&parse("{ a: [1, { c: null, b: 'x' }] }")),
+        Equal
+    );
+    assert_eq!(parse("{ a: 1, c: 1 }").total_cmp(&parse("{ b: 1 }")), Less);
+    assert_eq!(
+        parse("{ b: 1 }").total_cmp(&parse("{ c: 1, a: 1 }")),
+        Greater
+    );
+}
+
+#[test]
+fn json5_macro() {
+    use json5::json5;
+
+    assert_eq!(json5!(null), Value::Null);
+    assert_eq!(json5!(true), Value::Bool(true));
+    assert_eq!(json5!(-1.5), Value::Number((-1.5).into()));
+    assert_eq!(json5!("a"), Value::String("a".to_owned()));
+    assert_eq!(json5!([]), Value::Array(vec![]));
+    assert_eq!(json5!({}), Value::Object(Map::new()));
+
+    let x = 3;
+    let name = "c";
+    let value = json5!({
+        a: 1,
+        'b': [null, true, false, [x, x + 1,], {},],
+        "quoted key": { nested: { deeper: [] } },
+        (name): Some("some"),
+        (format!("{}{}", name, name)): None::<i32>,
+        2: vec![1u8, 2],
+    });
+    assert_eq!(
+        value,
+        json5::from_str(
+            "{
+                a: 1,
+                b: [null, true, false, [3, 4], {}],
+                'quoted key': { nested: { deeper: [] } },
+                c: 'some',
+                cc: null,
+                '2': [1, 2],
+            }"
+        )
+        .unwrap()
+    );
+    assert_eq!(json5!([1, "two", { three: 3 }])[2]["three"], Value::from(3));
+}
+
+#[test]
+fn deserializes_from_value() {
+    use serde_derive::Deserialize;
+    use std::collections::HashMap;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    enum E {
+        Unit,
+        Newtype(i8),
+        Tuple(u8, u8),
+        Struct { a: Option<bool> },
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S {
+        name: String,
+        small: u8,
+        truncated: i32,
+        float: f32,
+        tuple: (i64, char),
+        enums: Vec<E>,
+        #[serde(default)]
+        missing: Option<Box<S>>,
+        map: HashMap<String, ()>,
+    }
+
+    fn from_text<T: serde::de::DeserializeOwned>(s: &str) -> json5::Result<T> {
+        json5::from_value(json5::from_str(s).unwrap())
+    }
+
+    let input = "{
+        name: 'n', small: 255, truncated: -4.9, float: 1, tuple: [-1, 'c'],
+        enums: ['Unit', { Newtype: -8 }, { Tuple: [1, 2] }, { Struct: { a: null } }],
+        map: { x: null },
+    }";
+    assert_eq!(
+        from_text(input),
+        Ok(S {
+            name: "n".to_owned(),
+            small: 255,
+            truncated: -4,
+            float: 1.,
+            tuple: (-1, 'c'),
+            enums: vec![
+                E::Unit,
+                E::Newtype(-8),
+                E::Tuple(1, 2),
+                E::Struct { a: None }
+            ],
+            missing: None,
+            map: HashMap::from([("x".to_owned(), ())]),
+        })
+    );
+    assert_eq!(from_text::<Value>(input), json5::from_str(input));
+
+    assert!(from_text::<u8>("256").is_err());
+    assert_eq!(
+        from_text::<i32>("-Infinity"),
+        Err(json5::Error::ExpectedInteger("-Infinity".to_owned()))
+    );
+    assert_eq!(
+        from_text::<(i32, i32)>("[1, 2, 3]"),
+        Err(json5::Error::WrongTupleLength {
+            expected: 2,
+            found: 3
+        })
+    );
+    assert_eq!(
+        from_text::<E>("[]").unwrap_err().to_string(),
+        "expected a string or an object with a single entry, found an array"
+    );
+    assert_eq!(
+        from_text::<E>("{ Tuple: 1 }").unwrap_err().to_string(),
+        "expected an array, found a number"
+    );
+    assert!(from_text::<[i32; 1]>("[1, 2]").is_err());
+}
+
+#[test]
+fn deserializes_from_borrowed_value() {
+    use serde::de::{Deserialize, IntoDeserializer};
+    use serde_derive::Deserialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    enum E<'a> {
+        Unit,
+        Newtype(&'a str),
+        Tuple(u8, u8),
+        Struct { a: Option<bool> },
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct S<'a> {
+        name: &'a str,
+        small: u8,
+        truncated: i32,
+        tuple: (i64, char),
+        #[serde(borrow)]
+        enums: Vec<E<'a>>,
+        #[serde(borrow)]
+        map: BTreeMap<&'a str, &'a str>,
+    }
+
+    let value: Value = json5::from_str(
+        "{
+            name: 'n', small: 255, truncated: -4.9, tuple: [-1, 'c'],
+            enums: ['Unit', { Newtype: 'x' }, { Tuple: [1, 2] }, { Struct: { a: null } }],
+            map: { k: 'v' },
+        }",
+    )
+    .unwrap();
+    assert_eq!(
+        S::deserialize((&value).into_deserializer()),
+        Ok(S {
+            name: "n",
+            small: 255,
+            truncated: -4,
+            tuple: (-1, 'c'),
+            enums: vec![
+                E::Unit,
+                E::Newtype("x"),
+                E::Tuple(1, 2),
+                E::Struct { a: None }
+            ],
+            map: BTreeMap::from([("k", "v")]),
+        })
+    );
+    assert_eq!(Value::deserialize(&value), Ok(value.clone()));
+    assert_eq!(
+        <&str>::deserialize((&value["enums"][0]).into_deserializer()),
+        Ok("Unit")
+    );
+
+    // Integers and floats reach the visitor as such.
+    let numbers: Value = json5::from_str("[1, -1, 1.0, 0xFF, 1e3]").unwrap();
+    let numbers = serde_json::Value::deserialize(&numbers).unwrap();
+    assert!(numbers[0].is_u64() && numbers[1].is_i64() && numbers[3].is_u64());
+    assert!(numbers[2].is_f64() && numbers[4].is_f64());
+
+    let tuple = Value::from(vec![1, 2, 3]);
+    assert_eq!(
+        <(i32, i32)>::deserialize(&tuple),
+        Err(json5::Error::WrongTupleLength {
+            expected: 2,
+            found: 3
+        })
+    );
+    assert_eq!(
+        E::deserialize(&tuple).unwrap_err().to_string(),
+        "expected a string or an object with a single entry, found an array"
+    );
+
+    // The owned value can be deserialized the same way.
+    assert_eq!(i32::deserialize(Value::from(7).into_deserializer()), Ok(7));
+}
+
+#[test]
+fn merges_values() {
+    use json5::MergeStrategy;
+
+    let parse = |s| json5::from_str::<Value>(s).unwrap();
+    let merged = |base, other, strategy| {
+        let mut base = parse(base);
+        base.merge_with(parse(other), strategy);
+        base
+    };
+
+    let base = "{ a: 1, nested: { b: [1, 2], c: { d: true } }, list: [{ x: 1 }] }";
+    let other = "{ nested: { b: [3], c: { e: null } }, list: [{ y: 2 }], f: 'new' }";
+    assert_eq!(
+        merged(base, other, MergeStrategy::Replace),
+        parse("{ a: 1, nested: { b: [3], c: { d: true, e: null } }, list: [{ y: 2 }], f: 'new' }")
+    );
+    assert_eq!(
+        merged(base, other, MergeStrategy::Concat),
+        parse("{ a: 1, nested: { b: [1, 2, 3], c: { d: true, e: null } }, list: [{ x: 1 }, { y: 2 }], f: 'new' }")
+    );
+    assert_eq!(MergeStrategy::default(), MergeStrategy::Replace);
+
+    // Anything but two objects (or two arrays, when concatenating) is replaced.
+    assert_eq!(
+        merged("{ a: { b: 1 } }", "{ a: null }", MergeStrategy::Replace),
+        parse("{ a: null }")
+    );
+    assert_eq!(
+        merged("{ a: 1 }", "{ a: { b: 1 } }", MergeStrategy::Replace),
+        parse("{ a: { b: 1 } }")
+    );
+    assert_eq!(
+        merged("{ a: 1 }", "[1]", MergeStrategy::Concat),
+        parse("[1]")
+    );
+    assert_eq!(
+        merged("[1]", "{ a: 1 }", MergeStrategy::Concat),
+        parse("{ a: 1 }")
+    );
+    assert_eq!(merged("'x'", "2", MergeStrategy::Replace), parse("2"));
+
+    let mut value = parse("{ a: 1 }");
+    value.merge(parse("{}"));
+    assert_eq!(value, parse("{ a: 1 }"));
+}
+
+#[test]
+fn looks_up_values_by_pointer() {
+    let mut value: Value = json5::from_str(
+        "{
+            servers: [{ host: 'a', port: 80 }, { host: 'b', port: 8080 }],
+            'a/b': 1, 'm~n': 2, '~1': 3, '': 4, ' ': 5,
+            nested: { '': { x: [[true]] } },
+        }",
+    )
+    .unwrap();
+
+    assert_eq!(value.pointer(""), Some(&value));
+    assert_eq!(value.pointer("/servers/1/port"), Some(&Value::from(8080)));
+    assert_eq!(value.pointer("/servers/0/host"), Some(&Value::from("a")));
+    assert_eq!(value.pointer("/a~1b"), Some(&Value::from(1)));
+    assert_eq!(value.pointer("/m~0n"), Some(&Value::from(2)));
+    assert_eq!(value.pointer("/~01"), Some(&Value::from(3)));
+    assert_eq!(value.pointer("/"), Some(&Value::from(4)));
+    assert_eq!(value.pointer("/ "), Some(&Value::from(5)));
+    assert_eq!(value.pointer("/nested//x/0/0"), Some(&Value::Bool(true)));
+
+    for missing in &[
+        "servers",
+        "/missing",
+        "/servers/2",
+        "/servers/-",
+        "/servers/01",
+        "/servers/+1",
+        "/servers/-1",
+        "/servers/x",
+        "/servers/0/port/0",
+        "/a/b",
+        "/servers/0/",
+    ] {
+        assert_eq!(value.pointer(missing), None, "{}", missing);
+    }
+
+    *value.pointer_mut("/servers/0/port").unwrap() = Value::from(443);
+    assert_eq!(value["servers"][0]["port"], Value::from(443));
+    value
+        .pointer_mut("/nested")
+        .unwrap()
+        .merge(json5::json5!({ added: null }));
+    assert_eq!(value.pointer("/nested/added"), Some(&Value::Null));
+    assert!(value.pointer_mut("/servers/5").is_none());
+    assert!(value.pointer_mut("").unwrap().as_object().is_some());
+}
+
+#[test]
+fn round_trips_value() {
+    let input = "{
+        // every kind of value
+        null: null,
+        bools: [true, false],
+        numbers: [0, -1, 0xFF, 18446744073709551615, -9223372036854775808, 1.5, -2.5e-10, 1e300, 5.0, -0.0, 1e20],
+        special: [Infinity, -Infinity],
+        strings: ['', 'single \"quoted\"', \"line\\nbreak\", 'tab\\t', '\\u2028', '😀', 'é'],
+        nested: { a: [[], {}, [{ b: [null] }]], 'quoted key': 'x' },
+    }";
+    let value: Value = json5::from_str(input).unwrap();
+    for text in [
+        json5::to_string(&value).unwrap(),
+        json5::to_string_pretty(&value, &json5::PrettyConfig::new()).unwrap(),
+    ] {
+        let back = json5::from_str::<Value>(&text).unwrap();
+        assert_eq!(back, value, "{}", text);
+        assert_eq!(back["numbers"][8], Value::from(5.0));
+        assert!(back["numbers"][9].as_f64().unwrap().is_sign_negative());
+    }
+
+    // Built rather than parsed, then changed before being written out.
+    let mut value = json5::json5!({ name: "json5", tags: ["a"], count: 1 });
+    if let Some(Value::Array(tags)) = value.pointer_mut("/tags") {
+        tags.push("b".into());
+    }
+    value.merge(json5::json5!({ count: 2 }));
+    let text = json5::to_string(&value).unwrap();
+    assert_eq!(json5::from_str::<Value>(&text).unwrap(), value);
+    assert_eq!(
+        json5::from_str::<Value>(&text).unwrap(),
+        json5::json5!({ name: "json5", tags: ["a", "b"], count: 2 })
+    );
+
+    let nan: Value = json5::from_str(&json5::to_string(&Value::from(f64::NAN)).unwrap()).unwrap();
+    assert!(nan.as_f64().unwrap().is_nan());
+}